@@ -0,0 +1,380 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use glib::clone;
+use glib::subclass::Signal;
+use gst::prelude::*;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+
+/// The interval in which the position is updated while playing.
+const POSITION_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug)]
+    pub(crate) struct AudioPlayer {
+        pub(super) playbin: OnceCell<gst::Element>,
+        pub(super) position_source_id: RefCell<Option<glib::SourceId>>,
+        pub(super) is_playing: Cell<bool>,
+        pub(super) position: Cell<i64>,
+        pub(super) duration: Cell<i64>,
+        pub(super) rate: Cell<f64>,
+        /// The rate of the current pipeline, which can only be changed once it's prerolled.
+        pub(super) applied_rate: Cell<f64>,
+    }
+
+    impl Default for AudioPlayer {
+        fn default() -> Self {
+            Self {
+                playbin: Default::default(),
+                position_source_id: Default::default(),
+                is_playing: Default::default(),
+                position: Default::default(),
+                duration: Default::default(),
+                rate: Cell::new(1.0),
+                applied_rate: Cell::new(1.0),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AudioPlayer {
+        const NAME: &'static str = "AudioPlayer";
+        type Type = super::AudioPlayer;
+    }
+
+    impl ObjectImpl for AudioPlayer {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecBoolean::builder("is-playing")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecInt64::builder("position")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecInt64::builder("duration")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecDouble::builder("rate")
+                        .minimum(0.25)
+                        .maximum(4.0)
+                        .default_value(1.0)
+                        .explicit_notify()
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> =
+                Lazy::new(|| vec![Signal::builder("finished").build()]);
+            SIGNALS.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "rate" => obj.set_rate(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "is-playing" => obj.is_playing().to_value(),
+                "position" => obj.position().to_value(),
+                "duration" => obj.duration().to_value(),
+                "rate" => obj.rate().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            let playbin = gst::ElementFactory::make("playbin")
+                .build()
+                .expect("Missing the playbin element");
+
+            // Keep the pitch of the audio when changing the playback rate
+            match gst::ElementFactory::make("scaletempo").build() {
+                Ok(scaletempo) => playbin.set_property("audio-filter", &scaletempo),
+                Err(e) => log::warn!("Missing the scaletempo element: {e}"),
+            }
+
+            playbin
+                .bus()
+                .unwrap()
+                .add_watch_local(
+                    clone!(@weak obj => @default-return glib::Continue(false), move |_, message| {
+                        obj.handle_bus_message(message);
+                        glib::Continue(true)
+                    }),
+                )
+                .expect("Failed to add a bus watch");
+
+            self.playbin.set(playbin).unwrap();
+        }
+
+        fn dispose(&self) {
+            if let Some(source_id) = self.position_source_id.take() {
+                source_id.remove();
+            }
+
+            let playbin = self.playbin.get().unwrap();
+            if let Err(e) = playbin.set_state(gst::State::Null) {
+                log::warn!("Error stopping the audio player: {e}");
+            }
+            let _ = playbin.bus().unwrap().remove_watch();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A player for audio files, which keeps track of the position and the playback rate.
+    ///
+    /// Positions and durations are in microseconds, like the ones of `gtk::MediaStream`.
+    pub(crate) struct AudioPlayer(ObjectSubclass<imp::AudioPlayer>);
+}
+
+impl Default for AudioPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioPlayer {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn playbin(&self) -> &gst::Element {
+        self.imp().playbin.get().unwrap()
+    }
+
+    /// Loads the audio file at the specified path, stopping the current one.
+    pub(crate) fn set_file(&self, path: &str) {
+        let imp = self.imp();
+        let playbin = self.playbin();
+
+        let uri = match glib::filename_to_uri(path, None) {
+            Ok(uri) => uri,
+            Err(e) => {
+                log::warn!("Invalid audio file path {path}: {e}");
+                return;
+            }
+        };
+
+        self.set_state(gst::State::Null);
+        self.set_is_playing(false);
+        imp.applied_rate.set(1.0);
+        self.set_position(0);
+        self.set_duration(0);
+
+        playbin.set_property("uri", uri);
+
+        // Preroll the file, so that its duration is known and seeking is possible
+        self.set_state(gst::State::Paused);
+    }
+
+    pub(crate) fn play(&self) {
+        if self.set_state(gst::State::Playing) {
+            self.set_is_playing(true);
+        }
+    }
+
+    pub(crate) fn pause(&self) {
+        if self.set_state(gst::State::Paused) {
+            self.set_is_playing(false);
+            self.update_position();
+        }
+    }
+
+    /// Seeks to the specified position, in microseconds.
+    pub(crate) fn seek(&self, position: i64) {
+        let imp = self.imp();
+        let rate = imp.rate.get();
+
+        let result = self.playbin().seek(
+            rate,
+            gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+            gst::SeekType::Set,
+            gst::ClockTime::from_useconds(position.max(0) as u64),
+            gst::SeekType::End,
+            gst::ClockTime::ZERO,
+        );
+
+        match result {
+            Ok(_) => {
+                imp.applied_rate.set(rate);
+                self.set_position(position);
+            }
+            Err(e) => log::warn!("Error seeking in the audio player: {e}"),
+        }
+    }
+
+    /// Connects to the signal emitted when the end of the file was reached.
+    pub(crate) fn connect_finished<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("finished", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            f(&obj);
+            None
+        })
+    }
+
+    pub(crate) fn connect_is_playing_notify<F: Fn(&Self, &glib::ParamSpec) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_notify_local(Some("is-playing"), f)
+    }
+
+    pub(crate) fn connect_position_notify<F: Fn(&Self, &glib::ParamSpec) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_notify_local(Some("position"), f)
+    }
+
+    pub(crate) fn connect_duration_notify<F: Fn(&Self, &glib::ParamSpec) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_notify_local(Some("duration"), f)
+    }
+
+    pub(crate) fn is_playing(&self) -> bool {
+        self.imp().is_playing.get()
+    }
+
+    fn set_is_playing(&self, is_playing: bool) {
+        let imp = self.imp();
+
+        if self.is_playing() == is_playing {
+            return;
+        }
+
+        if is_playing {
+            let source_id = glib::timeout_add_local(
+                POSITION_UPDATE_INTERVAL,
+                clone!(@weak self as obj => @default-return glib::Continue(false), move || {
+                    obj.update_position();
+                    glib::Continue(true)
+                }),
+            );
+            imp.position_source_id.replace(Some(source_id));
+        } else if let Some(source_id) = imp.position_source_id.take() {
+            source_id.remove();
+        }
+
+        imp.is_playing.set(is_playing);
+        self.notify("is-playing");
+    }
+
+    pub(crate) fn position(&self) -> i64 {
+        self.imp().position.get()
+    }
+
+    fn set_position(&self, position: i64) {
+        if self.position() == position {
+            return;
+        }
+        self.imp().position.set(position);
+        self.notify("position");
+    }
+
+    pub(crate) fn duration(&self) -> i64 {
+        self.imp().duration.get()
+    }
+
+    fn set_duration(&self, duration: i64) {
+        if self.duration() == duration {
+            return;
+        }
+        self.imp().duration.set(duration);
+        self.notify("duration");
+    }
+
+    pub(crate) fn rate(&self) -> f64 {
+        self.imp().rate.get()
+    }
+
+    pub(crate) fn set_rate(&self, rate: f64) {
+        if self.rate() == rate {
+            return;
+        }
+        self.imp().rate.set(rate);
+        self.apply_rate();
+        self.notify("rate");
+    }
+
+    /// Changes the rate of the pipeline to the current one, if it's prerolled. Otherwise, this is
+    /// done once prerolling is completed.
+    fn apply_rate(&self) {
+        let (_, state, pending) = self.playbin().state(gst::ClockTime::ZERO);
+        if pending == gst::State::VoidPending && state >= gst::State::Paused {
+            self.update_position();
+            self.seek(self.position());
+        }
+    }
+
+    fn set_state(&self, state: gst::State) -> bool {
+        match self.playbin().set_state(state) {
+            Ok(_) => true,
+            Err(e) => {
+                log::warn!("Error changing the state of the audio player: {e}");
+                false
+            }
+        }
+    }
+
+    fn update_position(&self) {
+        if let Some(position) = self.playbin().query_position::<gst::ClockTime>() {
+            self.set_position(position.useconds() as i64);
+        }
+    }
+
+    fn update_duration(&self) {
+        if let Some(duration) = self.playbin().query_duration::<gst::ClockTime>() {
+            self.set_duration(duration.useconds() as i64);
+        }
+    }
+
+    fn handle_bus_message(&self, message: &gst::Message) {
+        use gst::MessageView;
+
+        match message.view() {
+            MessageView::AsyncDone(_) => {
+                self.update_duration();
+                if self.imp().applied_rate.get() != self.rate() {
+                    self.apply_rate();
+                }
+            }
+            MessageView::DurationChanged(_) => self.update_duration(),
+            MessageView::Eos(_) => {
+                // Rewind, so that the file can be played again
+                self.pause();
+                self.seek(0);
+                self.emit_by_name::<()>("finished", &[]);
+            }
+            MessageView::Error(e) => {
+                log::warn!("Error playing an audio file: {}", e.error());
+                self.set_state(gst::State::Null);
+                self.set_is_playing(false);
+            }
+            _ => {}
+        }
+    }
+}
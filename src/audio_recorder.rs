@@ -0,0 +1,302 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::clone;
+use gst::prelude::*;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use once_cell::sync::Lazy;
+
+use crate::APPLICATION_OPTS;
+
+/// The microphone pipeline, which is replaced by a test signal with `--fake-audio-source`.
+const AUDIO_SOURCE: &str = "autoaudiosrc";
+const FAKE_AUDIO_SOURCE: &str = "audiotestsrc is-live=true wave=ticks volume=0.5";
+/// The interval in which the `level` element reports the loudness of the recording.
+const LEVEL_INTERVAL: gst::ClockTime = gst::ClockTime::from_mseconds(50);
+/// The number of samples of a waveform, as expected by Telegram.
+const WAVEFORM_LENGTH: usize = 100;
+/// The maximum value of a 5-bit waveform sample.
+const MAX_SAMPLE: f64 = 31.0;
+
+/// A finished voice note recording.
+#[derive(Debug, Clone)]
+pub(crate) struct VoiceNoteRecording {
+    /// The path of the recorded OGG/Opus file.
+    pub(crate) path: String,
+    /// The duration in seconds.
+    pub(crate) duration: i32,
+    /// The waveform in the format used by TDLib.
+    pub(crate) waveform: String,
+}
+
+type StopCallback = Box<dyn FnOnce(Option<VoiceNoteRecording>)>;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default)]
+    pub(crate) struct AudioRecorder {
+        pub(super) pipeline: RefCell<Option<gst::Pipeline>>,
+        pub(super) path: RefCell<String>,
+        /// The peak amplitudes of the recording, in the range from 0 to 1.
+        pub(super) levels: RefCell<Vec<f64>>,
+        pub(super) start_time: Cell<i64>,
+        pub(super) stop_callback: RefCell<Option<StopCallback>>,
+    }
+
+    impl std::fmt::Debug for AudioRecorder {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("AudioRecorder")
+                .field("pipeline", &self.pipeline)
+                .field("path", &self.path)
+                .field("start_time", &self.start_time)
+                .finish_non_exhaustive()
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AudioRecorder {
+        const NAME: &'static str = "AudioRecorder";
+        type Type = super::AudioRecorder;
+    }
+
+    impl ObjectImpl for AudioRecorder {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecBoolean::builder("is-recording")
+                    .read_only()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "is-recording" => self.obj().is_recording().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.obj().cancel();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A recorder of voice notes, which encodes the microphone input to OGG/Opus and computes the
+    /// waveform of the recording.
+    pub(crate) struct AudioRecorder(ObjectSubclass<imp::AudioRecorder>);
+}
+
+impl Default for AudioRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioRecorder {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Starts recording to the file at the specified path.
+    pub(crate) fn start(&self, path: &str) -> Result<(), glib::Error> {
+        let imp = self.imp();
+
+        self.cancel();
+
+        let source = if APPLICATION_OPTS.get().unwrap().fake_audio_source {
+            FAKE_AUDIO_SOURCE
+        } else {
+            AUDIO_SOURCE
+        };
+        let pipeline = gst::parse_launch(&format!(
+            "{source} ! audioconvert ! audioresample ! audio/x-raw,channels=1 \
+             ! level interval={} post-messages=true \
+             ! opusenc bitrate=32000 ! oggmux ! filesink name=sink",
+            LEVEL_INTERVAL.nseconds()
+        ))?
+        .downcast::<gst::Pipeline>()
+        .unwrap();
+
+        pipeline
+            .by_name("sink")
+            .unwrap()
+            .set_property("location", path);
+
+        pipeline
+            .bus()
+            .unwrap()
+            .add_watch_local(
+                clone!(@weak self as obj => @default-return glib::Continue(false), move |_, message| {
+                    obj.handle_bus_message(message);
+                    glib::Continue(true)
+                }),
+            )
+            .expect("Failed to add a bus watch");
+
+        if let Err(e) = pipeline.set_state(gst::State::Playing) {
+            let _ = pipeline.bus().unwrap().remove_watch();
+            return Err(glib::Error::new(
+                gst::CoreError::StateChange,
+                &format!("Failed to start recording: {e}"),
+            ));
+        }
+
+        imp.path.replace(path.to_string());
+        imp.levels.borrow_mut().clear();
+        imp.start_time.set(glib::monotonic_time());
+        imp.pipeline.replace(Some(pipeline));
+
+        self.notify("is-recording");
+
+        Ok(())
+    }
+
+    /// Stops recording and calls `f` with the recording, once the file is completely written.
+    pub(crate) fn stop<F: FnOnce(Option<VoiceNoteRecording>) + 'static>(&self, f: F) {
+        let imp = self.imp();
+
+        let Some(pipeline) = imp.pipeline.borrow().clone() else {
+            f(None);
+            return;
+        };
+
+        imp.stop_callback.replace(Some(Box::new(f)));
+
+        // Let the muxer finalize the file before stopping the pipeline
+        pipeline.send_event(gst::event::Eos::new());
+    }
+
+    /// Stops recording and deletes the recorded file.
+    pub(crate) fn cancel(&self) {
+        let imp = self.imp();
+
+        if let Some(callback) = imp.stop_callback.take() {
+            callback(None);
+        }
+
+        if self.stop_pipeline() {
+            if let Err(e) = std::fs::remove_file(&*imp.path.borrow()) {
+                log::warn!("Error removing a cancelled recording: {e}");
+            }
+        }
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.imp().pipeline.borrow().is_some()
+    }
+
+    pub(crate) fn connect_is_recording_notify<F: Fn(&Self, &glib::ParamSpec) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_notify_local(Some("is-recording"), f)
+    }
+
+    /// Returns the duration of the current recording, in seconds.
+    pub(crate) fn duration(&self) -> i32 {
+        if self.is_recording() {
+            ((glib::monotonic_time() - self.imp().start_time.get()) / 1_000_000) as i32
+        } else {
+            0
+        }
+    }
+
+    /// Stops the pipeline, if there's one, and returns whether a recording was stopped.
+    fn stop_pipeline(&self) -> bool {
+        let Some(pipeline) = self.imp().pipeline.take() else {
+            return false;
+        };
+
+        if let Err(e) = pipeline.set_state(gst::State::Null) {
+            log::warn!("Error stopping the audio recorder: {e}");
+        }
+        let _ = pipeline.bus().unwrap().remove_watch();
+
+        self.notify("is-recording");
+
+        true
+    }
+
+    fn handle_bus_message(&self, message: &gst::Message) {
+        use gst::MessageView;
+
+        let imp = self.imp();
+
+        match message.view() {
+            MessageView::Element(element) => {
+                let Some(structure) = element.structure() else {
+                    return;
+                };
+                if structure.name() != "level" {
+                    return;
+                }
+
+                // The peak is in decibels, convert it to an amplitude
+                let peak = structure
+                    .get::<glib::ValueArray>("peak")
+                    .ok()
+                    .and_then(|peaks| peaks.nth(0))
+                    .and_then(|peak| peak.get::<f64>().ok());
+                if let Some(peak) = peak {
+                    imp.levels
+                        .borrow_mut()
+                        .push(10f64.powf(peak / 20.0).clamp(0.0, 1.0));
+                }
+            }
+            MessageView::Eos(_) => {
+                let recording = VoiceNoteRecording {
+                    path: imp.path.borrow().clone(),
+                    duration: self.duration(),
+                    waveform: encode_waveform(&imp.levels.borrow()),
+                };
+
+                self.stop_pipeline();
+
+                if let Some(callback) = imp.stop_callback.take() {
+                    callback(Some(recording));
+                }
+            }
+            MessageView::Error(e) => {
+                log::warn!("Error recording audio: {}", e.error());
+                self.cancel();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Encodes the specified amplitudes as a waveform, which is a list of 5-bit samples packed in
+/// little-endian bit order and encoded in base64.
+fn encode_waveform(levels: &[f64]) -> String {
+    let max_level = levels.iter().copied().fold(0.0, f64::max);
+
+    let mut bytes = vec![0_u8; (WAVEFORM_LENGTH * 5 + 7) / 8];
+    for i in 0..WAVEFORM_LENGTH {
+        // Use the loudest amplitude of the ones covered by the sample
+        let start = i * levels.len() / WAVEFORM_LENGTH;
+        let end = ((i + 1) * levels.len() / WAVEFORM_LENGTH).max(start + 1);
+        let level = levels
+            .get(start..end.min(levels.len()))
+            .map_or(0.0, |levels| levels.iter().copied().fold(0.0, f64::max));
+
+        let sample = if max_level > 0.0 {
+            (level / max_level * MAX_SAMPLE).round() as u16
+        } else {
+            0
+        };
+
+        let bit_offset = i * 5;
+        let byte = bit_offset / 8;
+        let value = sample << (bit_offset % 8);
+        bytes[byte] |= value as u8;
+        if let Some(next) = bytes.get_mut(byte + 1) {
+            *next |= (value >> 8) as u8;
+        }
+    }
+
+    glib::base64_encode(&bytes).into()
+}
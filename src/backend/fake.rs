@@ -0,0 +1,1290 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::future;
+
+use gtk::glib;
+use tdlib::enums;
+use tdlib::enums::Update;
+use tdlib::types;
+use tdlib::types::Error as TdError;
+
+use super::Backend;
+use super::BackendFuture;
+
+/// The reactions offered for every message.
+const AVAILABLE_REACTIONS: &[&str] = &["👍", "👎", "❤", "🔥", "🎉", "😁", "😢"];
+
+/// A backend that answers every request from the messages and files it has seen in the updates
+/// of its session. Requests that modify the history are applied in memory and their effects are
+/// reported back to the session through the update handler, just like TDLib would do.
+#[derive(Default)]
+pub(crate) struct FakeBackend {
+    /// The known messages, ordered by id for every chat.
+    messages: RefCell<HashMap<i64, BTreeMap<i64, types::Message>>>,
+    files: RefCell<HashMap<i32, types::File>>,
+    /// The read state of the incoming messages of every chat.
+    read_inbox: RefCell<HashMap<i64, types::UpdateChatReadInbox>>,
+    my_id: Cell<i64>,
+    update_handler: RefCell<Option<Box<dyn Fn(Update)>>>,
+}
+
+impl fmt::Debug for FakeBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FakeBackend")
+            .field("messages", &self.messages)
+            .field("files", &self.files)
+            .field("read_inbox", &self.read_inbox)
+            .field("my_id", &self.my_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FakeBackend {
+    /// Sets the function that receives the updates generated by this backend.
+    pub(crate) fn set_update_handler<F: Fn(Update) + 'static>(&self, handler: F) {
+        self.update_handler.replace(Some(Box::new(handler)));
+    }
+
+    fn emit(&self, update: Update) {
+        if let Some(handler) = self.update_handler.borrow().as_ref() {
+            handler(update);
+        }
+    }
+
+    fn insert_message(&self, message: types::Message) {
+        self.messages
+            .borrow_mut()
+            .entry(message.chat_id)
+            .or_default()
+            .insert(message.id, message);
+    }
+
+    fn message(&self, chat_id: i64, message_id: i64) -> Option<types::Message> {
+        self.messages
+            .borrow()
+            .get(&chat_id)
+            .and_then(|messages| messages.get(&message_id))
+            .cloned()
+    }
+
+    /// Returns the messages of a chat with the same paging rules as `getChatHistory`: the
+    /// messages are returned from the newest to the oldest, starting after `from_message_id` (or
+    /// from the last message, if it's 0), shifted towards the newer ones by a negative `offset`.
+    /// If `message_thread_id` isn't 0, only the messages of that thread are considered.
+    fn history(
+        &self,
+        chat_id: i64,
+        message_thread_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+    ) -> Vec<types::Message> {
+        self.filtered_history(chat_id, from_message_id, offset, limit, |message| {
+            message_thread_id == 0
+                || message.message_thread_id == message_thread_id
+                || message.id == message_thread_id
+        })
+    }
+
+    /// Like `history`, but only considers the messages for which `filter` returns true.
+    fn filtered_history<F: Fn(&types::Message) -> bool>(
+        &self,
+        chat_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+        filter: F,
+    ) -> Vec<types::Message> {
+        let messages = self.messages.borrow();
+        let Some(messages) = messages.get(&chat_id) else {
+            return Vec::new();
+        };
+
+        let from_message_id = if from_message_id == 0 {
+            i64::MAX
+        } else {
+            from_message_id
+        };
+
+        let newer_count = messages
+            .range(from_message_id..)
+            .map(|(_, message)| message)
+            .filter(|message| filter(message))
+            .count();
+        let start = (newer_count as i64 + offset as i64).max(0);
+
+        messages
+            .values()
+            .rev()
+            .filter(|message| filter(message))
+            .skip(start as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect()
+    }
+
+    fn paged_history(
+        &self,
+        chat_id: i64,
+        message_thread_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+    ) -> Result<enums::Messages, TdError> {
+        if limit <= 0 || offset > 0 || offset <= -limit {
+            return error(400, "Invalid limit or offset specified");
+        }
+
+        let messages = self.history(chat_id, message_thread_id, from_message_id, offset, limit);
+        Ok(enums::Messages::Messages(types::Messages {
+            total_count: messages.len() as i32,
+            messages: messages.into_iter().map(Some).collect(),
+        }))
+    }
+
+    /// Adds a file that is already downloaded, like one that was just sent.
+    fn add_local_file(&self, path: String) -> types::File {
+        let mut files = self.files.borrow_mut();
+
+        let size = std::fs::metadata(&path)
+            .map(|metadata| metadata.len() as i64)
+            .unwrap_or_default();
+        let file = types::File {
+            id: files.keys().max().copied().unwrap_or_default() + 1,
+            size,
+            expected_size: size,
+            local: types::LocalFile {
+                path,
+                can_be_downloaded: true,
+                can_be_deleted: true,
+                is_downloading_active: false,
+                is_downloading_completed: true,
+                download_offset: 0,
+                downloaded_prefix_size: size,
+                downloaded_size: size,
+            },
+            remote: types::RemoteFile {
+                id: String::new(),
+                unique_id: String::new(),
+                is_uploading_active: false,
+                is_uploading_completed: true,
+                uploaded_size: size,
+            },
+        };
+
+        files.insert(file.id, file.clone());
+        file
+    }
+
+    /// Applies `f` to the reactions of a message and notifies the session about the new
+    /// interaction info.
+    fn update_reactions<F: FnOnce(&mut Vec<types::MessageReaction>)>(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        f: F,
+    ) -> Result<(), TdError> {
+        let Some(mut message) = self.message(chat_id, message_id) else {
+            return error(400, "Message not found");
+        };
+
+        let mut interaction_info =
+            message
+                .interaction_info
+                .take()
+                .unwrap_or(types::MessageInteractionInfo {
+                    view_count: 0,
+                    forward_count: 0,
+                    reply_info: None,
+                    reactions: Vec::new(),
+                });
+        f(&mut interaction_info.reactions);
+        interaction_info.reactions.retain(|r| r.total_count > 0);
+
+        message.interaction_info = Some(interaction_info.clone());
+        self.insert_message(message);
+
+        self.emit(Update::MessageInteractionInfo(
+            types::UpdateMessageInteractionInfo {
+                chat_id,
+                message_id,
+                interaction_info: Some(interaction_info),
+            },
+        ));
+
+        Ok(())
+    }
+
+    /// Pins or unpins a message and notifies the session about it.
+    fn set_message_is_pinned(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        is_pinned: bool,
+    ) -> Result<(), TdError> {
+        let Some(mut message) = self.message(chat_id, message_id) else {
+            return error(400, "Message not found");
+        };
+
+        if message.is_pinned != is_pinned {
+            message.is_pinned = is_pinned;
+            self.insert_message(message);
+
+            self.emit(Update::MessageIsPinned(types::UpdateMessageIsPinned {
+                chat_id,
+                message_id,
+                is_pinned,
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+fn error<T>(code: i32, message: &str) -> Result<T, TdError> {
+    Err(TdError {
+        code,
+        message: message.to_owned(),
+    })
+}
+
+fn ready<T: 'static>(result: Result<T, TdError>) -> BackendFuture<T> {
+    Box::pin(future::ready(result))
+}
+
+fn now() -> i32 {
+    glib::DateTime::now_utc()
+        .map(|date_time| date_time.to_unix() as i32)
+        .unwrap_or_default()
+}
+
+/// Returns the text of a message or the caption of its media, which is what a query of
+/// `searchChatMessages` is matched against.
+fn message_text(content: &enums::MessageContent) -> Option<&str> {
+    use enums::MessageContent::*;
+
+    let text = match content {
+        MessageText(data) => &data.text,
+        MessageAnimation(data) => &data.caption,
+        MessageAudio(data) => &data.caption,
+        MessageDocument(data) => &data.caption,
+        MessagePhoto(data) => &data.caption,
+        MessageVideo(data) => &data.caption,
+        MessageVoiceNote(data) => &data.caption,
+        _ => return None,
+    };
+    Some(&text.text)
+}
+
+/// Returns whether a message matches a filter of `searchChatMessages`. Filters that depend on
+/// information only known by TDLib don't match any message.
+fn matches_filter(message: &types::Message, filter: &enums::SearchMessagesFilter) -> bool {
+    use enums::MessageContent;
+    use enums::SearchMessagesFilter::*;
+
+    let content = &message.content;
+    match filter {
+        Empty => true,
+        Animation => matches!(content, MessageContent::MessageAnimation(_)),
+        Audio => matches!(content, MessageContent::MessageAudio(_)),
+        Document => matches!(content, MessageContent::MessageDocument(_)),
+        Photo => matches!(content, MessageContent::MessagePhoto(_)),
+        Video => matches!(content, MessageContent::MessageVideo(_)),
+        VoiceNote => matches!(content, MessageContent::MessageVoiceNote(_)),
+        PhotoAndVideo => matches!(
+            content,
+            MessageContent::MessagePhoto(_) | MessageContent::MessageVideo(_)
+        ),
+        VideoNote => matches!(content, MessageContent::MessageVideoNote(_)),
+        VoiceAndVideoNote => matches!(
+            content,
+            MessageContent::MessageVoiceNote(_) | MessageContent::MessageVideoNote(_)
+        ),
+        Pinned => message.is_pinned,
+        _ => false,
+    }
+}
+
+impl Backend for FakeBackend {
+    fn handle_update(&self, update: &Update) {
+        match update {
+            Update::NewMessage(data) => self.insert_message(data.message.clone()),
+            Update::NewChat(data) => {
+                if let Some(ref message) = data.chat.last_message {
+                    self.insert_message(message.clone());
+                }
+                self.read_inbox.borrow_mut().insert(
+                    data.chat.id,
+                    types::UpdateChatReadInbox {
+                        chat_id: data.chat.id,
+                        last_read_inbox_message_id: data.chat.last_read_inbox_message_id,
+                        unread_count: data.chat.unread_count,
+                    },
+                );
+            }
+            Update::ChatReadInbox(data) => {
+                self.read_inbox
+                    .borrow_mut()
+                    .insert(data.chat_id, data.clone());
+            }
+            Update::ChatLastMessage(data) => {
+                if let Some(ref message) = data.last_message {
+                    self.insert_message(message.clone());
+                }
+            }
+            Update::MessageSendSucceeded(data) => {
+                if let Some(messages) = self.messages.borrow_mut().get_mut(&data.message.chat_id) {
+                    messages.remove(&data.old_message_id);
+                }
+                self.insert_message(data.message.clone());
+            }
+            Update::DeleteMessages(data) => {
+                if !data.from_cache {
+                    if let Some(messages) = self.messages.borrow_mut().get_mut(&data.chat_id) {
+                        for message_id in &data.message_ids {
+                            messages.remove(message_id);
+                        }
+                    }
+                }
+            }
+            Update::MessageContent(data) => {
+                if let Some(message) = self
+                    .messages
+                    .borrow_mut()
+                    .get_mut(&data.chat_id)
+                    .and_then(|messages| messages.get_mut(&data.message_id))
+                {
+                    message.content = data.new_content.clone();
+                }
+            }
+            Update::MessageContentOpened(data) => {
+                if let Some(message) = self
+                    .messages
+                    .borrow_mut()
+                    .get_mut(&data.chat_id)
+                    .and_then(|messages| messages.get_mut(&data.message_id))
+                {
+                    match message.content {
+                        enums::MessageContent::MessageVoiceNote(ref mut data) => {
+                            data.is_listened = true
+                        }
+                        enums::MessageContent::MessageVideoNote(ref mut data) => {
+                            data.is_viewed = true
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Update::MessageIsPinned(data) => {
+                if let Some(message) = self
+                    .messages
+                    .borrow_mut()
+                    .get_mut(&data.chat_id)
+                    .and_then(|messages| messages.get_mut(&data.message_id))
+                {
+                    message.is_pinned = data.is_pinned;
+                }
+            }
+            Update::File(data) => {
+                self.files
+                    .borrow_mut()
+                    .insert(data.file.id, data.file.clone());
+            }
+            Update::Option(data) => {
+                if let ("my_id", enums::OptionValue::Integer(value)) =
+                    (data.name.as_str(), &data.value)
+                {
+                    self.my_id.set(value.value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_option(&self, _name: String, _value: Option<enums::OptionValue>) -> BackendFuture<()> {
+        ready(Ok(()))
+    }
+
+    fn load_chats(&self, _chat_list: Option<enums::ChatList>, _limit: i32) -> BackendFuture<()> {
+        // The chats are only known from the updates, so all of them are already loaded
+        ready(error(404, "Not Found"))
+    }
+
+    fn get_message(&self, chat_id: i64, message_id: i64) -> BackendFuture<enums::Message> {
+        ready(match self.message(chat_id, message_id) {
+            Some(message) => Ok(enums::Message::Message(message)),
+            None => error(404, "Message not found"),
+        })
+    }
+
+    fn get_chat_history(
+        &self,
+        chat_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+        _only_local: bool,
+    ) -> BackendFuture<enums::Messages> {
+        ready(self.paged_history(chat_id, 0, from_message_id, offset, limit))
+    }
+
+    fn get_message_thread(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+    ) -> BackendFuture<enums::MessageThreadInfo> {
+        let Some(message) = self.message(chat_id, message_id) else {
+            return ready(error(400, "Message not found"));
+        };
+
+        // Threads are always kept in the chat of their messages, as no discussion groups are known
+        let message_thread_id = if message.message_thread_id != 0 {
+            message.message_thread_id
+        } else {
+            message.id
+        };
+
+        ready(Ok(enums::MessageThreadInfo::MessageThreadInfo(
+            types::MessageThreadInfo {
+                chat_id,
+                message_thread_id,
+                reply_info: message
+                    .interaction_info
+                    .and_then(|interaction_info| interaction_info.reply_info),
+                unread_message_count: 0,
+                messages: self
+                    .message(chat_id, message_thread_id)
+                    .into_iter()
+                    .collect(),
+                draft_message: None,
+            },
+        )))
+    }
+
+    fn get_message_thread_history(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+    ) -> BackendFuture<enums::Messages> {
+        let message_thread_id = match self.message(chat_id, message_id) {
+            Some(message) if message.message_thread_id != 0 => message.message_thread_id,
+            Some(_) => message_id,
+            None => return ready(error(400, "Message not found")),
+        };
+
+        ready(self.paged_history(chat_id, message_thread_id, from_message_id, offset, limit))
+    }
+
+    fn get_forum_topics(
+        &self,
+        _chat_id: i64,
+        _query: String,
+        _offset_date: i32,
+        _offset_message_id: i64,
+        _offset_message_thread_id: i64,
+        _limit: i32,
+    ) -> BackendFuture<enums::ForumTopics> {
+        // Topics aren't tracked, so every forum looks empty
+        ready(Ok(enums::ForumTopics::ForumTopics(types::ForumTopics {
+            total_count: 0,
+            topics: Vec::new(),
+            next_offset_date: 0,
+            next_offset_message_id: 0,
+            next_offset_message_thread_id: 0,
+        })))
+    }
+
+    fn search_chat_messages(
+        &self,
+        chat_id: i64,
+        query: String,
+        sender_id: Option<enums::MessageSender>,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+        filter: Option<enums::SearchMessagesFilter>,
+        message_thread_id: i64,
+    ) -> BackendFuture<enums::FoundChatMessages> {
+        if limit <= 0 || offset > 0 || offset <= -limit {
+            return ready(error(400, "Invalid limit or offset specified"));
+        }
+
+        let query = query.to_lowercase();
+        let messages = self.filtered_history(chat_id, from_message_id, offset, limit, |message| {
+            (message_thread_id == 0 || message.message_thread_id == message_thread_id)
+                && sender_id
+                    .as_ref()
+                    .map_or(true, |sender| &message.sender_id == sender)
+                && filter
+                    .as_ref()
+                    .map_or(true, |filter| matches_filter(message, filter))
+                && (query.is_empty()
+                    || message_text(&message.content)
+                        .map_or(false, |text| text.to_lowercase().contains(&query)))
+        });
+
+        ready(Ok(enums::FoundChatMessages::FoundChatMessages(
+            types::FoundChatMessages {
+                total_count: messages.len() as i32,
+                next_from_message_id: messages.last().map_or(0, |message| message.id),
+                messages,
+            },
+        )))
+    }
+
+    fn get_chat_message_by_date(&self, chat_id: i64, date: i32) -> BackendFuture<enums::Message> {
+        let message = self.messages.borrow().get(&chat_id).and_then(|messages| {
+            messages
+                .values()
+                .rev()
+                .find(|message| message.date <= date)
+                .cloned()
+        });
+
+        ready(match message {
+            Some(message) => Ok(enums::Message::Message(message)),
+            None => error(404, "Message not found"),
+        })
+    }
+
+    fn search_chat_members(
+        &self,
+        _chat_id: i64,
+        _query: String,
+        _limit: i32,
+        _filter: Option<enums::ChatMembersFilter>,
+    ) -> BackendFuture<enums::ChatMembers> {
+        // The members of a chat are only known by Telegram
+        ready(Ok(enums::ChatMembers::ChatMembers(types::ChatMembers {
+            total_count: 0,
+            members: Vec::new(),
+        })))
+    }
+
+    fn get_basic_group_full_info(
+        &self,
+        _basic_group_id: i64,
+    ) -> BackendFuture<enums::BasicGroupFullInfo> {
+        ready(error(400, "Basic group not found"))
+    }
+
+    fn get_supergroup_members(
+        &self,
+        _supergroup_id: i64,
+        _filter: Option<enums::SupergroupMembersFilter>,
+        _offset: i32,
+        _limit: i32,
+    ) -> BackendFuture<enums::ChatMembers> {
+        // The members of a chat are only known by Telegram
+        ready(Ok(enums::ChatMembers::ChatMembers(types::ChatMembers {
+            total_count: 0,
+            members: Vec::new(),
+        })))
+    }
+
+    fn set_chat_member_status(
+        &self,
+        _chat_id: i64,
+        _member_id: enums::MessageSender,
+        _status: enums::ChatMemberStatus,
+    ) -> BackendFuture<()> {
+        ready(Ok(()))
+    }
+
+    fn ban_chat_member(
+        &self,
+        _chat_id: i64,
+        _member_id: enums::MessageSender,
+        _banned_until_date: i32,
+        _revoke_messages: bool,
+    ) -> BackendFuture<()> {
+        ready(Ok(()))
+    }
+
+    fn set_chat_permissions(
+        &self,
+        _chat_id: i64,
+        _permissions: types::ChatPermissions,
+    ) -> BackendFuture<()> {
+        ready(Ok(()))
+    }
+
+    fn search_messages(
+        &self,
+        _chat_list: Option<enums::ChatList>,
+        query: String,
+        offset: String,
+        limit: i32,
+        filter: Option<enums::SearchMessagesFilter>,
+        min_date: i32,
+        max_date: i32,
+    ) -> BackendFuture<enums::FoundMessages> {
+        // The chat lists are unknown, so every chat is searched. The offset is simply the number
+        // of messages that were already returned.
+        let skip = if offset.is_empty() {
+            0
+        } else {
+            match offset.parse::<usize>() {
+                Ok(skip) => skip,
+                Err(_) => return ready(error(400, "Invalid offset specified")),
+            }
+        };
+        if limit <= 0 {
+            return ready(error(400, "Invalid limit specified"));
+        }
+
+        let query = query.to_lowercase();
+        let mut messages: Vec<types::Message> = self
+            .messages
+            .borrow()
+            .values()
+            .flat_map(|messages| messages.values())
+            .filter(|message| {
+                (min_date == 0 || message.date >= min_date)
+                    && (max_date == 0 || message.date <= max_date)
+                    && filter
+                        .as_ref()
+                        .map_or(true, |filter| matches_filter(message, filter))
+                    && message_text(&message.content)
+                        .map_or(false, |text| text.to_lowercase().contains(&query))
+            })
+            .cloned()
+            .collect();
+
+        // The newest messages come first
+        messages.sort_by(|a, b| b.date.cmp(&a.date).then(b.id.cmp(&a.id)));
+
+        let total_count = messages.len();
+        let messages: Vec<types::Message> = messages
+            .into_iter()
+            .skip(skip)
+            .take(limit as usize)
+            .collect();
+        let next_offset = if skip + messages.len() < total_count {
+            (skip + messages.len()).to_string()
+        } else {
+            String::new()
+        };
+
+        ready(Ok(enums::FoundMessages::FoundMessages(
+            types::FoundMessages {
+                total_count: total_count as i32,
+                messages,
+                next_offset,
+            },
+        )))
+    }
+
+    fn send_message(
+        &self,
+        chat_id: i64,
+        message_thread_id: i64,
+        reply_to_message_id: i64,
+        _options: Option<types::MessageSendOptions>,
+        input_message_content: enums::InputMessageContent,
+    ) -> BackendFuture<enums::Message> {
+        // Use the last message of the chat as a template, as building a message from scratch
+        // would require knowing about every field of it
+        let last_message = self
+            .messages
+            .borrow()
+            .get(&chat_id)
+            .and_then(|messages| messages.values().next_back().cloned());
+        let Some(mut message) = last_message else {
+            return ready(error(400, "Chat has no known messages"));
+        };
+
+        message.id += 1;
+        message.sender_id = enums::MessageSender::User(types::MessageSenderUser {
+            user_id: self.my_id.get(),
+        });
+        message.sending_state = None;
+        message.is_outgoing = true;
+        message.is_pinned = false;
+        message.date = now();
+        message.edit_date = 0;
+        message.forward_info = None;
+        message.interaction_info = None;
+        message.reply_in_chat_id = if reply_to_message_id != 0 { chat_id } else { 0 };
+        message.reply_to_message_id = reply_to_message_id;
+        message.message_thread_id = message_thread_id;
+        message.reply_markup = None;
+        message.content = match input_message_content {
+            enums::InputMessageContent::InputMessageText(input) => {
+                enums::MessageContent::MessageText(types::MessageText {
+                    text: input.text,
+                    web_page: None,
+                })
+            }
+            enums::InputMessageContent::InputMessagePoll(input) => {
+                enums::MessageContent::MessagePoll(types::MessagePoll {
+                    poll: types::Poll {
+                        id: message.id,
+                        question: input.question,
+                        options: input
+                            .options
+                            .into_iter()
+                            .map(|text| types::PollOption {
+                                text,
+                                voter_count: 0,
+                                vote_percentage: 0,
+                                is_chosen: false,
+                                is_being_chosen: false,
+                            })
+                            .collect(),
+                        total_voter_count: 0,
+                        recent_voter_user_ids: Vec::new(),
+                        is_anonymous: input.is_anonymous,
+                        r#type: input.r#type,
+                        open_period: input.open_period,
+                        close_date: input.close_date,
+                        is_closed: input.is_closed,
+                    },
+                })
+            }
+            enums::InputMessageContent::InputMessageVoiceNote(input) => {
+                let enums::InputFile::Local(file) = input.voice_note else {
+                    return ready(error(400, "Only local voice notes are supported"));
+                };
+
+                enums::MessageContent::MessageVoiceNote(types::MessageVoiceNote {
+                    voice_note: types::VoiceNote {
+                        duration: input.duration,
+                        waveform: input.waveform,
+                        mime_type: "audio/ogg".to_string(),
+                        voice: self.add_local_file(file.path),
+                    },
+                    caption: input.caption.unwrap_or(types::FormattedText {
+                        text: String::new(),
+                        entities: Vec::new(),
+                    }),
+                    is_listened: false,
+                })
+            }
+            _ => {
+                return ready(error(
+                    400,
+                    "Only text messages, polls and voice notes are supported",
+                ))
+            }
+        };
+
+        self.insert_message(message.clone());
+        self.emit(Update::NewMessage(types::UpdateNewMessage {
+            message: message.clone(),
+        }));
+
+        ready(Ok(enums::Message::Message(message)))
+    }
+
+    fn edit_message_text(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        input_message_content: enums::InputMessageContent,
+    ) -> BackendFuture<enums::Message> {
+        let enums::InputMessageContent::InputMessageText(input) = input_message_content else {
+            return ready(error(400, "Only text messages are supported"));
+        };
+        let Some(mut message) = self.message(chat_id, message_id) else {
+            return ready(error(400, "Message not found"));
+        };
+
+        message.content = enums::MessageContent::MessageText(types::MessageText {
+            text: input.text,
+            web_page: None,
+        });
+        message.edit_date = now();
+
+        self.insert_message(message.clone());
+        self.emit(Update::MessageContent(types::UpdateMessageContent {
+            chat_id,
+            message_id,
+            new_content: message.content.clone(),
+        }));
+        self.emit(Update::MessageEdited(types::UpdateMessageEdited {
+            chat_id,
+            message_id,
+            edit_date: message.edit_date,
+            reply_markup: None,
+        }));
+
+        ready(Ok(enums::Message::Message(message)))
+    }
+
+    fn delete_messages(
+        &self,
+        chat_id: i64,
+        message_ids: Vec<i64>,
+        _revoke: bool,
+    ) -> BackendFuture<()> {
+        let message_ids: Vec<i64> = match self.messages.borrow_mut().get_mut(&chat_id) {
+            Some(messages) => message_ids
+                .into_iter()
+                .filter(|id| messages.remove(id).is_some())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if !message_ids.is_empty() {
+            self.emit(Update::DeleteMessages(types::UpdateDeleteMessages {
+                chat_id,
+                message_ids,
+                is_permanent: true,
+                from_cache: false,
+            }));
+        }
+
+        ready(Ok(()))
+    }
+
+    fn view_messages(
+        &self,
+        chat_id: i64,
+        message_ids: Vec<i64>,
+        _source: Option<enums::MessageSource>,
+        _force_read: bool,
+    ) -> BackendFuture<()> {
+        let Some(mut read_inbox) = self.read_inbox.borrow().get(&chat_id).cloned() else {
+            return ready(error(400, "Chat not found"));
+        };
+
+        // Viewing a message also reads all the older ones
+        let Some(last_viewed_message_id) = message_ids
+            .into_iter()
+            .filter(|id| self.message(chat_id, *id).map_or(false, |m| !m.is_outgoing))
+            .max()
+            .filter(|id| *id > read_inbox.last_read_inbox_message_id)
+        else {
+            return ready(Ok(()));
+        };
+
+        let n_read_messages = self
+            .messages
+            .borrow()
+            .get(&chat_id)
+            .map(|messages| {
+                messages
+                    .range(read_inbox.last_read_inbox_message_id + 1..=last_viewed_message_id)
+                    .filter(|(_, message)| !message.is_outgoing)
+                    .count() as i32
+            })
+            .unwrap_or_default();
+
+        read_inbox.last_read_inbox_message_id = last_viewed_message_id;
+        read_inbox.unread_count = (read_inbox.unread_count - n_read_messages).max(0);
+        self.emit(Update::ChatReadInbox(read_inbox));
+
+        ready(Ok(()))
+    }
+
+    fn open_chat(&self, chat_id: i64) -> BackendFuture<()> {
+        if !self.read_inbox.borrow().contains_key(&chat_id) {
+            return ready(error(400, "Chat not found"));
+        }
+
+        ready(Ok(()))
+    }
+
+    fn close_chat(&self, chat_id: i64) -> BackendFuture<()> {
+        if !self.read_inbox.borrow().contains_key(&chat_id) {
+            return ready(error(400, "Chat not found"));
+        }
+
+        ready(Ok(()))
+    }
+
+    fn toggle_chat_is_marked_as_unread(
+        &self,
+        chat_id: i64,
+        is_marked_as_unread: bool,
+    ) -> BackendFuture<()> {
+        if !self.read_inbox.borrow().contains_key(&chat_id) {
+            return ready(error(400, "Chat not found"));
+        }
+
+        self.emit(Update::ChatIsMarkedAsUnread(
+            types::UpdateChatIsMarkedAsUnread {
+                chat_id,
+                is_marked_as_unread,
+            },
+        ));
+
+        ready(Ok(()))
+    }
+
+    fn open_message_content(&self, chat_id: i64, message_id: i64) -> BackendFuture<()> {
+        if self.message(chat_id, message_id).is_none() {
+            return ready(error(400, "Message not found"));
+        }
+
+        self.emit(Update::MessageContentOpened(
+            types::UpdateMessageContentOpened {
+                chat_id,
+                message_id,
+            },
+        ));
+
+        ready(Ok(()))
+    }
+
+    fn pin_chat_message(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        _disable_notification: bool,
+        _only_for_self: bool,
+    ) -> BackendFuture<()> {
+        ready(self.set_message_is_pinned(chat_id, message_id, true))
+    }
+
+    fn unpin_chat_message(&self, chat_id: i64, message_id: i64) -> BackendFuture<()> {
+        ready(self.set_message_is_pinned(chat_id, message_id, false))
+    }
+
+    fn add_message_reaction(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        reaction_type: enums::ReactionType,
+        _is_big: bool,
+        _update_recent_reactions: bool,
+    ) -> BackendFuture<()> {
+        ready(self.update_reactions(chat_id, message_id, |reactions| {
+            // Like for non-premium users, only a single reaction can be chosen at a time
+            for reaction in reactions.iter_mut().filter(|r| r.is_chosen) {
+                reaction.is_chosen = false;
+                reaction.total_count -= 1;
+            }
+
+            match reactions.iter_mut().find(|r| r.r#type == reaction_type) {
+                Some(reaction) => {
+                    reaction.is_chosen = true;
+                    reaction.total_count += 1;
+                }
+                None => reactions.push(types::MessageReaction {
+                    r#type: reaction_type,
+                    total_count: 1,
+                    is_chosen: true,
+                    recent_sender_ids: Vec::new(),
+                }),
+            }
+        }))
+    }
+
+    fn remove_message_reaction(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        reaction_type: enums::ReactionType,
+    ) -> BackendFuture<()> {
+        ready(self.update_reactions(chat_id, message_id, |reactions| {
+            if let Some(reaction) = reactions
+                .iter_mut()
+                .find(|r| r.is_chosen && r.r#type == reaction_type)
+            {
+                reaction.is_chosen = false;
+                reaction.total_count -= 1;
+            }
+        }))
+    }
+
+    fn get_message_available_reactions(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        _row_size: i32,
+    ) -> BackendFuture<enums::AvailableReactions> {
+        if self.message(chat_id, message_id).is_none() {
+            return ready(error(400, "Message not found"));
+        }
+
+        let top_reactions = AVAILABLE_REACTIONS
+            .iter()
+            .map(|emoji| types::AvailableReaction {
+                r#type: enums::ReactionType::Emoji(types::ReactionTypeEmoji {
+                    emoji: emoji.to_string(),
+                }),
+                needs_premium: false,
+            })
+            .collect();
+
+        ready(Ok(enums::AvailableReactions::AvailableReactions(
+            types::AvailableReactions {
+                top_reactions,
+                recent_reactions: Vec::new(),
+                popular_reactions: Vec::new(),
+                allow_custom_emoji: false,
+            },
+        )))
+    }
+
+    fn get_custom_emoji_stickers(
+        &self,
+        _custom_emoji_ids: Vec<i64>,
+    ) -> BackendFuture<enums::Stickers> {
+        // Custom emojis are only known by Telegram
+        ready(Ok(enums::Stickers::Stickers(types::Stickers {
+            stickers: Vec::new(),
+        })))
+    }
+
+    fn set_poll_answer(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        option_ids: Vec<i32>,
+    ) -> BackendFuture<()> {
+        let Some(mut message) = self.message(chat_id, message_id) else {
+            return ready(error(400, "Message not found"));
+        };
+        let enums::MessageContent::MessagePoll(ref mut data) = message.content else {
+            return ready(error(400, "Message is not a poll"));
+        };
+
+        let poll = &mut data.poll;
+        if poll.is_closed {
+            return ready(error(400, "Poll is closed"));
+        }
+
+        let had_voted = poll.options.iter().any(|option| option.is_chosen);
+        for (id, option) in poll.options.iter_mut().enumerate() {
+            if option.is_chosen {
+                option.voter_count -= 1;
+            }
+            option.is_chosen = option_ids.contains(&(id as i32));
+            if option.is_chosen {
+                option.voter_count += 1;
+            }
+        }
+
+        poll.total_voter_count += !option_ids.is_empty() as i32 - had_voted as i32;
+        let total_voter_count = poll.total_voter_count.max(1);
+        for option in &mut poll.options {
+            option.vote_percentage = option.voter_count * 100 / total_voter_count;
+        }
+
+        let new_content = message.content.clone();
+        self.insert_message(message);
+        self.emit(Update::MessageContent(types::UpdateMessageContent {
+            chat_id,
+            message_id,
+            new_content,
+        }));
+
+        ready(Ok(()))
+    }
+
+    fn send_chat_action(
+        &self,
+        _chat_id: i64,
+        _message_thread_id: i64,
+        _action: Option<enums::ChatAction>,
+    ) -> BackendFuture<()> {
+        ready(Ok(()))
+    }
+
+    fn download_file(
+        &self,
+        file_id: i32,
+        _priority: i32,
+        _offset: i64,
+        _limit: i64,
+        _synchronous: bool,
+    ) -> BackendFuture<enums::File> {
+        // Files can't be downloaded without TDLib, so only the known state is returned
+        ready(match self.files.borrow().get(&file_id) {
+            Some(file) => Ok(enums::File::File(file.clone())),
+            None => error(404, "File not found"),
+        })
+    }
+
+    fn cancel_download_file(&self, _file_id: i32, _only_if_pending: bool) -> BackendFuture<()> {
+        ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::update_log::UpdateReplay;
+
+    const CHAT_ID: i64 = 100;
+
+    /// Returns a backend that has seen the updates of the replay fixture.
+    fn replayed_backend() -> FakeBackend {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/backend/tests/replay.jsonl");
+        let backend = FakeBackend::default();
+
+        for result in UpdateReplay::open(&path).unwrap() {
+            let (update, client_id) = result.unwrap();
+            assert_eq!(client_id, 1);
+            backend.handle_update(&update);
+        }
+
+        backend
+    }
+
+    /// Runs a future on its own main context, as tests don't own the default one.
+    fn block_on<T>(future: BackendFuture<T>) -> Result<T, TdError> {
+        glib::MainContext::new().block_on(future)
+    }
+
+    /// Returns the updates that the backend emits from now on.
+    fn recorded_updates(backend: &FakeBackend) -> Rc<RefCell<Vec<Update>>> {
+        let updates = Rc::new(RefCell::new(Vec::new()));
+        backend.set_update_handler({
+            let updates = updates.clone();
+            move |update| updates.borrow_mut().push(update)
+        });
+        updates
+    }
+
+    fn text_input(text: &str) -> enums::InputMessageContent {
+        enums::InputMessageContent::InputMessageText(types::InputMessageText {
+            text: types::FormattedText {
+                text: text.to_string(),
+                entities: Vec::new(),
+            },
+            disable_web_page_preview: false,
+            clear_draft: true,
+        })
+    }
+
+    fn message_ids(messages: enums::Messages) -> Vec<i64> {
+        let enums::Messages::Messages(messages) = messages;
+        messages
+            .messages
+            .into_iter()
+            .flatten()
+            .map(|message| message.id)
+            .collect()
+    }
+
+    #[test]
+    fn replayed_history() {
+        let backend = replayed_backend();
+
+        let history = block_on(backend.get_chat_history(CHAT_ID, 0, 0, 10, false)).unwrap();
+        assert_eq!(message_ids(history), [3 << 20, 2 << 20, 1 << 20]);
+
+        let page = block_on(backend.get_chat_history(CHAT_ID, 0, 0, 2, false)).unwrap();
+        assert_eq!(message_ids(page), [3 << 20, 2 << 20]);
+
+        let enums::Message::Message(message) =
+            block_on(backend.get_message(CHAT_ID, 2 << 20)).unwrap();
+        let enums::MessageContent::MessageText(content) = message.content else {
+            panic!("Expected a text message");
+        };
+        assert_eq!(content.text.text, "How are you?");
+
+        assert!(block_on(backend.get_message(CHAT_ID, 4 << 20)).is_err());
+        assert!(
+            block_on(backend.get_chat_history(CHAT_ID + 1, 0, 0, 10, false))
+                .map(message_ids)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn send_message_after_replay() {
+        let backend = replayed_backend();
+
+        let updates = recorded_updates(&backend);
+
+        let enums::Message::Message(message) =
+            block_on(backend.send_message(CHAT_ID, 0, 0, None, text_input("Bye"))).unwrap();
+
+        assert!(message.is_outgoing);
+        assert_eq!(
+            message.sender_id,
+            enums::MessageSender::User(types::MessageSenderUser { user_id: 300 })
+        );
+        let updates = updates.borrow();
+        let [Update::NewMessage(data)] = updates.as_slice() else {
+            panic!("Expected a single new message update");
+        };
+        assert_eq!(data.message.id, message.id);
+
+        let history = block_on(backend.get_chat_history(CHAT_ID, 0, 0, 1, false)).unwrap();
+        assert_eq!(message_ids(history), [message.id]);
+    }
+
+    #[test]
+    fn page_around_message() {
+        let backend = replayed_backend();
+
+        // The history starts right before the specified message
+        let older = block_on(backend.get_chat_history(CHAT_ID, 3 << 20, 0, 10, false)).unwrap();
+        assert_eq!(message_ids(older), [2 << 20, 1 << 20]);
+
+        // A negative offset also returns the specified message and the newer ones
+        let around = block_on(backend.get_chat_history(CHAT_ID, 2 << 20, -1, 2, false)).unwrap();
+        assert_eq!(message_ids(around), [2 << 20, 1 << 20]);
+        let around = block_on(backend.get_chat_history(CHAT_ID, 2 << 20, -2, 3, false)).unwrap();
+        assert_eq!(message_ids(around), [3 << 20, 2 << 20, 1 << 20]);
+
+        // Like TDLib, the offset must be within the page
+        assert!(block_on(backend.get_chat_history(CHAT_ID, 2 << 20, 1, 3, false)).is_err());
+        assert!(block_on(backend.get_chat_history(CHAT_ID, 2 << 20, -3, 3, false)).is_err());
+    }
+
+    #[test]
+    fn edit_message() {
+        let backend = replayed_backend();
+        let updates = recorded_updates(&backend);
+
+        let enums::Message::Message(message) =
+            block_on(backend.edit_message_text(CHAT_ID, 2 << 20, text_input("Fine"))).unwrap();
+        assert_ne!(message.edit_date, 0);
+
+        let updates = updates.borrow();
+        let [Update::MessageContent(content), Update::MessageEdited(edited)] = updates.as_slice()
+        else {
+            panic!("Expected a content update followed by an edit update");
+        };
+        assert_eq!((content.chat_id, content.message_id), (CHAT_ID, 2 << 20));
+        let enums::MessageContent::MessageText(ref text) = content.new_content else {
+            panic!("Expected a text message");
+        };
+        assert_eq!(text.text.text, "Fine");
+        assert_eq!(edited.edit_date, message.edit_date);
+
+        let enums::Message::Message(message) =
+            block_on(backend.get_message(CHAT_ID, 2 << 20)).unwrap();
+        assert_eq!(message.content, content.new_content);
+
+        assert!(block_on(backend.edit_message_text(CHAT_ID, 4 << 20, text_input("?"))).is_err());
+    }
+
+    #[test]
+    fn delete_messages() {
+        let backend = replayed_backend();
+        let updates = recorded_updates(&backend);
+
+        // Unknown messages are ignored
+        block_on(backend.delete_messages(CHAT_ID, vec![2 << 20, 4 << 20], true)).unwrap();
+
+        let updates = updates.borrow();
+        let [Update::DeleteMessages(data)] = updates.as_slice() else {
+            panic!("Expected a single delete messages update");
+        };
+        assert_eq!(data.chat_id, CHAT_ID);
+        assert_eq!(data.message_ids, [2 << 20]);
+        assert!(data.is_permanent && !data.from_cache);
+
+        let history = block_on(backend.get_chat_history(CHAT_ID, 0, 0, 10, false)).unwrap();
+        assert_eq!(message_ids(history), [3 << 20, 1 << 20]);
+        assert!(block_on(backend.get_message(CHAT_ID, 2 << 20)).is_err());
+    }
+}
@@ -0,0 +1,706 @@
+//! Backends through which a [`Session`](crate::Session) sends its TDLib requests.
+//!
+//! Requests concerning chats, messages and files are not sent via `tdlib::functions` directly,
+//! but via the [`Backend`] owned by the session. [`TdlibBackend`] forwards them to TDLib and is
+//! used by default, while [`FakeBackend`] keeps everything in memory, which makes it possible to
+//! send, edit, delete and page messages without a connection to a Telegram server.
+//!
+//! The requests of the login and of the account settings, like the privacy settings or the
+//! active sessions, are still sent directly. They only concern the account on the server and
+//! none of the models built from the updates, so a fake backend couldn't answer them anyway.
+
+mod fake;
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use tdlib::enums;
+use tdlib::enums::Update;
+use tdlib::functions;
+use tdlib::types;
+use tdlib::types::Error as TdError;
+
+pub(crate) use self::fake::FakeBackend;
+
+/// The future returned by the requests of a [`Backend`].
+pub(crate) type BackendFuture<T> = Pin<Box<dyn Future<Output = Result<T, TdError>>>>;
+
+/// The requests a [`Session`](crate::Session) can send. The parameters are the same as the ones
+/// of the homonymous functions in `tdlib::functions`, except for the client id, which is owned by
+/// the backend.
+pub(crate) trait Backend: fmt::Debug {
+    /// Called for every update received by the session, before it is handled.
+    fn handle_update(&self, _update: &Update) {}
+
+    fn set_option(&self, name: String, value: Option<enums::OptionValue>) -> BackendFuture<()>;
+
+    fn load_chats(&self, chat_list: Option<enums::ChatList>, limit: i32) -> BackendFuture<()>;
+
+    fn get_message(&self, chat_id: i64, message_id: i64) -> BackendFuture<enums::Message>;
+
+    fn get_chat_history(
+        &self,
+        chat_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+        only_local: bool,
+    ) -> BackendFuture<enums::Messages>;
+
+    fn get_message_thread(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+    ) -> BackendFuture<enums::MessageThreadInfo>;
+
+    fn get_message_thread_history(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+    ) -> BackendFuture<enums::Messages>;
+
+    fn get_forum_topics(
+        &self,
+        chat_id: i64,
+        query: String,
+        offset_date: i32,
+        offset_message_id: i64,
+        offset_message_thread_id: i64,
+        limit: i32,
+    ) -> BackendFuture<enums::ForumTopics>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_chat_messages(
+        &self,
+        chat_id: i64,
+        query: String,
+        sender_id: Option<enums::MessageSender>,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+        filter: Option<enums::SearchMessagesFilter>,
+        message_thread_id: i64,
+    ) -> BackendFuture<enums::FoundChatMessages>;
+
+    fn get_chat_message_by_date(&self, chat_id: i64, date: i32) -> BackendFuture<enums::Message>;
+
+    fn search_chat_members(
+        &self,
+        chat_id: i64,
+        query: String,
+        limit: i32,
+        filter: Option<enums::ChatMembersFilter>,
+    ) -> BackendFuture<enums::ChatMembers>;
+
+    fn get_basic_group_full_info(
+        &self,
+        basic_group_id: i64,
+    ) -> BackendFuture<enums::BasicGroupFullInfo>;
+
+    fn get_supergroup_members(
+        &self,
+        supergroup_id: i64,
+        filter: Option<enums::SupergroupMembersFilter>,
+        offset: i32,
+        limit: i32,
+    ) -> BackendFuture<enums::ChatMembers>;
+
+    fn set_chat_member_status(
+        &self,
+        chat_id: i64,
+        member_id: enums::MessageSender,
+        status: enums::ChatMemberStatus,
+    ) -> BackendFuture<()>;
+
+    fn ban_chat_member(
+        &self,
+        chat_id: i64,
+        member_id: enums::MessageSender,
+        banned_until_date: i32,
+        revoke_messages: bool,
+    ) -> BackendFuture<()>;
+
+    fn set_chat_permissions(
+        &self,
+        chat_id: i64,
+        permissions: types::ChatPermissions,
+    ) -> BackendFuture<()>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_messages(
+        &self,
+        chat_list: Option<enums::ChatList>,
+        query: String,
+        offset: String,
+        limit: i32,
+        filter: Option<enums::SearchMessagesFilter>,
+        min_date: i32,
+        max_date: i32,
+    ) -> BackendFuture<enums::FoundMessages>;
+
+    fn send_message(
+        &self,
+        chat_id: i64,
+        message_thread_id: i64,
+        reply_to_message_id: i64,
+        options: Option<types::MessageSendOptions>,
+        input_message_content: enums::InputMessageContent,
+    ) -> BackendFuture<enums::Message>;
+
+    fn edit_message_text(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        input_message_content: enums::InputMessageContent,
+    ) -> BackendFuture<enums::Message>;
+
+    fn delete_messages(
+        &self,
+        chat_id: i64,
+        message_ids: Vec<i64>,
+        revoke: bool,
+    ) -> BackendFuture<()>;
+
+    fn view_messages(
+        &self,
+        chat_id: i64,
+        message_ids: Vec<i64>,
+        source: Option<enums::MessageSource>,
+        force_read: bool,
+    ) -> BackendFuture<()>;
+
+    fn open_chat(&self, chat_id: i64) -> BackendFuture<()>;
+
+    fn close_chat(&self, chat_id: i64) -> BackendFuture<()>;
+
+    fn toggle_chat_is_marked_as_unread(
+        &self,
+        chat_id: i64,
+        is_marked_as_unread: bool,
+    ) -> BackendFuture<()>;
+
+    fn open_message_content(&self, chat_id: i64, message_id: i64) -> BackendFuture<()>;
+
+    fn pin_chat_message(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        disable_notification: bool,
+        only_for_self: bool,
+    ) -> BackendFuture<()>;
+
+    fn unpin_chat_message(&self, chat_id: i64, message_id: i64) -> BackendFuture<()>;
+
+    fn add_message_reaction(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        reaction_type: enums::ReactionType,
+        is_big: bool,
+        update_recent_reactions: bool,
+    ) -> BackendFuture<()>;
+
+    fn remove_message_reaction(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        reaction_type: enums::ReactionType,
+    ) -> BackendFuture<()>;
+
+    fn get_message_available_reactions(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        row_size: i32,
+    ) -> BackendFuture<enums::AvailableReactions>;
+
+    fn get_custom_emoji_stickers(
+        &self,
+        custom_emoji_ids: Vec<i64>,
+    ) -> BackendFuture<enums::Stickers>;
+
+    fn set_poll_answer(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        option_ids: Vec<i32>,
+    ) -> BackendFuture<()>;
+
+    fn send_chat_action(
+        &self,
+        chat_id: i64,
+        message_thread_id: i64,
+        action: Option<enums::ChatAction>,
+    ) -> BackendFuture<()>;
+
+    fn download_file(
+        &self,
+        file_id: i32,
+        priority: i32,
+        offset: i64,
+        limit: i64,
+        synchronous: bool,
+    ) -> BackendFuture<enums::File>;
+
+    fn cancel_download_file(&self, file_id: i32, only_if_pending: bool) -> BackendFuture<()>;
+}
+
+/// The default backend, which sends every request to TDLib.
+#[derive(Debug)]
+pub(crate) struct TdlibBackend {
+    client_id: i32,
+}
+
+impl TdlibBackend {
+    pub(crate) fn new(client_id: i32) -> Self {
+        Self { client_id }
+    }
+}
+
+impl Backend for TdlibBackend {
+    fn set_option(&self, name: String, value: Option<enums::OptionValue>) -> BackendFuture<()> {
+        Box::pin(functions::set_option(name, value, self.client_id))
+    }
+
+    fn load_chats(&self, chat_list: Option<enums::ChatList>, limit: i32) -> BackendFuture<()> {
+        Box::pin(functions::load_chats(chat_list, limit, self.client_id))
+    }
+
+    fn get_message(&self, chat_id: i64, message_id: i64) -> BackendFuture<enums::Message> {
+        Box::pin(functions::get_message(chat_id, message_id, self.client_id))
+    }
+
+    fn get_chat_history(
+        &self,
+        chat_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+        only_local: bool,
+    ) -> BackendFuture<enums::Messages> {
+        Box::pin(functions::get_chat_history(
+            chat_id,
+            from_message_id,
+            offset,
+            limit,
+            only_local,
+            self.client_id,
+        ))
+    }
+
+    fn get_message_thread(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+    ) -> BackendFuture<enums::MessageThreadInfo> {
+        Box::pin(functions::get_message_thread(
+            chat_id,
+            message_id,
+            self.client_id,
+        ))
+    }
+
+    fn get_message_thread_history(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+    ) -> BackendFuture<enums::Messages> {
+        Box::pin(functions::get_message_thread_history(
+            chat_id,
+            message_id,
+            from_message_id,
+            offset,
+            limit,
+            self.client_id,
+        ))
+    }
+
+    fn get_forum_topics(
+        &self,
+        chat_id: i64,
+        query: String,
+        offset_date: i32,
+        offset_message_id: i64,
+        offset_message_thread_id: i64,
+        limit: i32,
+    ) -> BackendFuture<enums::ForumTopics> {
+        Box::pin(functions::get_forum_topics(
+            chat_id,
+            query,
+            offset_date,
+            offset_message_id,
+            offset_message_thread_id,
+            limit,
+            self.client_id,
+        ))
+    }
+
+    fn search_chat_messages(
+        &self,
+        chat_id: i64,
+        query: String,
+        sender_id: Option<enums::MessageSender>,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+        filter: Option<enums::SearchMessagesFilter>,
+        message_thread_id: i64,
+    ) -> BackendFuture<enums::FoundChatMessages> {
+        Box::pin(functions::search_chat_messages(
+            chat_id,
+            query,
+            sender_id,
+            from_message_id,
+            offset,
+            limit,
+            filter,
+            message_thread_id,
+            self.client_id,
+        ))
+    }
+
+    fn get_chat_message_by_date(&self, chat_id: i64, date: i32) -> BackendFuture<enums::Message> {
+        Box::pin(functions::get_chat_message_by_date(
+            chat_id,
+            date,
+            self.client_id,
+        ))
+    }
+
+    fn search_chat_members(
+        &self,
+        chat_id: i64,
+        query: String,
+        limit: i32,
+        filter: Option<enums::ChatMembersFilter>,
+    ) -> BackendFuture<enums::ChatMembers> {
+        Box::pin(functions::search_chat_members(
+            chat_id,
+            query,
+            limit,
+            filter,
+            self.client_id,
+        ))
+    }
+
+    fn get_basic_group_full_info(
+        &self,
+        basic_group_id: i64,
+    ) -> BackendFuture<enums::BasicGroupFullInfo> {
+        Box::pin(functions::get_basic_group_full_info(
+            basic_group_id,
+            self.client_id,
+        ))
+    }
+
+    fn get_supergroup_members(
+        &self,
+        supergroup_id: i64,
+        filter: Option<enums::SupergroupMembersFilter>,
+        offset: i32,
+        limit: i32,
+    ) -> BackendFuture<enums::ChatMembers> {
+        Box::pin(functions::get_supergroup_members(
+            supergroup_id,
+            filter,
+            offset,
+            limit,
+            self.client_id,
+        ))
+    }
+
+    fn set_chat_member_status(
+        &self,
+        chat_id: i64,
+        member_id: enums::MessageSender,
+        status: enums::ChatMemberStatus,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::set_chat_member_status(
+            chat_id,
+            member_id,
+            status,
+            self.client_id,
+        ))
+    }
+
+    fn ban_chat_member(
+        &self,
+        chat_id: i64,
+        member_id: enums::MessageSender,
+        banned_until_date: i32,
+        revoke_messages: bool,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::ban_chat_member(
+            chat_id,
+            member_id,
+            banned_until_date,
+            revoke_messages,
+            self.client_id,
+        ))
+    }
+
+    fn set_chat_permissions(
+        &self,
+        chat_id: i64,
+        permissions: types::ChatPermissions,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::set_chat_permissions(
+            chat_id,
+            permissions,
+            self.client_id,
+        ))
+    }
+
+    fn search_messages(
+        &self,
+        chat_list: Option<enums::ChatList>,
+        query: String,
+        offset: String,
+        limit: i32,
+        filter: Option<enums::SearchMessagesFilter>,
+        min_date: i32,
+        max_date: i32,
+    ) -> BackendFuture<enums::FoundMessages> {
+        Box::pin(functions::search_messages(
+            chat_list,
+            query,
+            offset,
+            limit,
+            filter,
+            min_date,
+            max_date,
+            self.client_id,
+        ))
+    }
+
+    fn send_message(
+        &self,
+        chat_id: i64,
+        message_thread_id: i64,
+        reply_to_message_id: i64,
+        options: Option<types::MessageSendOptions>,
+        input_message_content: enums::InputMessageContent,
+    ) -> BackendFuture<enums::Message> {
+        Box::pin(functions::send_message(
+            chat_id,
+            message_thread_id,
+            reply_to_message_id,
+            options,
+            input_message_content,
+            self.client_id,
+        ))
+    }
+
+    fn edit_message_text(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        input_message_content: enums::InputMessageContent,
+    ) -> BackendFuture<enums::Message> {
+        Box::pin(functions::edit_message_text(
+            chat_id,
+            message_id,
+            input_message_content,
+            self.client_id,
+        ))
+    }
+
+    fn delete_messages(
+        &self,
+        chat_id: i64,
+        message_ids: Vec<i64>,
+        revoke: bool,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::delete_messages(
+            chat_id,
+            message_ids,
+            revoke,
+            self.client_id,
+        ))
+    }
+
+    fn view_messages(
+        &self,
+        chat_id: i64,
+        message_ids: Vec<i64>,
+        source: Option<enums::MessageSource>,
+        force_read: bool,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::view_messages(
+            chat_id,
+            message_ids,
+            source,
+            force_read,
+            self.client_id,
+        ))
+    }
+
+    fn open_chat(&self, chat_id: i64) -> BackendFuture<()> {
+        Box::pin(functions::open_chat(chat_id, self.client_id))
+    }
+
+    fn close_chat(&self, chat_id: i64) -> BackendFuture<()> {
+        Box::pin(functions::close_chat(chat_id, self.client_id))
+    }
+
+    fn toggle_chat_is_marked_as_unread(
+        &self,
+        chat_id: i64,
+        is_marked_as_unread: bool,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::toggle_chat_is_marked_as_unread(
+            chat_id,
+            is_marked_as_unread,
+            self.client_id,
+        ))
+    }
+
+    fn open_message_content(&self, chat_id: i64, message_id: i64) -> BackendFuture<()> {
+        Box::pin(functions::open_message_content(
+            chat_id,
+            message_id,
+            self.client_id,
+        ))
+    }
+
+    fn pin_chat_message(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        disable_notification: bool,
+        only_for_self: bool,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::pin_chat_message(
+            chat_id,
+            message_id,
+            disable_notification,
+            only_for_self,
+            self.client_id,
+        ))
+    }
+
+    fn unpin_chat_message(&self, chat_id: i64, message_id: i64) -> BackendFuture<()> {
+        Box::pin(functions::unpin_chat_message(
+            chat_id,
+            message_id,
+            self.client_id,
+        ))
+    }
+
+    fn add_message_reaction(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        reaction_type: enums::ReactionType,
+        is_big: bool,
+        update_recent_reactions: bool,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::add_message_reaction(
+            chat_id,
+            message_id,
+            reaction_type,
+            is_big,
+            update_recent_reactions,
+            self.client_id,
+        ))
+    }
+
+    fn remove_message_reaction(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        reaction_type: enums::ReactionType,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::remove_message_reaction(
+            chat_id,
+            message_id,
+            reaction_type,
+            self.client_id,
+        ))
+    }
+
+    fn get_message_available_reactions(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        row_size: i32,
+    ) -> BackendFuture<enums::AvailableReactions> {
+        Box::pin(functions::get_message_available_reactions(
+            chat_id,
+            message_id,
+            row_size,
+            self.client_id,
+        ))
+    }
+
+    fn get_custom_emoji_stickers(
+        &self,
+        custom_emoji_ids: Vec<i64>,
+    ) -> BackendFuture<enums::Stickers> {
+        Box::pin(functions::get_custom_emoji_stickers(
+            custom_emoji_ids,
+            self.client_id,
+        ))
+    }
+
+    fn set_poll_answer(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        option_ids: Vec<i32>,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::set_poll_answer(
+            chat_id,
+            message_id,
+            option_ids,
+            self.client_id,
+        ))
+    }
+
+    fn send_chat_action(
+        &self,
+        chat_id: i64,
+        message_thread_id: i64,
+        action: Option<enums::ChatAction>,
+    ) -> BackendFuture<()> {
+        Box::pin(functions::send_chat_action(
+            chat_id,
+            message_thread_id,
+            action,
+            self.client_id,
+        ))
+    }
+
+    fn download_file(
+        &self,
+        file_id: i32,
+        priority: i32,
+        offset: i64,
+        limit: i64,
+        synchronous: bool,
+    ) -> BackendFuture<enums::File> {
+        Box::pin(functions::download_file(
+            file_id,
+            priority,
+            offset,
+            limit,
+            synchronous,
+            self.client_id,
+        ))
+    }
+
+    fn cancel_download_file(&self, file_id: i32, only_if_pending: bool) -> BackendFuture<()> {
+        Box::pin(functions::cancel_download_file(
+            file_id,
+            only_if_pending,
+            self.client_id,
+        ))
+    }
+}
@@ -3,6 +3,9 @@
 #![allow(clippy::format_push_string)]
 
 mod application;
+mod audio_player;
+mod audio_recorder;
+mod backend;
 #[rustfmt::skip]
 #[allow(clippy::all)]
 mod config;
@@ -15,6 +18,7 @@ mod session;
 mod session_manager;
 mod strings;
 mod tdlib;
+mod update_log;
 mod utils;
 mod window;
 
@@ -53,6 +57,8 @@ fn main() -> glib::ExitCode {
     let res = gio::Resource::load(RESOURCES_FILE).expect("Could not load gresource file");
     gio::resources_register(&res);
 
+    gst::init().expect("Unable to initialize GStreamer");
+
     let app = setup_cli(Application::new());
 
     // Command line handling
@@ -79,6 +85,13 @@ fn main() -> glib::ExitCode {
                 application_opts.test_dc = true;
             }
 
+            application_opts.record_updates = dict.lookup::<PathBuf>("record-updates").unwrap();
+            application_opts.replay_updates = dict.lookup::<PathBuf>("replay-updates").unwrap();
+
+            if dict.contains("fake-audio-source") {
+                application_opts.fake_audio_source = true;
+            }
+
             APPLICATION_OPTS.set(application_opts).unwrap();
 
             -1
@@ -105,12 +118,21 @@ fn main() -> glib::ExitCode {
 pub(crate) struct ApplicationOptions {
     pub(crate) data_dir: PathBuf,
     pub(crate) test_dc: bool,
+    /// The file to record the received TDLib updates to (see [`update_log`]).
+    pub(crate) record_updates: Option<PathBuf>,
+    /// The file to replay TDLib updates from instead of connecting to TDLib (see [`update_log`]).
+    pub(crate) replay_updates: Option<PathBuf>,
+    /// Whether to record voice notes from a test signal instead of the microphone.
+    pub(crate) fake_audio_source: bool,
 }
 impl Default for ApplicationOptions {
     fn default() -> Self {
         Self {
             data_dir: PathBuf::from(glib::user_data_dir().to_str().unwrap()).join("paper-plane"),
             test_dc: Default::default(),
+            record_updates: Default::default(),
+            replay_updates: Default::default(),
+            fake_audio_source: Default::default(),
         }
     }
 }
@@ -143,5 +165,32 @@ fn setup_cli<A: IsA<gio::Application>>(app: A) -> A {
         None,
     );
 
+    app.add_main_option(
+        "record-updates",
+        glib::Char::from(0u8),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::Filename,
+        &gettext("Record the received TDLib updates to a file"),
+        Some("FILE"),
+    );
+
+    app.add_main_option(
+        "replay-updates",
+        glib::Char::from(0u8),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::Filename,
+        &gettext("Replay recorded TDLib updates from a file instead of connecting to Telegram"),
+        Some("FILE"),
+    );
+
+    app.add_main_option(
+        "fake-audio-source",
+        glib::Char::from(0u8),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        &gettext("Record voice notes from a test signal instead of the microphone"),
+        None,
+    );
+
     app
 }
@@ -0,0 +1,193 @@
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums::MessageContent;
+use tdlib::enums::SearchMessagesFilter;
+
+use crate::audio_player::AudioPlayer;
+use crate::tdlib::Message;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct AudioPlaylist {
+        pub(super) player: OnceCell<AudioPlayer>,
+        pub(super) message: RefCell<Option<Message>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AudioPlaylist {
+        const NAME: &'static str = "AudioPlaylist";
+        type Type = super::AudioPlaylist;
+    }
+
+    impl ObjectImpl for AudioPlaylist {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .read_only()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.obj().message().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            let player = AudioPlayer::new();
+            player.connect_finished(clone!(@weak obj => move |_| {
+                spawn(async move {
+                    obj.play_next().await;
+                });
+            }));
+            self.player.set(player).unwrap();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The playlist of the audio files of a session. It plays one audio message at a time and,
+    /// once it's finished, continues with the next audio message of the same chat, so that the
+    /// playback isn't bound to the chat that is currently open.
+    pub(crate) struct AudioPlaylist(ObjectSubclass<imp::AudioPlaylist>);
+}
+
+impl Default for AudioPlaylist {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioPlaylist {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub(crate) fn player(&self) -> &AudioPlayer {
+        self.imp().player.get().unwrap()
+    }
+
+    /// Returns the audio message that is currently loaded, if any.
+    pub(crate) fn message(&self) -> Option<Message> {
+        self.imp().message.borrow().clone()
+    }
+
+    /// Plays the specified audio message, or toggles its playback if it's the current one.
+    pub(crate) fn play_message(&self, message: &Message) {
+        if self.imp().message.borrow().as_ref() == Some(message) {
+            self.play_pause();
+            return;
+        }
+
+        let MessageContent::MessageAudio(data) = message.content().0 else {
+            return;
+        };
+
+        self.player().pause();
+        self.set_message(Some(message.clone()));
+
+        let file = data.audio.audio;
+        if file.local.is_downloading_completed {
+            self.play_file(&file.local.path);
+        } else {
+            message.chat().session().download_file_with_updates(
+                file.id,
+                clone!(@weak self as obj, @weak message => move |file| {
+                    // Only play the file if no other message was chosen in the meantime
+                    if file.local.is_downloading_completed
+                        && obj.imp().message.borrow().as_ref() == Some(&message)
+                    {
+                        obj.play_file(&file.local.path);
+                    }
+                }),
+            );
+        }
+    }
+
+    pub(crate) fn play_pause(&self) {
+        let player = self.player();
+        if player.is_playing() {
+            player.pause();
+        } else if self.imp().message.borrow().is_some() {
+            player.play();
+        }
+    }
+
+    /// Stops the playback and clears the current message.
+    pub(crate) fn stop(&self) {
+        self.player().pause();
+        self.set_message(None);
+    }
+
+    /// Plays the audio message of the same chat that follows the current one. The playback is
+    /// stopped if there's none.
+    pub(crate) async fn play_next(&self) {
+        let Some(message) = self.message() else {
+            return;
+        };
+
+        // Start from the message after the current one, which is also returned
+        let result = message
+            .chat()
+            .search_messages(
+                String::new(),
+                None,
+                message.id(),
+                -1,
+                2,
+                Some(SearchMessagesFilter::Audio),
+                0,
+            )
+            .await;
+
+        // Another message may have been chosen while searching
+        if self.imp().message.borrow().as_ref() != Some(&message) {
+            return;
+        }
+
+        match result {
+            Ok(messages) => {
+                let next_message = messages
+                    .into_iter()
+                    .filter(|m| m.id() > message.id())
+                    .min_by_key(Message::id);
+                match next_message {
+                    Some(next_message) => self.play_message(&next_message),
+                    None => self.stop(),
+                }
+            }
+            Err(e) => {
+                log::warn!("Error searching for the next audio message: {:?}", e);
+                self.stop();
+            }
+        }
+    }
+
+    fn play_file(&self, path: &str) {
+        let player = self.player();
+        player.set_file(path);
+        player.play();
+    }
+
+    fn set_message(&self, message: Option<Message>) {
+        if self.imp().message.replace(message) != self.message() {
+            self.notify("message");
+        }
+    }
+}
@@ -1,6 +1,10 @@
+mod new_chat_window;
 mod row;
 
+use std::cell::Cell;
+
 use adw::subclass::prelude::*;
+use gettextrs::gettext;
 use glib::clone;
 use glib::subclass::Signal;
 use gtk::gio;
@@ -10,7 +14,10 @@ use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use once_cell::unsync::OnceCell;
 use row::ContactRow;
+use tdlib::enums;
+use tdlib::functions;
 
+use self::new_chat_window::NewChatWindow;
 use crate::strings;
 use crate::tdlib::User;
 use crate::utils::spawn;
@@ -22,6 +29,22 @@ mod imp {
     #[derive(Debug, Default, CompositeTemplate)]
     #[template(string = r#"
     <interface>
+      <menu id="new_chat_menu">
+        <section>
+          <item>
+            <attribute name="label" translatable="yes">New _Group</attribute>
+            <attribute name="action">contacts-window.new-group</attribute>
+          </item>
+          <item>
+            <attribute name="label" translatable="yes">New _Channel</attribute>
+            <attribute name="action">contacts-window.new-channel</attribute>
+          </item>
+          <item>
+            <attribute name="label" translatable="yes">New _Secret Chat</attribute>
+            <attribute name="action">contacts-window.new-secret-chat</attribute>
+          </item>
+        </section>
+      </menu>
       <template class="ContactsWindow" parent="AdwWindow">
         <property name="title" translatable="true">Contacts</property>
         <property name="modal">true</property>
@@ -30,7 +53,15 @@ mod imp {
         <property name="content">
           <object class="AdwToolbarView">
             <child type="top">
-              <object class="GtkHeaderBar"/>
+              <object class="GtkHeaderBar">
+                <child type="start">
+                  <object class="GtkMenuButton" id="new_chat_button">
+                    <property name="icon-name">list-add-symbolic</property>
+                    <property name="tooltip-text" translatable="yes">New Chat</property>
+                    <property name="menu-model">new_chat_menu</property>
+                  </object>
+                </child>
+              </object>
             </child>
             <property name="content">
               <object class="GtkScrolledWindow">
@@ -90,6 +121,10 @@ mod imp {
     "#)]
     pub(crate) struct ContactsWindow {
         pub(super) session: OnceCell<Session>,
+        /// Whether activating a contact starts a new secret chat with them.
+        pub(super) is_secret_chat_mode: Cell<bool>,
+        #[template_child]
+        pub(super) new_chat_button: TemplateChild<gtk::MenuButton>,
         #[template_child]
         pub(super) sort_model: TemplateChild<gtk::SortListModel>,
         #[template_child]
@@ -106,6 +141,19 @@ mod imp {
             ContactRow::static_type();
             klass.bind_template();
             klass.bind_template_callbacks();
+
+            klass.install_action("contacts-window.new-group", None, |widget, _, _| {
+                widget.show_new_chat_window(false);
+            });
+            klass.install_action("contacts-window.new-channel", None, |widget, _, _| {
+                widget.show_new_chat_window(true);
+            });
+            klass.install_action("contacts-window.new-secret-chat", None, |widget, _, _| {
+                let imp = widget.imp();
+                imp.is_secret_chat_mode.set(true);
+                imp.new_chat_button.set_visible(false);
+                widget.set_title(Some(&gettext("New Secret Chat")));
+            });
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -140,6 +188,13 @@ mod imp {
                 .and_downcast::<User>()
                 .unwrap();
 
+            if self.is_secret_chat_mode.get() {
+                spawn(clone!(@weak obj => async move {
+                    obj.create_secret_chat(user.id()).await;
+                }));
+                return;
+            }
+
             obj.emit_by_name::<()>("contact-activated", &[&user.id()]);
             obj.close();
         }
@@ -171,6 +226,15 @@ impl ContactsWindow {
         obj
     }
 
+    /// Creates a window that is only used to pick a contact, without the actions to create new
+    /// chats.
+    pub(crate) fn new_picker(parent: Option<&gtk::Window>, session: Session, title: &str) -> Self {
+        let obj = Self::new(parent, session);
+        obj.imp().new_chat_button.set_visible(false);
+        obj.set_title(Some(title));
+        obj
+    }
+
     async fn fetch_contacts(&self) {
         let session = self.imp().session.get().unwrap();
 
@@ -187,6 +251,27 @@ impl ContactsWindow {
         }
     }
 
+    fn show_new_chat_window(&self, is_channel: bool) {
+        let imp = self.imp();
+        let parent = self.transient_for();
+        let session = imp.session.get().unwrap().clone();
+
+        NewChatWindow::new(parent.as_ref(), session, is_channel, &*imp.sort_model).present();
+        self.close();
+    }
+
+    async fn create_secret_chat(&self, user_id: i64) {
+        let session = self.imp().session.get().unwrap();
+
+        match functions::create_new_secret_chat(user_id, session.client_id()).await {
+            Ok(enums::Chat::Chat(chat)) => {
+                session.select_chat(chat.id);
+                self.close();
+            }
+            Err(e) => log::warn!("Error creating a new secret chat: {:?}", e),
+        }
+    }
+
     pub(crate) fn connect_contact_activated<F: Fn(&Self, i64) + 'static>(
         &self,
         f: F,
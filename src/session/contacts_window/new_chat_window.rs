@@ -0,0 +1,293 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gdk;
+use gtk::gio;
+use gtk::glib;
+use gtk::CompositeTemplate;
+use once_cell::unsync::OnceCell;
+use tdlib::enums;
+use tdlib::enums::InputChatPhoto;
+use tdlib::enums::InputFile;
+use tdlib::functions;
+use tdlib::types;
+
+use crate::components::Avatar;
+use crate::strings;
+use crate::tdlib::User;
+use crate::Session;
+
+const PHOTO_MIME_TYPES: &[&str] = &["image/png", "image/jpeg"];
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/new-chat-window.ui")]
+    pub(crate) struct NewChatWindow {
+        pub(super) session: OnceCell<Session>,
+        pub(super) is_channel: Cell<bool>,
+        pub(super) filter: OnceCell<gtk::CustomFilter>,
+        /// The ids of the contacts chosen as members of the new chat.
+        pub(super) selected_users: RefCell<HashSet<i64>>,
+        pub(super) photo_path: RefCell<Option<String>>,
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) member_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) photo_avatar: TemplateChild<adw::Avatar>,
+        #[template_child]
+        pub(super) title_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) description_row: TemplateChild<adw::EntryRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for NewChatWindow {
+        const NAME: &'static str = "NewChatWindow";
+        type Type = super::NewChatWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+
+            klass.install_action("new-chat-window.next", None, |widget, _, _| {
+                let imp = widget.imp();
+                imp.stack.set_visible_child_name("details");
+                imp.title_row.grab_focus();
+            });
+            klass.install_action("new-chat-window.back", None, |widget, _, _| {
+                widget.imp().stack.set_visible_child_name("members");
+            });
+            klass.install_action_async(
+                "new-chat-window.select-photo",
+                None,
+                |widget, _, _| async move {
+                    widget.select_photo().await;
+                },
+            );
+            klass.install_action_async("new-chat-window.create", None, |widget, _, _| async move {
+                widget.create_chat().await;
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for NewChatWindow {}
+    impl WidgetImpl for NewChatWindow {}
+    impl WindowImpl for NewChatWindow {}
+    impl AdwWindowImpl for NewChatWindow {}
+
+    #[gtk::template_callbacks]
+    impl NewChatWindow {
+        #[template_callback]
+        fn on_search_changed(&self) {
+            if let Some(filter) = self.filter.get() {
+                filter.changed(gtk::FilterChange::Different);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A window to create a new group or channel, choosing its members among the contacts and
+    /// setting its title and photo.
+    pub(crate) struct NewChatWindow(ObjectSubclass<imp::NewChatWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl NewChatWindow {
+    pub(crate) fn new(
+        parent: Option<&gtk::Window>,
+        session: Session,
+        is_channel: bool,
+        contacts: &impl IsA<gio::ListModel>,
+    ) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        let imp = obj.imp();
+
+        if is_channel {
+            obj.set_title(Some(&gettext("New Channel")));
+            imp.title_row.set_title(&gettext("Channel Name"));
+            imp.description_row.set_visible(true);
+        } else {
+            obj.set_title(Some(&gettext("New Group")));
+            imp.title_row.set_title(&gettext("Group Name"));
+        }
+
+        let filter =
+            gtk::CustomFilter::new(clone!(@weak obj => @default-return true, move |item| {
+                let query = obj.imp().search_entry.text().to_lowercase();
+                let user = item.downcast_ref::<User>().unwrap();
+
+                query.is_empty()
+                    || strings::user_display_name(user, true)
+                        .to_lowercase()
+                        .contains(&query)
+                    || user.username().to_lowercase().contains(&query)
+            }));
+        let model = gtk::FilterListModel::new(Some(contacts.clone()), Some(filter.clone()));
+        imp.member_list.bind_model(
+            Some(&model),
+            clone!(@weak obj => @default-panic, move |item| {
+                obj.member_row(item.downcast_ref().unwrap()).upcast()
+            }),
+        );
+
+        imp.filter.set(filter).unwrap();
+        imp.is_channel.set(is_channel);
+        imp.session.set(session).unwrap();
+
+        obj
+    }
+
+    fn member_row(&self, user: &User) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&strings::user_display_name(
+                user, true,
+            )))
+            .subtitle(strings::user_status(&user.status().0))
+            .build();
+
+        let avatar = Avatar::new();
+        avatar.set_size(32);
+        avatar.set_item(Some(user.clone().upcast()));
+        row.add_prefix(&avatar);
+
+        let user_id = user.id();
+        let check_button = gtk::CheckButton::builder()
+            .active(self.imp().selected_users.borrow().contains(&user_id))
+            .valign(gtk::Align::Center)
+            .build();
+        check_button.connect_toggled(clone!(@weak self as obj => move |check_button| {
+            let mut selected_users = obj.imp().selected_users.borrow_mut();
+            if check_button.is_active() {
+                selected_users.insert(user_id);
+            } else {
+                selected_users.remove(&user_id);
+            }
+        }));
+        row.add_suffix(&check_button);
+        row.set_activatable_widget(Some(&check_button));
+
+        row
+    }
+
+    async fn select_photo(&self) {
+        let dialog = gtk::FileDialog::new();
+        let filter = gtk::FileFilter::new();
+        let filters = gio::ListStore::new(gtk::FileFilter::static_type());
+
+        filter.set_name(Some(&gettext("Images")));
+        for mime in PHOTO_MIME_TYPES {
+            filter.add_mime_type(mime);
+        }
+
+        filters.append(&filter);
+        dialog.set_filters(&filters);
+
+        if let Ok(file) = dialog.open_future(Some(self)).await {
+            match gdk::Texture::from_file(&file) {
+                Ok(texture) => {
+                    let imp = self.imp();
+                    imp.photo_avatar.set_custom_image(Some(&texture));
+                    imp.photo_path
+                        .replace(file.path().and_then(|path| path.to_str().map(String::from)));
+                }
+                Err(e) => log::warn!("Error loading the chat photo: {:?}", e),
+            }
+        }
+    }
+
+    async fn create_chat(&self) {
+        let imp = self.imp();
+        let session = imp.session.get().unwrap();
+        let client_id = session.client_id();
+
+        let title = imp.title_row.text().trim().to_string();
+        if title.is_empty() {
+            imp.toast_overlay
+                .add_toast(adw::Toast::new(&gettext("The name can't be empty")));
+            return;
+        }
+
+        self.action_set_enabled("new-chat-window.create", false);
+
+        let user_ids: Vec<i64> = imp.selected_users.borrow().iter().copied().collect();
+        let result = if imp.is_channel.get() {
+            let description = imp.description_row.text().trim().to_string();
+            functions::create_new_supergroup_chat(
+                title,
+                false,
+                true,
+                description,
+                None,
+                0,
+                false,
+                client_id,
+            )
+            .await
+        } else {
+            functions::create_new_basic_group_chat(user_ids.clone(), title, 0, client_id).await
+        };
+
+        let chat_id = match result {
+            Ok(enums::Chat::Chat(chat)) => chat.id,
+            Err(e) => {
+                log::warn!("Error creating a new chat: {:?}", e);
+                imp.toast_overlay
+                    .add_toast(adw::Toast::new(&gettext("Could not create the chat")));
+                self.action_set_enabled("new-chat-window.create", true);
+                return;
+            }
+        };
+
+        let mut failures = Vec::new();
+
+        // The subscribers of a channel can only be added once the channel exists
+        if imp.is_channel.get() && !user_ids.is_empty() {
+            if let Err(e) = functions::add_chat_members(chat_id, user_ids, client_id).await {
+                log::warn!("Error adding members to a new chat: {:?}", e);
+                failures.push(gettext("Could not add the subscribers to the channel"));
+            }
+        }
+
+        let photo_path = imp.photo_path.borrow().clone();
+        if let Some(path) = photo_path {
+            let photo = InputChatPhoto::Static(types::InputChatPhotoStatic {
+                photo: InputFile::Local(types::InputFileLocal { path }),
+            });
+            if let Err(e) = functions::set_chat_photo(chat_id, Some(photo), client_id).await {
+                log::warn!("Error setting the photo of a new chat: {:?}", e);
+                failures.push(gettext("Could not set the photo of the chat"));
+            }
+        }
+
+        session.select_chat(chat_id);
+
+        // The chat exists anyway, so keep the window open only to tell what went wrong
+        if failures.is_empty() {
+            self.close();
+        } else {
+            for failure in failures {
+                imp.toast_overlay.add_toast(adw::Toast::new(&failure));
+            }
+        }
+    }
+}
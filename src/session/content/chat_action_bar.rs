@@ -14,6 +14,7 @@ use once_cell::unsync::OnceCell;
 use tdlib::enums::ChatAction;
 use tdlib::enums::ChatMemberStatus;
 use tdlib::enums::FormattedText;
+use tdlib::enums::InputFile;
 use tdlib::enums::InputMessageContent;
 use tdlib::enums::MessageContent;
 use tdlib::enums::MessageSender as TdMessageSender;
@@ -21,8 +22,11 @@ use tdlib::enums::UserType;
 use tdlib::functions;
 use tdlib::types;
 
+use crate::audio_recorder::AudioRecorder;
+use crate::audio_recorder::VoiceNoteRecording;
 use crate::components::MessageEntry;
 use crate::expressions;
+use crate::session::content::CreatePollWindow;
 use crate::session::content::SendMediaWindow;
 use crate::strings;
 use crate::tdlib::BasicGroup;
@@ -53,6 +57,7 @@ mod imp {
     #[template(resource = "/app/drey/paper-plane/ui/content-chat-action-bar.ui")]
     pub(crate) struct ChatActionBar {
         pub(super) chat: RefCell<Option<Chat>>,
+        pub(super) message_thread_id: Cell<i64>,
         pub(super) chat_action_in_cooldown: Cell<bool>,
         pub(super) state: Cell<ChatActionBarState>,
         pub(super) emoji_chooser: RefCell<Option<gtk::EmojiChooser>>,
@@ -60,6 +65,9 @@ mod imp {
         pub(super) basic_group_signal_group: OnceCell<glib::SignalGroup>,
         pub(super) supergroup_signal_group: OnceCell<glib::SignalGroup>,
         pub(super) bindings: RefCell<Vec<gtk::ExpressionWatch>>,
+        pub(super) recorder: AudioRecorder,
+        /// The timer that updates the duration while recording a voice note.
+        pub(super) recording_source_id: RefCell<Option<glib::SourceId>>,
         #[template_child]
         pub(super) top_bar_revealer: TemplateChild<gtk::Revealer>,
         #[template_child]
@@ -73,7 +81,11 @@ mod imp {
         #[template_child]
         pub(super) send_message_button: TemplateChild<gtk::Button>,
         #[template_child]
-        pub(super) select_file_button: TemplateChild<gtk::Button>,
+        pub(super) record_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) recording_time_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) attach_button: TemplateChild<gtk::MenuButton>,
         #[template_child]
         pub(super) restriction_label: TemplateChild<gtk::Label>,
         #[template_child]
@@ -106,6 +118,9 @@ mod imp {
                     widget.select_file().await;
                 },
             );
+            klass.install_action("chat-action-bar.create-poll", None, move |widget, _, _| {
+                widget.create_poll();
+            });
             klass.install_action_async(
                 "chat-action-bar.send-message",
                 None,
@@ -117,6 +132,27 @@ mod imp {
                     }
                 },
             );
+            klass.install_action(
+                "chat-action-bar.record-voice-note",
+                None,
+                move |widget, _, _| {
+                    widget.start_recording();
+                },
+            );
+            klass.install_action(
+                "chat-action-bar.cancel-recording",
+                None,
+                move |widget, _, _| {
+                    widget.cancel_recording();
+                },
+            );
+            klass.install_action(
+                "chat-action-bar.send-voice-note",
+                None,
+                move |widget, _, _| {
+                    widget.stop_recording();
+                },
+            );
             klass.install_action_async(
                 "chat-action-bar.join-chat",
                 None,
@@ -170,9 +206,14 @@ mod imp {
     impl ObjectImpl for ChatActionBar {
         fn properties() -> &'static [glib::ParamSpec] {
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
-                vec![glib::ParamSpecObject::builder::<Chat>("chat")
-                    .explicit_notify()
-                    .build()]
+                vec![
+                    glib::ParamSpecObject::builder::<Chat>("chat")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecInt64::builder("message-thread-id")
+                        .explicit_notify()
+                        .build(),
+                ]
             });
             PROPERTIES.as_ref()
         }
@@ -180,6 +221,7 @@ mod imp {
         fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
             match pspec.name() {
                 "chat" => self.obj().set_chat(value.get().unwrap()),
+                "message-thread-id" => self.obj().set_message_thread_id(value.get().unwrap()),
                 _ => unimplemented!(),
             }
         }
@@ -187,6 +229,7 @@ mod imp {
         fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
             match pspec.name() {
                 "chat" => self.obj().chat().to_value(),
+                "message-thread-id" => self.obj().message_thread_id().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -210,6 +253,7 @@ mod imp {
                         .map(|f| f.0.text.contains(|c: char| !c.is_whitespace()))
                         .unwrap_or_default();
                     obj.action_set_enabled("chat-action-bar.send-message", should_enable);
+                    obj.update_send_button();
 
                     // Send typing action
                     spawn(clone!(@weak obj => async move {
@@ -231,6 +275,7 @@ mod imp {
             // The message entry is always empty at this point, so disable the
             // send-message action
             obj.action_set_enabled("chat-action-bar.send-message", false);
+            obj.update_send_button();
 
             self.message_entry
                 .connect_activate(clone!(@weak obj => move |_| {
@@ -241,6 +286,9 @@ mod imp {
         }
 
         fn dispose(&self) {
+            if let Some(source_id) = self.recording_source_id.take() {
+                source_id.remove();
+            }
             self.top_bar_revealer.unparent();
             self.action_bar_stack.unparent();
             if let Some(emoji_chooser) = self.emoji_chooser.take() {
@@ -316,11 +364,7 @@ impl ChatActionBar {
             // If we were editing, go back to the previous state by loading the
             // draft message if we have one, otherwise just reset everything
             // and go back to the "Composing" state.
-            if let Some(draft_message) = self.chat().and_then(|c| c.draft_message()) {
-                self.load_draft_message(draft_message);
-            } else {
-                self.reset();
-            }
+            self.load_draft_message_or_reset();
         } else {
             // We were probably replying, so just go back to "Composing" state
             self.set_state(Composing);
@@ -407,14 +451,25 @@ impl ChatActionBar {
         use ChatActionBarState::*;
         let imp = self.imp();
 
-        match imp.state.get() {
+        let is_editing = match imp.state.get() {
             Editing(_) => {
                 imp.send_message_button.set_icon_name("done-symbolic");
+                true
             }
             _ => {
                 imp.send_message_button.set_icon_name("go-up-symbolic");
+                false
             }
-        }
+        };
+
+        // Offer to record a voice note instead of sending an empty message
+        let has_text = imp
+            .message_entry
+            .formatted_text()
+            .map(|f| f.0.text.contains(|c: char| !c.is_whitespace()))
+            .unwrap_or_default();
+        imp.send_message_button.set_visible(is_editing || has_text);
+        imp.record_button.set_visible(!is_editing && !has_text);
     }
 
     fn load_message_to_edit(&self, message_id: i64) {
@@ -477,6 +532,13 @@ impl ChatActionBar {
         emoji_chooser.as_ref().unwrap().popup();
     }
 
+    fn create_poll(&self) {
+        if let Some(chat) = self.chat() {
+            let parent = self.root().and_downcast().unwrap();
+            CreatePollWindow::new(&parent, chat, self.message_thread_id()).present();
+        }
+    }
+
     async fn select_file(&self) {
         let dialog = gtk::FileDialog::new();
         let filter = gtk::FileFilter::new();
@@ -495,19 +557,106 @@ impl ChatActionBar {
             let path = file.path().unwrap().to_str().unwrap().to_string();
             let chat = self.chat().unwrap();
 
-            SendMediaWindow::new(&parent, chat, path).present();
+            SendMediaWindow::new(&parent, chat, self.message_thread_id(), path).present();
         }
     }
 
+    /// Returns whether a voice note is being recorded. The recording may still be finalized
+    /// afterwards, before being sent.
+    fn is_recording(&self) -> bool {
+        self.imp().recording_source_id.borrow().is_some()
+    }
+
+    fn start_recording(&self) {
+        let imp = self.imp();
+
+        // The previous recording is still being finalized
+        if imp.recorder.is_recording() {
+            return;
+        }
+
+        let Some(temp_dir) = temp_dir() else {
+            log::warn!("Error recording a voice note: the temporary directory doesn't exist");
+            return;
+        };
+        let path = temp_dir.join(format!("voice-note-{}.ogg", glib::monotonic_time()));
+
+        if let Err(e) = imp.recorder.start(path.to_str().unwrap()) {
+            log::warn!("Error recording a voice note: {:?}", e);
+            return;
+        }
+
+        imp.action_bar_stack.set_visible_child_name("recording");
+        self.update_recording_time();
+
+        let source_id = glib::timeout_add_seconds_local(
+            1,
+            clone!(@weak self as obj => @default-return glib::Continue(false), move || {
+                obj.update_recording_time();
+                glib::Continue(true)
+            }),
+        );
+        imp.recording_source_id.replace(Some(source_id));
+    }
+
+    fn update_recording_time(&self) {
+        let duration = self.imp().recorder.duration();
+        self.imp().recording_time_label.set_label(&format!(
+            "{}:{:02}",
+            duration / 60,
+            duration % 60
+        ));
+
+        spawn(clone!(@weak self as obj => async move {
+            obj.send_chat_action(ChatAction::RecordingVoiceNote).await;
+        }));
+    }
+
+    fn finish_recording(&self) {
+        if let Some(source_id) = self.imp().recording_source_id.take() {
+            source_id.remove();
+        }
+        self.update_stack_page();
+    }
+
+    fn cancel_recording(&self) {
+        self.imp().recorder.cancel();
+        self.finish_recording();
+    }
+
+    fn stop_recording(&self) {
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        let message_thread_id = self.message_thread_id();
+        let reply_to_message_id = if let ChatActionBarState::Replying(id) = self.imp().state.get() {
+            id
+        } else {
+            0
+        };
+
+        self.imp().recorder.stop(move |recording| {
+            if let Some(recording) = recording {
+                spawn(async move {
+                    send_voice_note(chat, message_thread_id, reply_to_message_id, recording).await;
+                });
+            }
+        });
+
+        self.finish_recording();
+        self.set_state(ChatActionBarState::Composing);
+    }
+
     async fn edit_message(&self) {
         if let Some(chat) = self.chat() {
             if let ChatActionBarState::Editing(message_id) = self.imp().state.get() {
                 if let Some(message) = self.compose_text_message().await {
-                    let client_id = chat.session().client_id();
-                    let chat_id = chat.id();
-
-                    let result =
-                        functions::edit_message_text(chat_id, message_id, message, client_id).await;
+                    let result = chat
+                        .session()
+                        .backend()
+                        .edit_message_text(chat.id(), message_id, message)
+                        .await;
                     if let Err(e) = result {
                         log::warn!("Error editing a text message: {:?}", e);
                     }
@@ -521,8 +670,6 @@ impl ChatActionBar {
     async fn send_text_message(&self) {
         if let Some(chat) = self.chat() {
             if let Some(message) = self.compose_text_message().await {
-                let client_id = chat.session().client_id();
-                let chat_id = chat.id();
                 let reply_to_message_id =
                     if let ChatActionBarState::Replying(id) = self.imp().state.get() {
                         id
@@ -531,15 +678,17 @@ impl ChatActionBar {
                     };
 
                 // Send the message
-                let result = functions::send_message(
-                    chat_id,
-                    0,
-                    reply_to_message_id,
-                    None,
-                    message,
-                    client_id,
-                )
-                .await;
+                let result = chat
+                    .session()
+                    .backend()
+                    .send_message(
+                        chat.id(),
+                        self.message_thread_id(),
+                        reply_to_message_id,
+                        None,
+                        message,
+                    )
+                    .await;
                 if let Err(e) = result {
                     log::warn!("Error sending a message: {:?}", e);
                 }
@@ -597,6 +746,11 @@ impl ChatActionBar {
     }
 
     async fn save_message_as_draft(&self) {
+        // Drafts are only kept for the chat itself, not for its message threads
+        if self.message_thread_id() != 0 {
+            return;
+        }
+
         if let Some(chat) = self.chat() {
             let client_id = chat.session().client_id();
             let chat_id = chat.id();
@@ -652,14 +806,15 @@ impl ChatActionBar {
         }
 
         if let Some(chat) = self.chat() {
-            let client_id = chat.session().client_id();
-            let chat_id = chat.id();
-
             // Enable chat action cooldown right away
             imp.chat_action_in_cooldown.set(true);
 
             // Send typing action
-            let result = functions::send_chat_action(chat_id, 0, Some(action), client_id).await;
+            let result = chat
+                .session()
+                .backend()
+                .send_chat_action(chat.id(), self.message_thread_id(), Some(action))
+                .await;
             if result.is_ok() {
                 glib::timeout_add_seconds_local_once(
                     5,
@@ -703,7 +858,7 @@ impl ChatActionBar {
 
             let parent = self.root().and_downcast().unwrap();
             let path = path.to_str().unwrap().to_string();
-            SendMediaWindow::new(&parent, chat, path).present();
+            SendMediaWindow::new(&parent, chat, self.message_thread_id(), path).present();
         }
 
         Ok(())
@@ -718,6 +873,10 @@ impl ChatActionBar {
             return;
         }
 
+        if self.is_recording() {
+            self.cancel_recording();
+        }
+
         spawn(clone!(@weak self as obj => async move {
             obj.save_message_as_draft().await;
         }));
@@ -728,15 +887,22 @@ impl ChatActionBar {
             binding.unwatch();
         }
 
+        imp.chat.replace(chat.clone());
+
         if let Some(ref chat) = chat {
-            if let Some(draft_message) = chat.draft_message() {
-                self.load_draft_message(draft_message);
-            } else {
-                self.reset();
-            }
+            self.load_draft_message_or_reset();
 
             imp.chat_action_in_cooldown.set(false);
 
+            // Polls can't be sent to private chats
+            self.action_set_enabled(
+                "chat-action-bar.create-poll",
+                matches!(
+                    chat.type_(),
+                    ChatType::BasicGroup(_) | ChatType::Supergroup(_)
+                ),
+            );
+
             // Handle restriction_label caption
             let restriction_label_binding = expressions::restriction_expression(chat).bind(
                 &*imp.restriction_label,
@@ -747,14 +913,46 @@ impl ChatActionBar {
             bindings.push(restriction_label_binding);
         }
 
-        imp.chat.replace(chat);
-
         self.update_stack_page();
         self.update_signal_groups();
 
         self.notify("chat");
     }
 
+    /// Returns the message thread or forum topic in which the messages are sent, or 0.
+    pub(crate) fn message_thread_id(&self) -> i64 {
+        self.imp().message_thread_id.get()
+    }
+
+    fn set_message_thread_id(&self, message_thread_id: i64) {
+        if self.message_thread_id() == message_thread_id {
+            return;
+        }
+
+        self.imp().message_thread_id.set(message_thread_id);
+
+        if self.chat().is_some() {
+            self.load_draft_message_or_reset();
+        }
+
+        self.notify("message-thread-id");
+    }
+
+    /// Loads the draft message of the chat, if there's one and no message thread is selected.
+    fn load_draft_message_or_reset(&self) {
+        let draft_message = if self.message_thread_id() == 0 {
+            self.chat().and_then(|c| c.draft_message())
+        } else {
+            None
+        };
+
+        if let Some(draft_message) = draft_message {
+            self.load_draft_message(draft_message);
+        } else {
+            self.reset();
+        }
+    }
+
     pub(crate) fn reply_to_message_id(&self, message_id: i64) {
         self.set_state(ChatActionBarState::Replying(message_id));
     }
@@ -765,6 +963,12 @@ impl ChatActionBar {
 
     fn update_stack_page(&self) {
         let imp = self.imp();
+
+        if self.is_recording() {
+            imp.action_bar_stack.set_visible_child_name("recording");
+            return;
+        }
+
         if let Some(chat) = self.chat() {
             match chat.type_() {
                 ChatType::Private(user) => {
@@ -859,6 +1063,37 @@ impl ChatActionBar {
     }
 }
 
+async fn send_voice_note(
+    chat: Chat,
+    message_thread_id: i64,
+    reply_to_message_id: i64,
+    recording: VoiceNoteRecording,
+) {
+    let content = InputMessageContent::InputMessageVoiceNote(types::InputMessageVoiceNote {
+        voice_note: InputFile::Local(types::InputFileLocal {
+            path: recording.path,
+        }),
+        duration: recording.duration,
+        waveform: recording.waveform,
+        caption: None,
+    });
+
+    let result = chat
+        .session()
+        .backend()
+        .send_message(
+            chat.id(),
+            message_thread_id,
+            reply_to_message_id,
+            None,
+            content,
+        )
+        .await;
+    if let Err(e) = result {
+        log::warn!("Error sending a voice note: {:?}", e);
+    }
+}
+
 async fn save_stream_to_file(
     stream: gio::InputStream,
     path: impl AsRef<std::path::Path>,
@@ -1,10 +1,13 @@
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::collections::HashSet;
+use std::time::Duration;
 
 use adw::prelude::*;
 use adw::subclass::prelude::BinImpl;
 use gettextrs::gettext;
 use glib::clone;
+use gtk::gdk;
 use gtk::gio;
 use gtk::glib;
 use gtk::subclass::prelude::*;
@@ -12,22 +15,36 @@ use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use once_cell::unsync::OnceCell;
 use tdlib::enums::ChatMemberStatus;
+use tdlib::enums::MessageSource;
 use tdlib::functions;
 
 use crate::expressions;
 use crate::session::content::Background;
 use crate::session::content::ChatActionBar;
 use crate::session::content::ChatHistoryError;
+use crate::session::content::ChatHistoryItem;
+use crate::session::content::ChatHistoryItemType;
 use crate::session::content::ChatHistoryModel;
 use crate::session::content::ChatHistoryRow;
 use crate::session::content::ChatInfoWindow;
+use crate::session::content::ChatSearchBar;
+use crate::session::content::MessageReactionPicker;
+use crate::session::content::MiniPlayer;
+use crate::session::content::PinnedMessageBar;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatType;
+use crate::tdlib::ForumTopic;
+use crate::tdlib::Message;
 use crate::tdlib::SponsoredMessage;
 use crate::utils::spawn;
 use crate::Session;
 
 const MIN_N_ITEMS: u32 = 20;
+/// The number of frames to wait for the row of a message to be shown, before giving up on
+/// highlighting it.
+const MAX_HIGHLIGHT_FRAMES: u32 = 30;
+/// The time to wait after scrolling, before reporting the shown messages as viewed.
+const VIEW_MESSAGES_DELAY: Duration = Duration::from_millis(300);
 
 mod imp {
     use super::*;
@@ -36,14 +53,27 @@ mod imp {
     #[template(resource = "/app/drey/paper-plane/ui/content-chat-history.ui")]
     pub(crate) struct ChatHistory {
         pub(super) chat: RefCell<Option<Chat>>,
+        pub(super) topic: RefCell<Option<ForumTopic>>,
         pub(super) chat_handler: RefCell<Option<glib::SignalHandlerId>>,
         pub(super) model: RefCell<Option<ChatHistoryModel>>,
         pub(super) message_menu: OnceCell<gtk::PopoverMenu>,
+        pub(super) reaction_picker: MessageReactionPicker,
         pub(super) is_auto_scrolling: Cell<bool>,
         pub(super) sticky: Cell<bool>,
+        /// The messages that were already reported as viewed since the history was shown.
+        pub(super) viewed_message_ids: RefCell<HashSet<i64>>,
+        pub(super) view_messages_source_id: RefCell<Option<glib::SourceId>>,
+        #[template_child]
+        pub(super) back_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) topics_button: TemplateChild<gtk::Button>,
         #[template_child]
         pub(super) window_title: TemplateChild<adw::WindowTitle>,
         #[template_child]
+        pub(super) search_bar: TemplateChild<ChatSearchBar>,
+        #[template_child]
+        pub(super) mini_player: TemplateChild<MiniPlayer>,
+        #[template_child]
         pub(super) background: TemplateChild<Background>,
         #[template_child]
         pub(super) scrolled_window: TemplateChild<gtk::ScrolledWindow>,
@@ -61,13 +91,25 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             ChatHistoryRow::static_type();
+            ChatSearchBar::static_type();
+            MiniPlayer::static_type();
+            PinnedMessageBar::static_type();
             klass.bind_template();
 
             klass.install_action("chat-history.view-info", None, move |widget, _, _| {
                 widget.open_info_dialog();
             });
+            klass.add_binding_action(
+                gdk::Key::f,
+                gdk::ModifierType::CONTROL_MASK,
+                "chat-history.search",
+                None,
+            );
+            klass.install_action("chat-history.search", None, move |widget, _, _| {
+                widget.imp().search_bar.set_search_mode_enabled(true);
+            });
             klass.install_action("chat-history.scroll-down", None, move |widget, _, _| {
-                widget.scroll_down();
+                widget.scroll_to_bottom();
             });
             klass.install_action(
                 "chat-history.reply",
@@ -81,6 +123,14 @@ mod imp {
                 let message_id = variant.and_then(|v| v.get()).unwrap();
                 widget.imp().chat_action_bar.edit_message_id(message_id);
             });
+            klass.install_action_async(
+                "chat-history.scroll-to-message",
+                Some("x"),
+                |widget, _, variant| async move {
+                    let message_id = variant.and_then(|v| v.get()).unwrap();
+                    widget.scroll_to_message(message_id).await;
+                },
+            );
             klass.install_action_async(
                 "chat-history.leave-chat",
                 None,
@@ -102,6 +152,12 @@ mod imp {
                     glib::ParamSpecObject::builder::<Chat>("chat")
                         .explicit_notify()
                         .build(),
+                    glib::ParamSpecObject::builder::<ForumTopic>("topic")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecInt64::builder("message-thread-id")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecBoolean::builder("sticky")
                         .read_only()
                         .build(),
@@ -118,6 +174,10 @@ mod imp {
                     let chat = value.get().unwrap();
                     obj.set_chat(chat);
                 }
+                "topic" => {
+                    let topic = value.get().unwrap();
+                    obj.set_topic(topic);
+                }
                 "sticky" => obj.set_sticky(value.get().unwrap()),
                 _ => unimplemented!(),
             }
@@ -128,6 +188,8 @@ mod imp {
 
             match pspec.name() {
                 "chat" => obj.chat().to_value(),
+                "topic" => obj.topic().to_value(),
+                "message-thread-id" => obj.message_thread_id().to_value(),
                 "sticky" => obj.sticky().to_value(),
                 _ => unimplemented!(),
             }
@@ -150,20 +212,51 @@ mod imp {
                         obj.set_sticky(true);
                     }
                 } else {
-                    obj.set_sticky(adj.value() + adj.page_size() >= adj.upper());
+                    // The history only sticks to the bottom if the newest message is loaded
+                    obj.set_sticky(
+                        adj.value() + adj.page_size() >= adj.upper() && obj.has_newest_message(),
+                    );
                     obj.load_older_messages(adj);
+                    obj.load_newer_messages(adj);
                 }
+
+                obj.queue_view_messages();
             }));
 
             adj.connect_upper_notify(clone!(@weak obj => move |_| {
                 if obj.sticky() || obj.imp().is_auto_scrolling.get() {
                     obj.scroll_down();
                 }
+
+                obj.queue_view_messages();
             }));
         }
+
+        fn dispose(&self) {
+            if let Some(source_id) = self.view_messages_source_id.take() {
+                source_id.remove();
+            }
+            if let Some(chat) = self.chat.take() {
+                if let Some(handler) = self.chat_handler.take() {
+                    chat.disconnect(handler);
+                }
+                spawn(async move {
+                    if let Err(e) = chat.close().await {
+                        log::warn!("Error closing a chat: {:?}", e);
+                    }
+                });
+            }
+        }
     }
 
     impl WidgetImpl for ChatHistory {
+        fn map(&self) {
+            self.parent_map();
+
+            // Messages aren't viewed while the history is hidden, so view the shown ones now
+            self.obj().queue_view_messages();
+        }
+
         fn direction_changed(&self, previous_direction: gtk::TextDirection) {
             let obj = self.obj();
 
@@ -223,6 +316,87 @@ impl ChatHistory {
         }
     }
 
+    fn load_newer_messages(&self, adj: &gtk::Adjustment) {
+        if adj.value() + adj.page_size() * 3.0 >= adj.upper() {
+            if let Some(model) = self.imp().model.borrow().as_ref() {
+                spawn(clone!(@weak model => async move {
+                    if let Err(ChatHistoryError::Tdlib(e)) = model.load_newer_messages(20).await {
+                        log::warn!("Couldn't load newer chat messages: {:?}", e);
+                    }
+                }));
+            }
+        }
+    }
+
+    fn has_newest_message(&self) -> bool {
+        self.imp()
+            .model
+            .borrow()
+            .as_ref()
+            .map_or(true, ChatHistoryModel::has_newest_message)
+    }
+
+    fn load_initial_messages(&self, model: &ChatHistoryModel) {
+        spawn(clone!(@weak model => async move {
+            while model.n_items() < MIN_N_ITEMS {
+                let limit = MIN_N_ITEMS - model.n_items();
+                match model.load_older_messages(limit as i32).await {
+                    Ok(can_load_more) => if !can_load_more {
+                        break;
+                    }
+                    // A newer load was started in place of this one
+                    Err(ChatHistoryError::Reset) => break,
+                    Err(e) => {
+                        log::warn!("Couldn't load initial history messages: {}", e);
+                        break;
+                    }
+                }
+            }
+        }));
+    }
+
+    /// Loads the messages around the last read message and scrolls to the first unread one.
+    fn load_unread_messages(&self, model: &ChatHistoryModel, last_read_message_id: i64) {
+        let imp = self.imp();
+
+        // Don't let the history jump to the bottom while the messages are loading
+        imp.is_auto_scrolling.set(false);
+        self.set_sticky(false);
+
+        spawn(clone!(@weak self as obj, @weak model => async move {
+            let result = model
+                .load_messages_around(last_read_message_id, MIN_N_ITEMS as i32 * 2)
+                .await;
+
+            // Another chat may have been opened in the meantime
+            if obj.imp().model.borrow().as_ref() != Some(&model) {
+                return;
+            }
+
+            match result {
+                Ok(()) => {}
+                // The history was scrolled to the bottom in the meantime
+                Err(ChatHistoryError::Reset) => return,
+                Err(e) => {
+                    log::warn!("Couldn't load the unread messages: {}", e);
+                    obj.load_initial_messages(&model);
+                    return;
+                }
+            }
+
+            match obj.unread_divider_position() {
+                Some(position) => {
+                    obj.imp()
+                        .list_view
+                        .activate_action("list.scroll-to-item", Some(&position.to_variant()))
+                        .unwrap();
+                }
+                // The unread messages may have been deleted in the meantime
+                None => obj.scroll_down(),
+            }
+        }));
+    }
+
     fn open_info_dialog(&self) {
         if let Some(chat) = self.chat() {
             ChatInfoWindow::new(&self.parent_window(), &chat).present();
@@ -274,12 +448,20 @@ impl ChatHistory {
         }));
     }
 
-    pub(crate) fn message_menu(&self) -> &gtk::PopoverMenu {
-        self.imp().message_menu.get_or_init(|| {
+    /// Returns the context menu of the messages, prepared for the specified message.
+    pub(crate) fn message_menu(&self, message: &glib::Object) -> &gtk::PopoverMenu {
+        let imp = self.imp();
+
+        imp.reaction_picker
+            .set_message(message.downcast_ref::<Message>());
+
+        imp.message_menu.get_or_init(|| {
             let menu = gtk::Builder::from_resource("/app/drey/paper-plane/ui/message-menu.ui")
                 .object::<gtk::PopoverMenu>("menu")
                 .unwrap();
 
+            menu.add_child(&imp.reaction_picker, "reaction-picker");
+
             menu.set_halign(if self.direction() == gtk::TextDirection::Rtl {
                 gtk::Align::End
             } else {
@@ -299,10 +481,56 @@ impl ChatHistory {
     }
 
     pub(crate) fn set_chat(&self, chat: Option<Chat>) {
-        if self.chat() == chat {
+        if self.chat() == chat && self.topic().is_none() && self.message_thread_id() == 0 {
             return;
         }
 
+        self.set_history(chat, None, 0);
+    }
+
+    pub(crate) fn topic(&self) -> Option<ForumTopic> {
+        self.imp().topic.borrow().clone()
+    }
+
+    /// Shows the history of a forum topic. Passing `None` shows the whole history of the current
+    /// chat again.
+    pub(crate) fn set_topic(&self, topic: Option<ForumTopic>) {
+        if self.topic() == topic {
+            return;
+        }
+
+        let chat = match &topic {
+            Some(topic) => Some(topic.chat()),
+            None => self.chat(),
+        };
+        self.set_history(chat, topic, 0);
+    }
+
+    /// Shows the replies to a message, or the comments of a channel post, which are contained
+    /// in the specified message thread of the chat.
+    pub(crate) fn set_message_thread(&self, chat: Chat, message_thread_id: i64, is_comments: bool) {
+        self.set_history(Some(chat), None, message_thread_id);
+
+        let subtitle = if is_comments {
+            gettext("Comments")
+        } else {
+            gettext("Replies")
+        };
+        self.imp().window_title.set_subtitle(&subtitle);
+    }
+
+    /// Returns the id of the message thread the history is restricted to, or 0 if the whole
+    /// history of the chat is shown.
+    pub(crate) fn message_thread_id(&self) -> i64 {
+        self.imp()
+            .model
+            .borrow()
+            .as_ref()
+            .map(ChatHistoryModel::message_thread_id)
+            .unwrap_or_default()
+    }
+
+    fn set_history(&self, chat: Option<Chat>, topic: Option<ForumTopic>, message_thread_id: i64) {
         let imp = self.imp();
 
         if let Some(ref chat) = chat {
@@ -315,7 +543,13 @@ impl ChatHistory {
                 },
             );
 
-            let model = ChatHistoryModel::new(chat);
+            imp.mini_player
+                .set_playlist(Some(chat.session().audio_playlist()));
+
+            let model = match &topic {
+                Some(topic) => ChatHistoryModel::for_topic(topic),
+                None => ChatHistoryModel::new(chat, message_thread_id),
+            };
 
             // Request sponsored message, if needed
             let list_view_model: gio::ListModel = if matches!(chat.type_(), ChatType::Supergroup(supergroup) if supergroup.is_channel())
@@ -335,20 +569,12 @@ impl ChatHistory {
                 model.clone().upcast()
             };
 
-            spawn(clone!(@weak model => async move {
-                while model.n_items() < MIN_N_ITEMS {
-                    let limit = MIN_N_ITEMS - model.n_items();
-                    match model.load_older_messages(limit as i32).await {
-                        Ok(can_load_more) => if !can_load_more {
-                            break;
-                        }
-                        Err(e) => {
-                            log::warn!("Couldn't load initial history messages: {}", e);
-                            break;
-                        }
-                    }
+            match model.last_read_message_id() {
+                Some(last_read_message_id) => {
+                    self.load_unread_messages(&model, last_read_message_id)
                 }
-            }));
+                None => self.load_initial_messages(&model),
+            }
 
             let handler = chat.connect_new_message(clone!(@weak self as obj => move |_, msg| {
                 if msg.is_outgoing() {
@@ -364,12 +590,47 @@ impl ChatHistory {
 
             let selection = gtk::NoSelection::new(Some(list_view_model));
             imp.list_view.set_model(Some(&selection));
+            imp.viewed_message_ids.borrow_mut().clear();
 
             imp.model.replace(Some(model));
+        } else if let Some(old_handler) = imp.chat_handler.take() {
+            if let Some(old_chat) = &*imp.chat.borrow() {
+                old_chat.disconnect(old_handler);
+            }
         }
 
-        imp.chat.replace(chat);
-        self.notify("chat");
+        imp.window_title
+            .set_subtitle(&topic.as_ref().map(ForumTopic::name).unwrap_or_default());
+        imp.topics_button.set_visible(topic.is_some());
+        imp.back_button
+            .set_visible(topic.is_none() && message_thread_id != 0);
+
+        let topic_changed = self.topic() != topic;
+        imp.topic.replace(topic);
+
+        // The chat must be notified first, so that the chat action bar switches to the new chat
+        // before it targets the new message thread
+        if self.chat() != chat {
+            if let Some(old_chat) = imp.chat.replace(chat.clone()) {
+                spawn(async move {
+                    if let Err(e) = old_chat.close().await {
+                        log::warn!("Error closing a chat: {:?}", e);
+                    }
+                });
+            }
+            if let Some(chat) = chat {
+                spawn(async move {
+                    if let Err(e) = chat.open().await {
+                        log::warn!("Error opening a chat: {:?}", e);
+                    }
+                });
+            }
+            self.notify("chat");
+        }
+        if topic_changed {
+            self.notify("topic");
+        }
+        self.notify("message-thread-id");
     }
 
     pub(crate) fn sticky(&self) -> bool {
@@ -385,6 +646,185 @@ impl ChatHistory {
         self.notify("sticky");
     }
 
+    /// Scrolls to the specified message and highlights it. If the message isn't loaded, the
+    /// messages around it are loaded in place of the current ones.
+    pub(crate) async fn scroll_to_message(&self, message_id: i64) {
+        let imp = self.imp();
+
+        let Some(model) = imp.model.borrow().clone() else {
+            return;
+        };
+
+        if self.message_position(message_id).is_none() {
+            // Don't let the history jump back to the bottom while the messages are replaced
+            imp.is_auto_scrolling.set(false);
+            self.set_sticky(false);
+
+            let result = model
+                .load_messages_around(message_id, MIN_N_ITEMS as i32 * 2)
+                .await;
+
+            // Another chat may have been opened in the meantime
+            if imp.model.borrow().as_ref() != Some(&model) {
+                return;
+            }
+
+            match result {
+                Ok(()) => {}
+                // Another message was jumped to, or the history was reset, in the meantime
+                Err(ChatHistoryError::Reset) => return,
+                Err(e) => {
+                    log::warn!("Couldn't load the messages around a message: {}", e);
+                    return;
+                }
+            }
+        }
+
+        if let Some(position) = self.message_position(message_id) {
+            imp.list_view
+                .activate_action("list.scroll-to-item", Some(&position.to_variant()))
+                .unwrap();
+            self.highlight_message(message_id);
+        }
+    }
+
+    /// Highlights the row of a message once it's shown, which may take a few frames after
+    /// scrolling to it.
+    fn highlight_message(&self, message_id: i64) {
+        let n_frames = Cell::new(0);
+        self.imp().list_view.add_tick_callback(move |list_view, _| {
+            let row = std::iter::successors(list_view.first_child(), |w| w.next_sibling())
+                .filter_map(|item_widget| item_widget.first_child())
+                .filter_map(|w| w.downcast::<ChatHistoryRow>().ok())
+                .find(|row| {
+                    row.item()
+                        .and_downcast::<ChatHistoryItem>()
+                        .and_then(|item| item.message().map(Message::id))
+                        == Some(message_id)
+                });
+
+            n_frames.set(n_frames.get() + 1);
+            match row {
+                Some(row) => {
+                    row.highlight();
+                    glib::Continue(false)
+                }
+                None => glib::Continue(n_frames.get() < MAX_HIGHLIGHT_FRAMES),
+            }
+        });
+    }
+
+    /// Returns the position of a message in the list view, if it's loaded.
+    fn message_position(&self, message_id: i64) -> Option<u32> {
+        let model = self.imp().list_view.model()?;
+
+        (0..model.n_items()).find(|position| {
+            model
+                .item(*position)
+                .and_downcast::<ChatHistoryItem>()
+                .map_or(false, |item| {
+                    item.message().map(Message::id) == Some(message_id)
+                })
+        })
+    }
+
+    /// Reports the shown messages as viewed once scrolling settles down.
+    fn queue_view_messages(&self) {
+        let imp = self.imp();
+
+        if !self.is_mapped() || imp.view_messages_source_id.borrow().is_some() {
+            return;
+        }
+
+        let source_id = glib::timeout_add_local_once(
+            VIEW_MESSAGES_DELAY,
+            clone!(@weak self as obj => move || {
+                obj.imp().view_messages_source_id.take();
+                obj.view_visible_messages();
+            }),
+        );
+        imp.view_messages_source_id.replace(Some(source_id));
+    }
+
+    /// Marks the messages whose rows are within the viewport as viewed, which also reads them.
+    fn view_visible_messages(&self) {
+        let imp = self.imp();
+
+        // A hidden history, like the one of a chat behind an open thread, must not read messages
+        if !self.is_mapped() {
+            return;
+        }
+
+        let Some(model) = imp.model.borrow().clone() else {
+            return;
+        };
+
+        let height = imp.scrolled_window.height() as f32;
+        let mut viewed_message_ids = imp.viewed_message_ids.borrow_mut();
+        let message_ids: Vec<i64> =
+            std::iter::successors(imp.list_view.first_child(), |w| w.next_sibling())
+                .filter_map(|item_widget| item_widget.first_child())
+                .filter_map(|w| w.downcast::<ChatHistoryRow>().ok())
+                .filter(|row| {
+                    row.compute_bounds(&*imp.scrolled_window)
+                        .map_or(false, |bounds| {
+                            bounds.y() < height && bounds.y() + bounds.height() > 0.0
+                        })
+                })
+                .filter_map(|row| {
+                    row.item()
+                        .and_downcast::<ChatHistoryItem>()
+                        .and_then(|item| item.message().map(Message::id))
+                })
+                .filter(|message_id| viewed_message_ids.insert(*message_id))
+                .collect();
+
+        if message_ids.is_empty() {
+            return;
+        }
+
+        let source = if self.topic().is_some() {
+            MessageSource::ForumTopicHistory
+        } else if model.message_thread_id() != 0 {
+            MessageSource::MessageThreadHistory
+        } else {
+            MessageSource::ChatHistory
+        };
+
+        spawn(async move {
+            if let Err(e) = model.chat().view_messages(message_ids, source).await {
+                log::warn!("Error viewing messages: {:?}", e);
+            }
+        });
+    }
+
+    /// Returns the position of the unread divider in the list view, if it's loaded.
+    fn unread_divider_position(&self) -> Option<u32> {
+        let model = self.imp().list_view.model()?;
+
+        (0..model.n_items()).find(|position| {
+            model
+                .item(*position)
+                .and_downcast::<ChatHistoryItem>()
+                .map_or(false, |item| {
+                    matches!(item.type_(), ChatHistoryItemType::UnreadDivider)
+                })
+        })
+    }
+
+    /// Scrolls to the newest message, loading it again if the history was moved to an older
+    /// message.
+    fn scroll_to_bottom(&self) {
+        if let Some(model) = self.imp().model.borrow().as_ref() {
+            if !model.has_newest_message() {
+                model.reset();
+                self.load_initial_messages(model);
+            }
+        }
+
+        self.scroll_down();
+    }
+
     fn scroll_down(&self) {
         let imp = self.imp();
 
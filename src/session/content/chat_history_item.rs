@@ -12,6 +12,9 @@ use crate::tdlib::Message;
 pub(crate) enum ChatHistoryItemType {
     Message(Message),
     DayDivider(DateTime),
+    /// The divider above the first unread message, which stays where it is until the chat is
+    /// opened again.
+    UnreadDivider,
 }
 
 mod imp {
@@ -66,6 +69,11 @@ impl ChatHistoryItem {
         glib::Object::builder().property("type", type_).build()
     }
 
+    pub(crate) fn for_unread_divider() -> Self {
+        let type_ = ChatHistoryItemType::UnreadDivider;
+        glib::Object::builder().property("type", type_).build()
+    }
+
     pub(crate) fn type_(&self) -> &ChatHistoryItemType {
         self.imp().type_.get().unwrap()
     }
@@ -15,12 +15,15 @@ use thiserror::Error;
 use crate::session::content::ChatHistoryItem;
 use crate::session::content::ChatHistoryItemType;
 use crate::tdlib::Chat;
+use crate::tdlib::ForumTopic;
 use crate::tdlib::Message;
 
 #[derive(Error, Debug)]
 pub(crate) enum ChatHistoryError {
     #[error("The chat history is already loading messages")]
     AlreadyLoading,
+    #[error("The chat history was reset while loading messages")]
+    Reset,
     #[error("TDLib error: {0:?}")]
     Tdlib(tdlib::types::Error),
 }
@@ -31,7 +34,17 @@ mod imp {
     #[derive(Debug, Default)]
     pub(crate) struct ChatHistoryModel {
         pub(super) chat: WeakRef<Chat>,
+        pub(super) message_thread_id: Cell<i64>,
+        pub(super) is_general_topic: Cell<bool>,
         pub(super) is_loading: Cell<bool>,
+        /// Incremented on every reset, so that the messages requested before it are discarded.
+        pub(super) generation: Cell<u32>,
+        /// Whether the newest message of the history is loaded. If it isn't, the loaded messages
+        /// are a window around a message that was jumped to.
+        pub(super) has_newest_message: Cell<bool>,
+        /// The last read message when the history was created, or 0 if there were no unread
+        /// messages. The unread divider is placed below the messages that are newer than this.
+        pub(super) last_read_message_id: Cell<i64>,
         pub(super) list: RefCell<VecDeque<ChatHistoryItem>>,
     }
 
@@ -45,9 +58,14 @@ mod imp {
     impl ObjectImpl for ChatHistoryModel {
         fn properties() -> &'static [glib::ParamSpec] {
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
-                vec![glib::ParamSpecObject::builder::<Chat>("chat")
-                    .read_only()
-                    .build()]
+                vec![
+                    glib::ParamSpecObject::builder::<Chat>("chat")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecInt64::builder("message-thread-id")
+                        .read_only()
+                        .build(),
+                ]
             });
             PROPERTIES.as_ref()
         }
@@ -57,6 +75,7 @@ mod imp {
 
             match pspec.name() {
                 "chat" => obj.chat().to_value(),
+                "message-thread-id" => obj.message_thread_id().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -87,54 +106,288 @@ glib::wrapper! {
 }
 
 impl ChatHistoryModel {
-    pub(crate) fn new(chat: &Chat) -> Self {
+    /// Creates the history of a chat. If `message_thread_id` isn't 0, the history only contains
+    /// the messages of that message thread or forum topic.
+    pub(crate) fn new(chat: &Chat, message_thread_id: i64) -> Self {
         let obj: ChatHistoryModel = glib::Object::new();
+        let imp = obj.imp();
 
-        obj.imp().chat.set(Some(chat));
+        imp.chat.set(Some(chat));
+        imp.message_thread_id.set(message_thread_id);
+        imp.has_newest_message.set(true);
+
+        // Message threads have their own read state, which isn't tracked
+        if message_thread_id == 0 && chat.unread_count() > 0 {
+            imp.last_read_message_id
+                .set(chat.last_read_inbox_message_id());
+        }
 
         chat.connect_new_message(clone!(@weak obj => move |_, message| {
-            obj.push_front(message);
+            // New messages are loaded later when paging forward, to avoid leaving a gap
+            if obj.has_newest_message() && obj.contains_thread_of(&message) {
+                obj.push_front(message);
+            }
         }));
         chat.connect_deleted_message(clone!(@weak obj => move |_, message| {
-            obj.remove(message);
+            if obj.contains_thread_of(&message) {
+                obj.remove(message);
+            }
         }));
 
         obj
     }
 
+    /// Creates the history of a forum topic.
+    pub(crate) fn for_topic(topic: &ForumTopic) -> Self {
+        let obj = Self::new(&topic.chat(), topic.message_thread_id());
+        obj.imp().is_general_topic.set(topic.is_general());
+        obj
+    }
+
+    /// Returns whether the message belongs to the message thread of this history.
+    fn contains_thread_of(&self, message: &Message) -> bool {
+        let message_thread_id = self.message_thread_id();
+        message_thread_id == 0
+            || message.message_thread_id() == message_thread_id
+            || message.id() == message_thread_id
+            // The General topic also contains the messages sent before the chat became a forum
+            || (self.imp().is_general_topic.get() && !message.is_topic_message())
+    }
+
     /// Loads older messages from this chat history.
     ///
     /// Returns `true` when more messages can be loaded.
     pub(crate) async fn load_older_messages(&self, limit: i32) -> Result<bool, ChatHistoryError> {
+        let oldest_message_id = self
+            .imp()
+            .list
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|item| item.message())
+            .map(|m| m.id())
+            .unwrap_or_default();
+
+        let messages = self.load(oldest_message_id, 0, limit).await?;
+
+        if messages.is_empty() {
+            return Ok(false);
+        }
+
+        self.append(messages);
+        self.insert_unread_divider();
+
+        Ok(true)
+    }
+
+    /// Loads newer messages from this chat history, if the newest message isn't loaded yet.
+    ///
+    /// Returns `true` when more messages can be loaded.
+    pub(crate) async fn load_newer_messages(&self, limit: i32) -> Result<bool, ChatHistoryError> {
+        if self.has_newest_message() {
+            return Ok(false);
+        }
+
+        let newest_message_id = self.newest_message_id();
+
+        // TDLib may return fewer messages than requested, or none at all while it's fetching
+        // them from the server, so the newest message is only assumed to be reached once no
+        // newer messages are returned twice
+        for _ in 0..2 {
+            let messages: Vec<Message> = self
+                .load(newest_message_id, -limit, limit + 1)
+                .await?
+                .into_iter()
+                // The newest loaded message is also returned
+                .filter(|message| message.id() > newest_message_id)
+                .collect();
+
+            if !messages.is_empty() {
+                self.prepend(messages);
+                self.insert_unread_divider();
+                self.update_has_newest_message();
+
+                return Ok(!self.has_newest_message());
+            }
+        }
+
+        self.imp().has_newest_message.set(true);
+        Ok(false)
+    }
+
+    /// Replaces the loaded messages with the ones around the specified message, so that the
+    /// history can be paged in both directions from there. The messages that are still being
+    /// loaded are discarded, as they would be replaced anyway.
+    pub(crate) async fn load_messages_around(
+        &self,
+        message_id: i64,
+        limit: i32,
+    ) -> Result<(), ChatHistoryError> {
+        let imp = self.imp();
+        imp.generation.set(imp.generation.get() + 1);
+        imp.is_loading.set(false);
+
+        let messages = self.load(message_id, -limit / 2, limit).await?;
+
+        self.clear();
+        self.append(messages);
+        self.insert_unread_divider();
+        self.update_has_newest_message();
+
+        Ok(())
+    }
+
+    /// Removes all the loaded messages, so that the history can be loaded again from the newest
+    /// message. The messages that are still being loaded are discarded.
+    pub(crate) fn reset(&self) {
+        let imp = self.imp();
+
+        imp.generation.set(imp.generation.get() + 1);
+        imp.is_loading.set(false);
+
+        self.clear();
+        imp.has_newest_message.set(true);
+    }
+
+    /// Returns the id of the last message that was read before the history was created, if there
+    /// were unread messages.
+    pub(crate) fn last_read_message_id(&self) -> Option<i64> {
+        Some(self.imp().last_read_message_id.get()).filter(|id| *id != 0)
+    }
+
+    /// Returns the position of the unread divider, if it's loaded.
+    fn unread_divider_position(&self) -> Option<u32> {
+        self.imp()
+            .list
+            .borrow()
+            .iter()
+            .position(|item| matches!(item.type_(), ChatHistoryItemType::UnreadDivider))
+            .map(|position| position as u32)
+    }
+
+    /// Returns whether the newest message of the history is loaded, which means that new
+    /// messages are added as they arrive.
+    pub(crate) fn has_newest_message(&self) -> bool {
+        self.imp().has_newest_message.get()
+    }
+
+    /// Requests the messages of the history, making sure that only one request is running and
+    /// that its messages are still needed when it finishes.
+    async fn load(
+        &self,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+    ) -> Result<Vec<Message>, ChatHistoryError> {
         let imp = self.imp();
 
         if imp.is_loading.get() {
             return Err(ChatHistoryError::AlreadyLoading);
         }
 
-        let oldest_message_id = imp
+        let generation = imp.generation.get();
+
+        imp.is_loading.set(true);
+        let result = self.history(from_message_id, offset, limit).await;
+
+        if imp.generation.get() != generation {
+            return Err(ChatHistoryError::Reset);
+        }
+
+        imp.is_loading.set(false);
+
+        result.map_err(ChatHistoryError::Tdlib)
+    }
+
+    async fn history(
+        &self,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+    ) -> Result<Vec<Message>, tdlib::types::Error> {
+        let message_thread_id = self.message_thread_id();
+        if message_thread_id == 0 {
+            self.chat()
+                .get_chat_history(from_message_id, offset, limit)
+                .await
+        } else {
+            self.chat()
+                .get_message_thread_history(message_thread_id, from_message_id, offset, limit)
+                .await
+        }
+    }
+
+    fn newest_message_id(&self) -> i64 {
+        self.imp()
             .list
             .borrow()
             .iter()
-            .rev()
             .find_map(|item| item.message())
             .map(|m| m.id())
-            .unwrap_or_default();
+            .unwrap_or_default()
+    }
 
-        imp.is_loading.set(true);
+    /// Checks whether the newest message is loaded. The last message is only known for the
+    /// whole history of the chat, so the newest message of a message thread is only assumed to be
+    /// loaded once paging forward returns no more messages.
+    fn update_has_newest_message(&self) {
+        let has_newest_message = self.message_thread_id() == 0
+            && self
+                .chat()
+                .last_message()
+                .map_or(true, |message| self.newest_message_id() >= message.id());
+        self.imp().has_newest_message.set(has_newest_message);
+    }
 
-        let result = self.chat().get_chat_history(oldest_message_id, limit).await;
+    /// Inserts the unread divider above the oldest unread message, once both that message and
+    /// the last read message are loaded.
+    fn insert_unread_divider(&self) {
+        let Some(last_read_message_id) = self.last_read_message_id() else {
+            return;
+        };
+        if self.unread_divider_position().is_some() {
+            return;
+        }
 
-        imp.is_loading.set(false);
+        let position = {
+            let mut list = self.imp().list.borrow_mut();
 
-        let messages = result.map_err(ChatHistoryError::Tdlib)?;
+            let Some(position) = list.iter().position(|item| {
+                item.message()
+                    .map_or(false, |m| m.id() <= last_read_message_id)
+            }) else {
+                return;
+            };
+            // The oldest unread message must be loaded too
+            if !list.range(..position).any(|item| item.message().is_some()) {
+                return;
+            }
 
-        if messages.is_empty() {
-            return Ok(false);
-        }
+            // Put the divider below the day divider of the oldest unread message, if any
+            let position = if let Some(ChatHistoryItemType::DayDivider(_)) =
+                list.get(position - 1).map(|item| item.type_())
+            {
+                position - 1
+            } else {
+                position
+            };
 
-        self.append(messages);
-        Ok(true)
+            list.insert(position, ChatHistoryItem::for_unread_divider());
+            position as u32
+        };
+
+        self.upcast_ref::<gio::ListModel>()
+            .items_changed(position, 0, 1);
+    }
+
+    fn remove_unread_divider(&self) {
+        let Some(position) = self.unread_divider_position() else {
+            return;
+        };
+
+        self.imp().list.borrow_mut().remove(position as usize);
+        self.upcast_ref::<gio::ListModel>()
+            .items_changed(position, 1, 0);
     }
 
     fn items_changed(&self, position: u32, removed: u32, added: u32) {
@@ -146,8 +399,8 @@ impl ChatHistoryModel {
             let added = added as usize;
 
             let mut list = imp.list.borrow_mut();
-            let mut previous_timestamp = if position + 1 < list.len() {
-                list.get(position + 1)
+            let mut previous_timestamp = if position + added < list.len() {
+                list.get(position + added)
                     .and_then(|item| item.message_timestamp())
             } else {
                 None
@@ -246,6 +499,33 @@ impl ChatHistoryModel {
         self.items_changed(0, 0, 1);
     }
 
+    /// Adds messages that are newer than the loaded ones. The messages must be ordered from the
+    /// newest to the oldest.
+    fn prepend(&self, messages: Vec<Message>) {
+        let added = messages.len();
+
+        {
+            let mut list = self.imp().list.borrow_mut();
+            for message in messages.into_iter().rev() {
+                list.push_front(ChatHistoryItem::for_message(message));
+            }
+        }
+
+        self.items_changed(0, 0, added as u32);
+    }
+
+    fn clear(&self) {
+        let removed = {
+            let mut list = self.imp().list.borrow_mut();
+            let removed = list.len();
+            list.clear();
+            removed
+        };
+
+        self.upcast_ref::<gio::ListModel>()
+            .items_changed(0, removed as u32, 0);
+    }
+
     fn append(&self, messages: Vec<Message>) {
         let imp = self.imp();
         let added = messages.len();
@@ -265,6 +545,10 @@ impl ChatHistoryModel {
     fn remove(&self, message: Message) {
         let imp = self.imp();
 
+        // The unread divider is placed again afterwards, so that it doesn't get in the way of the
+        // day dividers and disappears along with the last unread message
+        self.remove_unread_divider();
+
         // Put this in a block, so that we only need to borrow the list once and the runtime
         // borrow checker does not panic in Self::items_changed when it borrows the list again.
         let index = {
@@ -290,17 +574,116 @@ impl ChatHistoryModel {
                             ordering
                         }
                     }
+                    ChatHistoryItemType::UnreadDivider => {
+                        unreachable!("The unread divider is removed before searching")
+                    }
                 })
-                .unwrap();
+                // The message may not be loaded, like after jumping to another message
+                .ok();
 
-            list.remove(index);
-            index as u32
+            index.map(|index| {
+                list.remove(index);
+                index as u32
+            })
         };
 
-        self.items_changed(index, 1, 0);
+        if let Some(index) = index {
+            self.items_changed(index, 1, 0);
+        }
+        self.insert_unread_divider();
     }
 
     pub(crate) fn chat(&self) -> Chat {
         self.imp().chat.upgrade().unwrap()
     }
+
+    pub(crate) fn message_thread_id(&self) -> i64 {
+        self.imp().message_thread_id.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::session_manager::SessionManager;
+    use crate::tdlib::ChatListItem;
+    use crate::update_log::UpdateReplay;
+    use crate::ApplicationOptions;
+    use crate::APPLICATION_OPTS;
+
+    const CHAT_ID: i64 = 100;
+
+    /// Initializes GTK and registers the resources of the installed application. Returns `false`
+    /// if the widgets can't be created in this environment.
+    fn init_gtk() -> bool {
+        if gtk::init().is_err() {
+            eprintln!("Skipping the test, as there is no display");
+            return false;
+        }
+
+        match gio::Resource::load(crate::config::RESOURCES_FILE) {
+            Ok(resource) => gio::resources_register(&resource),
+            Err(_) => {
+                eprintln!("Skipping the test, as the resources aren't installed");
+                return false;
+            }
+        }
+
+        adw::init().unwrap();
+        true
+    }
+
+    #[test]
+    fn replayed_session() {
+        if !init_gtk() {
+            return;
+        }
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/backend/tests/replay.jsonl");
+        APPLICATION_OPTS
+            .set(ApplicationOptions {
+                replay_updates: Some(path.clone()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let session_manager: SessionManager = glib::Object::new();
+        for result in UpdateReplay::open(&path).unwrap() {
+            let (update, client_id) = result.unwrap();
+            session_manager.handle_update(update, client_id);
+        }
+
+        let session = session_manager.client(1).unwrap().session;
+        assert_eq!(session.me().id(), 300);
+
+        let chat_list = session.main_chat_list();
+        let chat_ids: Vec<i64> = (0..chat_list.n_items())
+            .filter_map(|position| chat_list.item(position).and_downcast::<ChatListItem>())
+            .map(|item| item.chat().id())
+            .collect();
+        assert_eq!(chat_ids, [CHAT_ID]);
+
+        let chat = session.chat(CHAT_ID);
+        assert_eq!(chat.unread_count(), 1);
+
+        let model = ChatHistoryModel::new(&chat, 0);
+        let can_load_more = glib::MainContext::default()
+            .block_on(model.load_older_messages(10))
+            .unwrap();
+        assert!(can_load_more);
+
+        let message_ids: Vec<i64> = (0..model.n_items())
+            .filter_map(|position| model.item(position).and_downcast::<ChatHistoryItem>())
+            .filter_map(|item| item.message().map(Message::id))
+            .collect();
+        assert_eq!(message_ids, [3 << 20, 2 << 20, 1 << 20]);
+
+        // The first page already contained all the messages
+        let can_load_more = glib::MainContext::default()
+            .block_on(model.load_older_messages(10))
+            .unwrap();
+        assert!(!can_load_more);
+    }
 }
@@ -1,8 +1,10 @@
 use std::cell::RefCell;
+use std::time::Duration;
 
 use adw::prelude::BinExt;
 use adw::subclass::prelude::BinImpl;
 use gettextrs::gettext;
+use glib::clone;
 use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
@@ -14,7 +16,12 @@ use crate::session::content::ChatHistoryItemType;
 use crate::session::content::EventRow;
 use crate::session::content::MessageRow;
 use crate::strings;
+use crate::tdlib::Message;
 use crate::tdlib::SponsoredMessage;
+use crate::utils::spawn;
+
+/// The duration of the highlight animation, which must match the one in the stylesheet.
+const HIGHLIGHT_DURATION: Duration = Duration::from_millis(1500);
 
 mod imp {
     use super::*;
@@ -23,6 +30,7 @@ mod imp {
     pub(crate) struct ChatHistoryRow {
         /// An `ChatHistoryItem` or `SponsoredMessage`
         pub(super) item: RefCell<Option<glib::Object>>,
+        pub(super) highlight_source_id: RefCell<Option<glib::SourceId>>,
     }
 
     #[glib::object_subclass]
@@ -81,6 +89,25 @@ impl ChatHistoryRow {
         glib::Object::new()
     }
 
+    /// Shows the content of a pinned message in the event about pinning it once it's fetched, as
+    /// it may not be cached when the event is shown.
+    fn fetch_pinned_message(&self, item: &glib::Object, message: &Message, pinned_message_id: i64) {
+        spawn(
+            clone!(@weak self as obj, @weak item, @weak message => async move {
+                if let Err(e) = message.chat().fetch_message(pinned_message_id).await {
+                    log::warn!("Error fetching a pinned message: {:?}", e);
+                    return;
+                }
+
+                // The row may have been reused for another item in the meantime
+                if obj.item().as_ref() == Some(&item) {
+                    obj.get_or_create_event_row()
+                        .set_label(&strings::message_content(&message));
+                }
+            }),
+        );
+    }
+
     pub(crate) fn item(&self) -> Option<glib::Object> {
         self.imp().item.borrow().to_owned()
     }
@@ -90,6 +117,9 @@ impl ChatHistoryRow {
             return;
         }
 
+        // The row may be reused for another item while it's highlighted
+        self.stop_highlight();
+
         if let Some(ref item) = item {
             if let Some(item) = item.downcast_ref::<ChatHistoryItem>() {
                 match item.type_() {
@@ -97,6 +127,18 @@ impl ChatHistoryRow {
                         use tdlib::enums::MessageContent::*;
 
                         match message.content().0 {
+                            MessagePinMessage(data) => {
+                                self.get_or_create_event_row()
+                                    .set_label(&strings::message_content(message));
+
+                                if message.chat().message(data.message_id).is_none() {
+                                    self.fetch_pinned_message(
+                                        item.upcast_ref(),
+                                        message,
+                                        data.message_id,
+                                    );
+                                }
+                            }
                             MessageExpiredPhoto
                             | MessageExpiredVideo
                             | MessageCall(_)
@@ -109,7 +151,6 @@ impl ChatHistoryRow {
                             | MessageChatJoinByLink
                             | MessageChatJoinByRequest
                             | MessageChatDeleteMember(_)
-                            | MessagePinMessage(_)
                             | MessageScreenshotTaken
                             | MessageGameScore(_)
                             | MessageContactRegistered => {
@@ -132,6 +173,10 @@ impl ChatHistoryRow {
                         let child = self.get_or_create_event_row();
                         child.set_label(&date);
                     }
+                    ChatHistoryItemType::UnreadDivider => {
+                        self.get_or_create_event_row()
+                            .set_label(&gettext("Unread Messages"));
+                    }
                 }
             } else if let Some(sponsored_message) = item.downcast_ref::<SponsoredMessage>() {
                 let content = &sponsored_message.content().0;
@@ -149,6 +194,28 @@ impl ChatHistoryRow {
         self.notify("item");
     }
 
+    /// Briefly highlights the row, to draw attention to it after jumping to its message.
+    pub(crate) fn highlight(&self) {
+        self.stop_highlight();
+        self.add_css_class("highlighted");
+
+        let source_id = glib::timeout_add_local_once(
+            HIGHLIGHT_DURATION,
+            clone!(@weak self as obj => move || {
+                obj.imp().highlight_source_id.take();
+                obj.remove_css_class("highlighted");
+            }),
+        );
+        self.imp().highlight_source_id.replace(Some(source_id));
+    }
+
+    fn stop_highlight(&self) {
+        if let Some(source_id) = self.imp().highlight_source_id.take() {
+            source_id.remove();
+        }
+        self.remove_css_class("highlighted");
+    }
+
     fn update_or_create_message_row(&self, message: glib::Object) {
         match self.child().and_then(|w| w.downcast::<MessageRow>().ok()) {
             Some(child) => child.set_message(message),
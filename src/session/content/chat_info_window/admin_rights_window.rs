@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::AdwWindowImpl;
+use gettextrs::gettext;
+use glib::subclass::Signal;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums::ChatMemberStatus;
+use tdlib::types;
+use tdlib::types::ChatAdministratorRights;
+
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::tdlib::MessageSender;
+
+/// A function returning one of the fields of `ChatAdministratorRights`.
+type RightField = fn(&mut ChatAdministratorRights) -> &mut bool;
+
+/// Returns the titles and the fields of the administrator rights that make sense in the chat.
+fn right_fields(chat: &Chat) -> Vec<(String, RightField)> {
+    let (is_channel, is_forum) = match chat.type_() {
+        ChatType::Supergroup(supergroup) => (supergroup.is_channel(), supergroup.is_forum()),
+        _ => (false, false),
+    };
+
+    let mut fields: Vec<(String, RightField)> =
+        vec![(gettext("Change Chat Info"), |r| &mut r.can_change_info)];
+    if is_channel {
+        fields.push((gettext("Post Messages"), |r| &mut r.can_post_messages));
+        fields.push((gettext("Edit Messages of Others"), |r| {
+            &mut r.can_edit_messages
+        }));
+    }
+    fields.push((gettext("Delete Messages"), |r| &mut r.can_delete_messages));
+    fields.push((gettext("Add Members"), |r| &mut r.can_invite_users));
+    if !is_channel {
+        fields.push((gettext("Ban Users"), |r| &mut r.can_restrict_members));
+        fields.push((gettext("Pin Messages"), |r| &mut r.can_pin_messages));
+    }
+    if is_forum {
+        fields.push((gettext("Manage Topics"), |r| &mut r.can_manage_topics));
+    }
+    fields.push((gettext("Add New Admins"), |r| &mut r.can_promote_members));
+    fields.push((gettext("Manage Video Chats"), |r| {
+        &mut r.can_manage_video_chats
+    }));
+    if !is_channel {
+        fields.push((gettext("Remain Anonymous"), |r| &mut r.is_anonymous));
+    }
+    fields
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-chat-info-admin-rights-window.ui")]
+    pub(crate) struct AdminRightsWindow {
+        pub(super) chat: OnceCell<Chat>,
+        pub(super) member: OnceCell<MessageSender>,
+        pub(super) rights: RefCell<Option<ChatAdministratorRights>>,
+        /// The switch rows of the rights, in the same order as `right_fields()`.
+        pub(super) switch_rows: RefCell<Vec<adw::SwitchRow>>,
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        pub(super) rights_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) custom_title_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) dismiss_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AdminRightsWindow {
+        const NAME: &'static str = "ContentChatInfoAdminRightsWindow";
+        type Type = super::AdminRightsWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action_async(
+                "admin-rights-window.save",
+                None,
+                |widget, _, _| async move {
+                    widget.save().await;
+                },
+            );
+            klass.install_action_async(
+                "admin-rights-window.dismiss",
+                None,
+                |widget, _, _| async move {
+                    widget.set_status(ChatMemberStatus::Member).await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for AdminRightsWindow {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> =
+                Lazy::new(|| vec![Signal::builder("saved").build()]);
+            SIGNALS.as_ref()
+        }
+    }
+
+    impl WidgetImpl for AdminRightsWindow {}
+    impl WindowImpl for AdminRightsWindow {}
+    impl AdwWindowImpl for AdminRightsWindow {}
+}
+
+glib::wrapper! {
+    /// A window to promote a member of a chat to administrator or to change their rights.
+    pub(crate) struct AdminRightsWindow(ObjectSubclass<imp::AdminRightsWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl AdminRightsWindow {
+    /// Creates a window that changes the administrator rights of `member`, whose current status
+    /// in the chat is `status`.
+    pub(crate) fn new(
+        parent: &impl IsA<gtk::Window>,
+        chat: &Chat,
+        member: MessageSender,
+        status: &ChatMemberStatus,
+    ) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        let imp = obj.imp();
+
+        obj.set_title(Some(&crate::i18n::gettext_f(
+            "Admin Rights of {name}",
+            &[("name", &strings::message_sender(&member, false))],
+        )));
+
+        let rights = if let ChatMemberStatus::Administrator(data) = status {
+            imp.custom_title_row.set_text(&data.custom_title);
+            imp.dismiss_button.set_visible(true);
+            data.rights.clone()
+        } else {
+            // New administrators can do what most groups expect them to do
+            ChatAdministratorRights {
+                can_manage_chat: true,
+                can_change_info: true,
+                can_post_messages: true,
+                can_edit_messages: true,
+                can_delete_messages: true,
+                can_invite_users: true,
+                can_restrict_members: true,
+                can_pin_messages: true,
+                can_manage_topics: true,
+                can_manage_video_chats: true,
+                ..Default::default()
+            }
+        };
+
+        let mut switch_rows = Vec::new();
+        let mut current = rights.clone();
+        for (title, field) in right_fields(chat) {
+            let row = adw::SwitchRow::builder()
+                .title(title)
+                .active(*field(&mut current))
+                .build();
+            imp.rights_list.append(&row);
+            switch_rows.push(row);
+        }
+
+        imp.switch_rows.replace(switch_rows);
+        imp.rights.replace(Some(rights));
+        imp.chat.set(chat.clone()).unwrap();
+        imp.member.set(member).unwrap();
+
+        obj
+    }
+
+    pub(crate) fn connect_saved<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("saved", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            f(&obj);
+
+            None
+        })
+    }
+
+    async fn save(&self) {
+        let imp = self.imp();
+        let chat = imp.chat.get().unwrap();
+
+        let mut rights = imp.rights.borrow().clone().unwrap();
+        for ((_, field), row) in right_fields(chat)
+            .into_iter()
+            .zip(imp.switch_rows.borrow().iter())
+        {
+            *field(&mut rights) = row.is_active();
+        }
+        rights.can_manage_chat = true;
+
+        let status = ChatMemberStatus::Administrator(types::ChatMemberStatusAdministrator {
+            custom_title: imp.custom_title_row.text().trim().to_string(),
+            can_be_edited: true,
+            rights,
+        });
+        self.set_status(status).await;
+    }
+
+    async fn set_status(&self, status: ChatMemberStatus) {
+        let imp = self.imp();
+        let chat = imp.chat.get().unwrap();
+        let member = imp.member.get().unwrap();
+
+        match chat.set_member_status(member, status).await {
+            Ok(()) => {
+                self.emit_by_name::<()>("saved", &[]);
+                self.close();
+            }
+            Err(e) => {
+                log::warn!("Error changing the admin rights: {:?}", e);
+                imp.toast_overlay.add_toast(adw::Toast::new(&gettext(
+                    "Could not change the admin rights",
+                )));
+            }
+        }
+    }
+}
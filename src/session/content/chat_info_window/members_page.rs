@@ -0,0 +1,586 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use adw::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use glib::closure;
+use gtk::gio;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums::ChatMemberStatus;
+use tdlib::enums::SupergroupMembersFilter;
+use tdlib::enums::UserType;
+use tdlib::types;
+
+use super::admin_rights_window::AdminRightsWindow;
+use super::permissions_window::PermissionsWindow;
+use crate::components::Avatar;
+use crate::strings;
+use crate::tdlib::BoxedUserStatus;
+use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::tdlib::MessageSender;
+use crate::tdlib::User;
+use crate::utils::spawn;
+
+/// The number of members that are loaded at a time.
+const PAGE_SIZE: i32 = 50;
+
+/// The kinds of members that can be listed, in the same order as in the filter drop down.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum MembersFilter {
+    #[default]
+    Recent,
+    Administrators,
+    Bots,
+    Banned,
+}
+
+impl MembersFilter {
+    fn from_position(position: u32) -> Self {
+        match position {
+            1 => Self::Administrators,
+            2 => Self::Bots,
+            3 => Self::Banned,
+            _ => Self::Recent,
+        }
+    }
+
+    /// Returns whether a member of a basic group matches the filter. The members of supergroups
+    /// are filtered by TDLib instead.
+    fn matches(self, sender: &MessageSender, status: &ChatMemberStatus) -> bool {
+        match self {
+            Self::Recent => !matches!(status, ChatMemberStatus::Banned(_)),
+            Self::Administrators => matches!(
+                status,
+                ChatMemberStatus::Creator(_) | ChatMemberStatus::Administrator(_)
+            ),
+            Self::Bots => sender
+                .as_user()
+                .map_or(false, |user| matches!(user.type_().0, UserType::Bot(_))),
+            Self::Banned => matches!(status, ChatMemberStatus::Banned(_)),
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-chat-info-members-page.ui")]
+    pub(crate) struct MembersPage {
+        pub(super) chat: OnceCell<Chat>,
+        /// The number of members already received from TDLib, from which the next page starts.
+        pub(super) offset: Cell<i32>,
+        pub(super) can_load_more: Cell<bool>,
+        pub(super) is_loading: Cell<bool>,
+        /// Incremented whenever the list is cleared, so that stale results can be told apart.
+        pub(super) generation: Cell<u32>,
+        /// The listed members and their status, by the id of the member.
+        pub(super) members: RefCell<HashMap<i64, (MessageSender, ChatMemberStatus)>>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) filter_drop_down: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub(super) member_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) empty_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) spinner: TemplateChild<gtk::Spinner>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MembersPage {
+        const NAME: &'static str = "ContentChatInfoMembersPage";
+        type Type = super::MembersPage;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+
+            klass.install_action("members-page.promote", Some("x"), |widget, _, variant| {
+                let member_id = variant.and_then(|v| v.get()).unwrap();
+                widget.promote_member(member_id);
+            });
+            klass.install_action("members-page.restrict", Some("x"), |widget, _, variant| {
+                let member_id = variant.and_then(|v| v.get()).unwrap();
+                widget.restrict_member(member_id);
+            });
+            klass.install_action("members-page.ban", Some("x"), |widget, _, variant| {
+                let member_id = variant.and_then(|v| v.get()).unwrap();
+                widget.ban_member(member_id);
+            });
+            klass.install_action_async(
+                "members-page.unban",
+                Some("x"),
+                |widget, _, variant| async move {
+                    let member_id = variant.and_then(|v| v.get()).unwrap();
+                    widget.unban_member(member_id).await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MembersPage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Chat>("chat")
+                    .construct_only()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "chat" => self.chat.set(value.get().unwrap()).unwrap(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.can_load_more.set(true);
+
+            let obj = self.obj();
+            self.filter_drop_down
+                .connect_selected_notify(clone!(@weak obj => move |_| {
+                    obj.reload();
+                }));
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for MembersPage {
+        fn map(&self) {
+            self.parent_map();
+
+            // Only start loading once the page is shown for the first time
+            if self.offset.get() == 0 && self.member_list.first_child().is_none() {
+                self.obj().load_more();
+            }
+        }
+    }
+
+    #[gtk::template_callbacks]
+    impl MembersPage {
+        #[template_callback]
+        fn on_search_changed(&self) {
+            self.obj().reload();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A tab of the chat info window listing the members of a group. The members can be searched
+    /// and filtered, and are loaded in pages for supergroups.
+    pub(crate) struct MembersPage(ObjectSubclass<imp::MembersPage>)
+        @extends gtk::Widget;
+}
+
+impl MembersPage {
+    pub(crate) fn new(chat: &Chat) -> Self {
+        glib::Object::builder().property("chat", chat).build()
+    }
+
+    pub(crate) fn chat(&self) -> Option<&Chat> {
+        self.imp().chat.get()
+    }
+
+    /// Clears the list and loads the members matching the current query and filter again.
+    pub(crate) fn reload(&self) {
+        let imp = self.imp();
+
+        imp.generation.set(imp.generation.get().wrapping_add(1));
+        imp.offset.set(0);
+        imp.can_load_more.set(true);
+        imp.is_loading.set(false);
+        imp.members.borrow_mut().clear();
+
+        while let Some(row) = imp.member_list.first_child() {
+            imp.member_list.remove(&row);
+        }
+
+        self.load_more();
+    }
+
+    /// Loads the next page of members, if there is one.
+    pub(crate) fn load_more(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading.get() || !imp.can_load_more.get() {
+            return;
+        }
+
+        imp.is_loading.set(true);
+        self.update_visibility();
+
+        let chat = self.chat().unwrap().clone();
+        let query = imp.search_entry.text().to_string();
+        let filter = MembersFilter::from_position(imp.filter_drop_down.selected());
+        let offset = imp.offset.get();
+        let generation = imp.generation.get();
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = fetch_members(&chat, query.clone(), filter, offset).await;
+
+            // The query or the filter may have been changed in the meantime
+            let imp = obj.imp();
+            if imp.generation.get() != generation {
+                return;
+            }
+
+            imp.is_loading.set(false);
+
+            match result {
+                Ok((members, n_received, can_load_more)) => {
+                    imp.offset.set(offset + n_received);
+                    imp.can_load_more.set(can_load_more);
+
+                    let session = chat.session();
+                    let query = query.to_lowercase();
+                    for member in members {
+                        let sender = MessageSender::from_td_object(&member.member_id, &session);
+                        if matches_query(&sender, &query) {
+                            imp.member_list.append(&obj.member_row(&sender, &member.status));
+                            imp.members
+                                .borrow_mut()
+                                .insert(sender.id(), (sender, member.status));
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Error loading the chat members: {:?}", e);
+                    imp.can_load_more.set(false);
+                }
+            }
+
+            obj.update_visibility();
+        }));
+    }
+
+    fn update_visibility(&self) {
+        let imp = self.imp();
+
+        let has_members = imp.member_list.first_child().is_some();
+
+        imp.member_list.set_visible(has_members);
+        imp.spinner.set_visible(imp.is_loading.get());
+        imp.empty_label
+            .set_visible(!imp.is_loading.get() && !has_members);
+    }
+
+    fn member_row(&self, sender: &MessageSender, status: &ChatMemberStatus) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&strings::message_sender(
+                sender, true,
+            )))
+            .build();
+
+        let avatar = Avatar::new();
+        avatar.set_size(32);
+        avatar.set_item(Some(match sender {
+            MessageSender::User(user) => user.clone().upcast(),
+            MessageSender::Chat(chat) => chat.clone().upcast(),
+        }));
+        row.add_prefix(&avatar);
+
+        if let Some(role) = role_text(status) {
+            let label = gtk::Label::builder()
+                .label(role)
+                .valign(gtk::Align::Center)
+                .build();
+            label.add_css_class("dim-label");
+            label.add_css_class("caption");
+            row.add_suffix(&label);
+        }
+
+        if let MessageSender::User(user) = sender {
+            if let UserType::Bot(_) = user.type_().0 {
+                row.set_subtitle(&gettext("bot"));
+            } else {
+                User::this_expression("status")
+                    .chain_closure::<String>(closure!(
+                        |_: Option<glib::Object>, status: BoxedUserStatus| {
+                            strings::user_status(&status.0)
+                        }
+                    ))
+                    .bind(&row, "subtitle", Some(user));
+            }
+
+            let session = user.session();
+            if user.id() != session.me().id() && !matches!(user.type_().0, UserType::Deleted) {
+                let button = gtk::Button::builder()
+                    .icon_name("mail-send-symbolic")
+                    .tooltip_text(gettext("Message"))
+                    .valign(gtk::Align::Center)
+                    .action_name("chat-info-window.message-user")
+                    .action_target(&user.id().to_variant())
+                    .build();
+                button.add_css_class("flat");
+                row.add_suffix(&button);
+            }
+        }
+
+        if let Some(menu) = self.member_menu(sender, status) {
+            let button = gtk::MenuButton::builder()
+                .icon_name("view-more-symbolic")
+                .tooltip_text(gettext("More"))
+                .valign(gtk::Align::Center)
+                .menu_model(&menu)
+                .build();
+            button.add_css_class("flat");
+            row.add_suffix(&button);
+        }
+
+        row
+    }
+
+    /// Returns the menu with the actions that the user can take on a member, if there are any.
+    fn member_menu(&self, sender: &MessageSender, status: &ChatMemberStatus) -> Option<gio::Menu> {
+        let chat = self.chat().unwrap();
+
+        if sender.id() == chat.session().me().id() {
+            return None;
+        }
+
+        let is_group = match chat.type_() {
+            ChatType::Supergroup(supergroup) => !supergroup.is_channel(),
+            _ => false,
+        };
+        let can_be_edited = match status {
+            ChatMemberStatus::Creator(_) => false,
+            ChatMemberStatus::Administrator(data) => data.can_be_edited,
+            _ => true,
+        };
+        if !can_be_edited {
+            return None;
+        }
+
+        let menu = gio::Menu::new();
+        let append = |label: String, action: &str| {
+            let item = gio::MenuItem::new(Some(&label), None);
+            item.set_action_and_target_value(Some(action), Some(&sender.id().to_variant()));
+            menu.append_item(&item);
+        };
+
+        if chat.can_promote_members() && sender.as_user().is_some() {
+            match status {
+                ChatMemberStatus::Administrator(_) => {
+                    append(gettext("Edit Admin Rights"), "members-page.promote")
+                }
+                ChatMemberStatus::Member | ChatMemberStatus::Restricted(_) => {
+                    append(gettext("Promote to Admin"), "members-page.promote")
+                }
+                _ => {}
+            }
+        }
+
+        if chat.can_restrict_members() {
+            match status {
+                ChatMemberStatus::Member | ChatMemberStatus::Restricted(_) if is_group => {
+                    append(gettext("Restrict"), "members-page.restrict");
+                    append(gettext("Ban"), "members-page.ban");
+                }
+                ChatMemberStatus::Member
+                | ChatMemberStatus::Restricted(_)
+                | ChatMemberStatus::Administrator(_) => append(gettext("Ban"), "members-page.ban"),
+                ChatMemberStatus::Banned(_) => append(gettext("Unban"), "members-page.unban"),
+                _ => {}
+            }
+        }
+
+        if menu.n_items() > 0 {
+            Some(menu)
+        } else {
+            None
+        }
+    }
+
+    fn member(&self, member_id: i64) -> Option<(MessageSender, ChatMemberStatus)> {
+        self.imp().members.borrow().get(&member_id).cloned()
+    }
+
+    fn parent_window(&self) -> gtk::Window {
+        self.root().and_then(|root| root.downcast().ok()).unwrap()
+    }
+
+    fn promote_member(&self, member_id: i64) {
+        if let Some((sender, status)) = self.member(member_id) {
+            let window = AdminRightsWindow::new(
+                &self.parent_window(),
+                self.chat().unwrap(),
+                sender,
+                &status,
+            );
+            window.connect_saved(clone!(@weak self as obj => move |_| obj.reload()));
+            window.present();
+        }
+    }
+
+    fn restrict_member(&self, member_id: i64) {
+        if let Some((sender, status)) = self.member(member_id) {
+            let chat = self.chat().unwrap();
+            let permissions = match status {
+                ChatMemberStatus::Restricted(data) => data.permissions,
+                _ => chat.permissions().0,
+            };
+
+            let window =
+                PermissionsWindow::new(&self.parent_window(), chat, Some(sender), permissions);
+            window.connect_saved(clone!(@weak self as obj => move |_| obj.reload()));
+            window.present();
+        }
+    }
+
+    fn ban_member(&self, member_id: i64) {
+        let Some((sender, _)) = self.member(member_id) else {
+            return;
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Ban Member"))
+            .body(crate::i18n::gettext_f(
+                "Do you want to ban {name} from the chat?",
+                &[("name", &strings::message_sender(&sender, true))],
+            ))
+            .transient_for(&self.parent_window())
+            .build();
+
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("ban", &gettext("_Ban"))]);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_response_appearance("ban", adw::ResponseAppearance::Destructive);
+
+        dialog.choose(
+            gio::Cancellable::NONE,
+            clone!(@weak self as obj => move |response| {
+                if response == "ban" {
+                    let chat = obj.chat().unwrap().clone();
+                    spawn(clone!(@weak obj => async move {
+                        match chat.ban_member(&sender, 0, false).await {
+                            Ok(()) => obj.reload(),
+                            Err(e) => log::warn!("Error banning a chat member: {:?}", e),
+                        }
+                    }));
+                }
+            }),
+        );
+    }
+
+    async fn unban_member(&self, member_id: i64) {
+        if let Some((sender, _)) = self.member(member_id) {
+            let chat = self.chat().unwrap();
+            match chat
+                .set_member_status(&sender, ChatMemberStatus::Left)
+                .await
+            {
+                Ok(()) => self.reload(),
+                Err(e) => log::warn!("Error unbanning a chat member: {:?}", e),
+            }
+        }
+    }
+}
+
+/// Fetches a page of the members of a group. Returns the members, the number of members received
+/// from TDLib and whether there are more members to load.
+async fn fetch_members(
+    chat: &Chat,
+    query: String,
+    filter: MembersFilter,
+    offset: i32,
+) -> Result<(Vec<types::ChatMember>, i32, bool), types::Error> {
+    let session = chat.session();
+
+    match chat.type_() {
+        ChatType::BasicGroup(_) => {
+            let data = chat.members(None, 0, 0).await?;
+
+            let n_received = data.members.len() as i32;
+            let members = data
+                .members
+                .into_iter()
+                .filter(|member| {
+                    let sender = MessageSender::from_td_object(&member.member_id, &session);
+                    filter.matches(&sender, &member.status)
+                })
+                .collect();
+
+            Ok((members, n_received, false))
+        }
+        ChatType::Supergroup(_) => {
+            let td_filter = match filter {
+                MembersFilter::Recent if query.is_empty() => SupergroupMembersFilter::Recent,
+                MembersFilter::Recent => {
+                    SupergroupMembersFilter::Search(types::SupergroupMembersFilterSearch { query })
+                }
+                MembersFilter::Administrators => SupergroupMembersFilter::Administrators,
+                MembersFilter::Bots => SupergroupMembersFilter::Bots,
+                MembersFilter::Banned => {
+                    SupergroupMembersFilter::Banned(types::SupergroupMembersFilterBanned { query })
+                }
+            };
+
+            let data = chat.members(Some(td_filter), offset, PAGE_SIZE).await?;
+
+            let n_received = data.members.len() as i32;
+            let can_load_more = n_received > 0 && offset + n_received < data.total_count;
+
+            Ok((data.members, n_received, can_load_more))
+        }
+        _ => Ok((Vec::new(), 0, false)),
+    }
+}
+
+/// Returns whether the name or the username of a member contains a lowercase query.
+fn matches_query(sender: &MessageSender, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+
+    let (name, username) = match sender {
+        MessageSender::User(user) => (strings::user_display_name(user, true), user.username()),
+        MessageSender::Chat(chat) => (chat.title(), String::new()),
+    };
+
+    name.to_lowercase().contains(query) || username.to_lowercase().contains(query)
+}
+
+/// Returns the badge shown next to a member with a special role.
+fn role_text(status: &ChatMemberStatus) -> Option<String> {
+    match status {
+        ChatMemberStatus::Creator(data) if !data.custom_title.is_empty() => {
+            Some(data.custom_title.clone())
+        }
+        ChatMemberStatus::Creator(_) => Some(gettext("Owner")),
+        ChatMemberStatus::Administrator(data) if !data.custom_title.is_empty() => {
+            Some(data.custom_title.clone())
+        }
+        ChatMemberStatus::Administrator(_) => Some(gettext("Admin")),
+        ChatMemberStatus::Restricted(_) => Some(gettext("Restricted")),
+        ChatMemberStatus::Banned(_) => Some(gettext("Banned")),
+        _ => None,
+    }
+}
@@ -1,3 +1,8 @@
+mod admin_rights_window;
+mod members_page;
+mod permissions_window;
+mod shared_media_page;
+
 use adw::prelude::*;
 use adw::subclass::prelude::AdwWindowImpl;
 use gettextrs::gettext;
@@ -8,11 +13,16 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
+use tdlib::enums::ChatMemberStatus;
 use tdlib::enums::UserType;
 use tdlib::functions;
 use tdlib::types::BasicGroupFullInfo;
 use tdlib::types::SupergroupFullInfo;
 
+use self::members_page::MembersPage;
+use self::permissions_window::PermissionsWindow;
+use self::shared_media_page::SharedMediaPage;
+use self::shared_media_page::SharedMediaType;
 use crate::expressions;
 use crate::i18n::ngettext_f;
 use crate::strings;
@@ -39,6 +49,8 @@ mod imp {
         pub(super) subtitle_label: TemplateChild<gtk::Inscription>,
         #[template_child]
         pub(super) info_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) shared_media_stack: TemplateChild<adw::ViewStack>,
     }
 
     #[glib::object_subclass]
@@ -49,6 +61,24 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             klass.bind_template();
+            klass.bind_template_callbacks();
+
+            klass.install_action(
+                "chat-info-window.show-message",
+                Some("x"),
+                |widget, _, variant| {
+                    let message_id = variant.and_then(|v| v.get()).unwrap();
+                    widget.show_message(message_id);
+                },
+            );
+            klass.install_action(
+                "chat-info-window.message-user",
+                Some("x"),
+                |widget, _, variant| {
+                    let user_id = variant.and_then(|v| v.get()).unwrap();
+                    widget.message_user(user_id);
+                },
+            );
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -91,6 +121,24 @@ mod imp {
     impl WidgetImpl for ChatInfoWindow {}
     impl WindowImpl for ChatInfoWindow {}
     impl AdwWindowImpl for ChatInfoWindow {}
+
+    #[gtk::template_callbacks]
+    impl ChatInfoWindow {
+        #[template_callback]
+        fn on_edge_reached(&self, pos: gtk::PositionType) {
+            if pos == gtk::PositionType::Bottom {
+                let Some(child) = self.shared_media_stack.visible_child() else {
+                    return;
+                };
+
+                if let Some(page) = child.downcast_ref::<MembersPage>() {
+                    page.load_more();
+                } else if let Some(page) = child.downcast_ref::<SharedMediaPage>() {
+                    page.load_more();
+                }
+            }
+        }
+    }
 }
 
 glib::wrapper! {
@@ -117,7 +165,40 @@ impl ChatInfoWindow {
             Some(self),
         );
 
-        match self.chat().unwrap().type_() {
+        let chat = self.chat().unwrap();
+
+        let has_members = match chat.type_() {
+            ChatType::BasicGroup(_) => true,
+            // Only the administrators can see the subscribers of a channel
+            ChatType::Supergroup(supergroup) => {
+                !supergroup.is_channel()
+                    || matches!(
+                        supergroup.status().0,
+                        ChatMemberStatus::Creator(_) | ChatMemberStatus::Administrator(_)
+                    )
+            }
+            _ => false,
+        };
+        if has_members {
+            imp.shared_media_stack.add_titled_with_icon(
+                &MembersPage::new(chat),
+                Some("members"),
+                &gettext("Members"),
+                "system-users-symbolic",
+            );
+        }
+
+        for media_type in SharedMediaType::ALL {
+            let page = SharedMediaPage::new(chat, media_type);
+            imp.shared_media_stack.add_titled_with_icon(
+                &page,
+                Some(media_type.name()),
+                &media_type.title(),
+                media_type.icon_name(),
+            );
+        }
+
+        match chat.type_() {
             ChatType::Private(user) => {
                 self.setup_user_info(user);
             }
@@ -183,6 +264,7 @@ impl ChatInfoWindow {
             }))
             .bind(&*imp.subtitle_label, "text", Some(basic_group));
 
+        self.append_permissions_row();
         self.update_info_list_visibility();
 
         // Full info
@@ -239,6 +321,10 @@ impl ChatInfoWindow {
             imp.info_list.append(&row);
         }
 
+        if !supergroup.is_channel() {
+            self.append_permissions_row();
+        }
+
         self.update_info_list_visibility();
 
         // Full info
@@ -268,6 +354,42 @@ impl ChatInfoWindow {
         self.update_info_list_visibility();
     }
 
+    /// Adds a row to change the default permissions of the members, if the user can do it.
+    fn append_permissions_row(&self) {
+        let chat = self.chat().unwrap();
+        if !chat.can_restrict_members() {
+            return;
+        }
+
+        let row = adw::ActionRow::builder()
+            .title(gettext("Permissions"))
+            .subtitle(gettext("What members of this group can do"))
+            .activatable(true)
+            .build();
+        row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+        row.connect_activated(clone!(@weak self as obj => move |_| {
+            let chat = obj.chat().unwrap();
+            PermissionsWindow::new(&obj, chat, None, chat.permissions().0).present();
+        }));
+        self.imp().info_list.append(&row);
+    }
+
+    /// Closes the window and shows a message of the chat in the chat history.
+    fn show_message(&self, message_id: i64) {
+        let chat = self.chat().unwrap();
+        if let Some(message) = chat.message(message_id) {
+            chat.session().select_message(&message);
+        }
+
+        self.close();
+    }
+
+    /// Closes the window and opens the private chat with a user.
+    fn message_user(&self, user_id: i64) {
+        self.chat().unwrap().session().select_chat(user_id);
+        self.close();
+    }
+
     fn update_info_list_visibility(&self) {
         let info_list = &self.imp().info_list;
         info_list.set_visible(info_list.first_child().is_some());
@@ -0,0 +1,232 @@
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::AdwWindowImpl;
+use gettextrs::gettext;
+use glib::subclass::Signal;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums::ChatMemberStatus;
+use tdlib::types;
+use tdlib::types::ChatPermissions;
+
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::MessageSender;
+
+/// A function returning one of the fields of `ChatPermissions`.
+type PermissionField = fn(&mut ChatPermissions) -> &mut bool;
+
+/// Returns the titles and the fields of the permissions that can be changed.
+fn permission_fields() -> Vec<(String, PermissionField)> {
+    vec![
+        (gettext("Send Messages"), |p| &mut p.can_send_basic_messages),
+        (gettext("Send Photos"), |p| &mut p.can_send_photos),
+        (gettext("Send Videos"), |p| &mut p.can_send_videos),
+        (gettext("Send Files"), |p| &mut p.can_send_documents),
+        (gettext("Send Music"), |p| &mut p.can_send_audios),
+        (gettext("Send Voice Messages"), |p| {
+            &mut p.can_send_voice_notes
+        }),
+        (gettext("Send Video Messages"), |p| {
+            &mut p.can_send_video_notes
+        }),
+        (gettext("Send Stickers and GIFs"), |p| {
+            &mut p.can_send_other_messages
+        }),
+        (gettext("Send Polls"), |p| &mut p.can_send_polls),
+        (gettext("Embed Links"), |p| &mut p.can_add_web_page_previews),
+        (gettext("Add Members"), |p| &mut p.can_invite_users),
+        (gettext("Pin Messages"), |p| &mut p.can_pin_messages),
+        (gettext("Change Chat Info"), |p| &mut p.can_change_info),
+        (gettext("Manage Topics"), |p| &mut p.can_manage_topics),
+    ]
+}
+
+/// The durations of a restriction that can be chosen, in seconds, in the same order as in the
+/// duration combo row. A duration of 0 means forever.
+const DURATIONS: [i32; 5] = [
+    0,
+    60 * 60,
+    24 * 60 * 60,
+    7 * 24 * 60 * 60,
+    30 * 24 * 60 * 60,
+];
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-chat-info-permissions-window.ui")]
+    pub(crate) struct PermissionsWindow {
+        pub(super) chat: OnceCell<Chat>,
+        /// The member to restrict, or `None` to change the default permissions of the chat.
+        pub(super) member: OnceCell<Option<MessageSender>>,
+        pub(super) permissions: RefCell<Option<ChatPermissions>>,
+        /// The switch rows of the permissions, in the same order as `permission_fields()`.
+        pub(super) switch_rows: RefCell<Vec<adw::SwitchRow>>,
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        pub(super) permission_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) duration_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) duration_row: TemplateChild<adw::ComboRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PermissionsWindow {
+        const NAME: &'static str = "ContentChatInfoPermissionsWindow";
+        type Type = super::PermissionsWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action_async(
+                "permissions-window.save",
+                None,
+                |widget, _, _| async move {
+                    widget.save().await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PermissionsWindow {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> =
+                Lazy::new(|| vec![Signal::builder("saved").build()]);
+            SIGNALS.as_ref()
+        }
+    }
+
+    impl WidgetImpl for PermissionsWindow {}
+    impl WindowImpl for PermissionsWindow {}
+    impl AdwWindowImpl for PermissionsWindow {}
+}
+
+glib::wrapper! {
+    /// A window to change the default permissions of a chat or to restrict a single member of it.
+    pub(crate) struct PermissionsWindow(ObjectSubclass<imp::PermissionsWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl PermissionsWindow {
+    /// Creates a window that changes the permissions of a member if `member` is set, or the
+    /// default permissions of the chat otherwise. `permissions` are the current ones.
+    pub(crate) fn new(
+        parent: &impl IsA<gtk::Window>,
+        chat: &Chat,
+        member: Option<MessageSender>,
+        permissions: ChatPermissions,
+    ) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        let imp = obj.imp();
+
+        match member {
+            Some(ref member) => {
+                obj.set_title(Some(&crate::i18n::gettext_f(
+                    "Restrict {name}",
+                    &[("name", &strings::message_sender(member, false))],
+                )));
+                imp.duration_list.set_visible(true);
+            }
+            None => obj.set_title(Some(&gettext("Default Permissions"))),
+        }
+
+        let mut switch_rows = Vec::new();
+        let mut current = permissions.clone();
+        for (title, field) in permission_fields() {
+            let row = adw::SwitchRow::builder()
+                .title(title)
+                .active(*field(&mut current))
+                .build();
+            imp.permission_list.append(&row);
+            switch_rows.push(row);
+        }
+
+        imp.switch_rows.replace(switch_rows);
+        imp.permissions.replace(Some(permissions));
+        imp.chat.set(chat.clone()).unwrap();
+        imp.member.set(member).unwrap();
+
+        obj
+    }
+
+    pub(crate) fn connect_saved<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("saved", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            f(&obj);
+
+            None
+        })
+    }
+
+    /// Returns the permissions chosen with the switch rows.
+    fn chosen_permissions(&self) -> ChatPermissions {
+        let imp = self.imp();
+
+        let mut permissions = imp.permissions.borrow().clone().unwrap();
+        for ((_, field), row) in permission_fields()
+            .into_iter()
+            .zip(imp.switch_rows.borrow().iter())
+        {
+            *field(&mut permissions) = row.is_active();
+        }
+        permissions
+    }
+
+    async fn save(&self) {
+        let imp = self.imp();
+        let chat = imp.chat.get().unwrap();
+        let permissions = self.chosen_permissions();
+
+        let result = match imp.member.get().unwrap() {
+            Some(member) => {
+                let duration = DURATIONS
+                    .get(imp.duration_row.selected() as usize)
+                    .copied()
+                    .unwrap_or_default();
+                let restricted_until_date = if duration == 0 {
+                    0
+                } else {
+                    glib::DateTime::now_utc()
+                        .map(|now| now.to_unix() as i32 + duration)
+                        .unwrap_or_default()
+                };
+
+                let status = ChatMemberStatus::Restricted(types::ChatMemberStatusRestricted {
+                    is_member: true,
+                    restricted_until_date,
+                    permissions,
+                });
+                chat.set_member_status(member, status).await
+            }
+            None => chat.set_default_permissions(permissions).await,
+        };
+
+        match result {
+            Ok(()) => {
+                self.emit_by_name::<()>("saved", &[]);
+                self.close();
+            }
+            Err(e) => {
+                log::warn!("Error changing the permissions: {:?}", e);
+                imp.toast_overlay.add_toast(adw::Toast::new(&gettext(
+                    "Could not change the permissions",
+                )));
+            }
+        }
+    }
+}
@@ -0,0 +1,563 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gdk;
+use gtk::gio;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums::MessageContent;
+use tdlib::enums::SearchMessagesFilter;
+use tdlib::enums::TextEntityType;
+use tdlib::enums::ThumbnailFormat;
+use tdlib::types::FormattedText;
+use tdlib::types::Minithumbnail;
+
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::Message;
+use crate::utils::decode_image_from_path;
+use crate::utils::format_duration;
+use crate::utils::linkify;
+use crate::utils::spawn;
+
+/// The number of messages that are loaded at a time.
+const PAGE_SIZE: i32 = 50;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "ContentSharedMediaType")]
+pub(crate) enum SharedMediaType {
+    #[default]
+    PhotoAndVideo,
+    Document,
+    Url,
+    VoiceNote,
+    Audio,
+}
+
+impl SharedMediaType {
+    pub(crate) const ALL: [Self; 5] = [
+        Self::PhotoAndVideo,
+        Self::Document,
+        Self::Url,
+        Self::VoiceNote,
+        Self::Audio,
+    ];
+
+    fn filter(self) -> SearchMessagesFilter {
+        match self {
+            Self::PhotoAndVideo => SearchMessagesFilter::PhotoAndVideo,
+            Self::Document => SearchMessagesFilter::Document,
+            Self::Url => SearchMessagesFilter::Url,
+            Self::VoiceNote => SearchMessagesFilter::VoiceNote,
+            Self::Audio => SearchMessagesFilter::Audio,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::PhotoAndVideo => "media",
+            Self::Document => "files",
+            Self::Url => "links",
+            Self::VoiceNote => "voice",
+            Self::Audio => "music",
+        }
+    }
+
+    pub(crate) fn title(self) -> String {
+        match self {
+            Self::PhotoAndVideo => gettext("Media"),
+            Self::Document => gettext("Files"),
+            Self::Url => gettext("Links"),
+            Self::VoiceNote => gettext("Voice"),
+            Self::Audio => gettext("Music"),
+        }
+    }
+
+    pub(crate) fn icon_name(self) -> &'static str {
+        match self {
+            Self::PhotoAndVideo => "image-x-generic-symbolic",
+            Self::Document => "folder-documents-symbolic",
+            Self::Url => "web-browser-symbolic",
+            Self::VoiceNote => "audio-input-microphone-symbolic",
+            Self::Audio => "audio-x-generic-symbolic",
+        }
+    }
+
+    fn empty_text(self) -> String {
+        match self {
+            Self::PhotoAndVideo => gettext("No Media"),
+            Self::Document => gettext("No Files"),
+            Self::Url => gettext("No Links"),
+            Self::VoiceNote => gettext("No Voice Messages"),
+            Self::Audio => gettext("No Music"),
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-chat-info-shared-media-page.ui")]
+    pub(crate) struct SharedMediaPage {
+        pub(super) chat: OnceCell<Chat>,
+        pub(super) media_type: Cell<SharedMediaType>,
+        /// The id of the oldest loaded message, from which the next page is loaded.
+        pub(super) last_message_id: Cell<i64>,
+        pub(super) can_load_more: Cell<bool>,
+        pub(super) is_loading: Cell<bool>,
+        /// The messages shown in the media grid, in the same order as its children.
+        pub(super) media_messages: RefCell<Vec<Message>>,
+        #[template_child]
+        pub(super) media_grid: TemplateChild<gtk::FlowBox>,
+        #[template_child]
+        pub(super) item_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) empty_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) spinner: TemplateChild<gtk::Spinner>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SharedMediaPage {
+        const NAME: &'static str = "ContentChatInfoSharedMediaPage";
+        type Type = super::SharedMediaPage;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SharedMediaPage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Chat>("chat")
+                        .construct_only()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<SharedMediaType>("media-type")
+                        .construct_only()
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "chat" => self.chat.set(value.get().unwrap()).unwrap(),
+                "media-type" => self.media_type.set(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                "media-type" => obj.media_type().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.can_load_more.set(true);
+            self.empty_label
+                .set_label(&self.media_type.get().empty_text());
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for SharedMediaPage {
+        fn map(&self) {
+            self.parent_map();
+
+            // Only start loading once the page is shown for the first time
+            if self.last_message_id.get() == 0 {
+                self.obj().load_more();
+            }
+        }
+    }
+
+    #[gtk::template_callbacks]
+    impl SharedMediaPage {
+        #[template_callback]
+        fn on_media_activated(&self, child: &gtk::FlowBoxChild) {
+            let message = self
+                .media_messages
+                .borrow()
+                .get(child.index() as usize)
+                .cloned();
+
+            if let Some(message) = message {
+                self.obj().show_message(&message);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A tab of the chat info window listing the messages of a chat with a specific kind of
+    /// shared media. The messages are loaded in pages, starting from the newest ones.
+    pub(crate) struct SharedMediaPage(ObjectSubclass<imp::SharedMediaPage>)
+        @extends gtk::Widget;
+}
+
+impl SharedMediaPage {
+    pub(crate) fn new(chat: &Chat, media_type: SharedMediaType) -> Self {
+        glib::Object::builder()
+            .property("chat", chat)
+            .property("media-type", media_type)
+            .build()
+    }
+
+    pub(crate) fn chat(&self) -> Option<&Chat> {
+        self.imp().chat.get()
+    }
+
+    pub(crate) fn media_type(&self) -> SharedMediaType {
+        self.imp().media_type.get()
+    }
+
+    /// Loads the next page of messages, if there is one.
+    pub(crate) fn load_more(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading.get() || !imp.can_load_more.get() {
+            return;
+        }
+
+        imp.is_loading.set(true);
+        self.update_visibility();
+
+        let chat = self.chat().unwrap().clone();
+        let from_message_id = imp.last_message_id.get();
+        let filter = self.media_type().filter();
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = chat
+                .search_messages(
+                    String::new(),
+                    None,
+                    from_message_id,
+                    0,
+                    PAGE_SIZE,
+                    Some(filter),
+                    0,
+                )
+                .await;
+
+            let imp = obj.imp();
+            imp.is_loading.set(false);
+
+            match result {
+                Ok(messages) => {
+                    match messages.last() {
+                        Some(message) => imp.last_message_id.set(message.id()),
+                        None => imp.can_load_more.set(false),
+                    }
+
+                    for message in messages {
+                        obj.append_message(message);
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Error loading the shared media: {:?}", e);
+                    imp.can_load_more.set(false);
+                }
+            }
+
+            obj.update_visibility();
+        }));
+    }
+
+    fn update_visibility(&self) {
+        let imp = self.imp();
+
+        let has_media = imp.media_grid.first_child().is_some();
+        let has_items = imp.item_list.first_child().is_some();
+
+        imp.media_grid.set_visible(has_media);
+        imp.item_list.set_visible(has_items);
+        imp.spinner.set_visible(imp.is_loading.get());
+        imp.empty_label
+            .set_visible(!imp.is_loading.get() && !has_media && !has_items);
+    }
+
+    fn append_message(&self, message: Message) {
+        let imp = self.imp();
+
+        match self.media_type() {
+            SharedMediaType::PhotoAndVideo => {
+                imp.media_grid.append(&self.media_thumbnail(&message));
+                imp.media_messages.borrow_mut().push(message);
+            }
+            SharedMediaType::Url => {
+                for link in message_links(&message) {
+                    let row = adw::ActionRow::builder()
+                        .title(glib::markup_escape_text(&link))
+                        .subtitle(date_text(message.date()))
+                        .activatable(true)
+                        .build();
+                    row.add_prefix(&gtk::Image::from_icon_name("web-browser-symbolic"));
+                    row.add_suffix(&self.show_message_button(&message));
+                    row.connect_activated(move |_| {
+                        if let Err(e) = gio::AppInfo::launch_default_for_uri(
+                            &linkify(&link),
+                            gio::AppLaunchContext::NONE,
+                        ) {
+                            log::warn!("Error opening a link: {:?}", e);
+                        }
+                    });
+                    imp.item_list.append(&row);
+                }
+            }
+            SharedMediaType::Document => {
+                let MessageContent::MessageDocument(data) = message.content().0 else {
+                    return;
+                };
+
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&data.document.file_name))
+                    .subtitle(format!(
+                        "{} · {}",
+                        glib::format_size(data.document.document.size as u64),
+                        date_text(message.date())
+                    ))
+                    .activatable(true)
+                    .build();
+                row.add_prefix(&gtk::Image::from_icon_name("text-x-generic-symbolic"));
+                row.add_suffix(&self.show_message_button(&message));
+
+                let file_id = data.document.document.id;
+                row.connect_activated(clone!(@weak message => move |_| {
+                    let session = message.chat().session();
+                    spawn(async move {
+                        match session.download_file(file_id).await {
+                            Ok(file) => {
+                                let uri = gio::File::for_path(&file.local.path).uri();
+                                if let Err(e) = gio::AppInfo::launch_default_for_uri(
+                                    &uri,
+                                    gio::AppLaunchContext::NONE,
+                                ) {
+                                    log::warn!("Error opening a file: {:?}", e);
+                                }
+                            }
+                            Err(e) => log::warn!("Error downloading a file: {:?}", e),
+                        }
+                    });
+                }));
+                imp.item_list.append(&row);
+            }
+            SharedMediaType::VoiceNote => {
+                let MessageContent::MessageVoiceNote(data) = message.content().0 else {
+                    return;
+                };
+
+                let row = self.message_row(
+                    &strings::message_sender(message.sender(), true),
+                    &format!(
+                        "{} · {}",
+                        format_duration(data.voice_note.duration),
+                        date_text(message.date())
+                    ),
+                    "audio-input-microphone-symbolic",
+                    &message,
+                );
+                imp.item_list.append(&row);
+            }
+            SharedMediaType::Audio => {
+                let MessageContent::MessageAudio(data) = message.content().0 else {
+                    return;
+                };
+
+                let row = self.message_row(
+                    &strings::audio_title(&data.audio),
+                    &format!(
+                        "{} · {}",
+                        strings::audio_performer(&data.audio),
+                        format_duration(data.audio.duration)
+                    ),
+                    "audio-x-generic-symbolic",
+                    &message,
+                );
+                imp.item_list.append(&row);
+            }
+        }
+    }
+
+    /// Returns a row that jumps to its message when activated.
+    fn message_row(
+        &self,
+        title: &str,
+        subtitle: &str,
+        icon_name: &str,
+        message: &Message,
+    ) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(title))
+            .subtitle(glib::markup_escape_text(subtitle))
+            .activatable(true)
+            .build();
+        row.add_prefix(&gtk::Image::from_icon_name(icon_name));
+        row.connect_activated(clone!(@weak self as obj, @weak message => move |_| {
+            obj.show_message(&message);
+        }));
+        row
+    }
+
+    fn show_message_button(&self, message: &Message) -> gtk::Button {
+        let button = gtk::Button::builder()
+            .icon_name("go-jump-symbolic")
+            .tooltip_text(gettext("Show in Chat"))
+            .valign(gtk::Align::Center)
+            .build();
+        button.add_css_class("flat");
+        button.connect_clicked(clone!(@weak self as obj, @weak message => move |_| {
+            obj.show_message(&message);
+        }));
+        button
+    }
+
+    fn show_message(&self, message: &Message) {
+        self.activate_action(
+            "chat-info-window.show-message",
+            Some(&message.id().to_variant()),
+        )
+        .unwrap();
+    }
+
+    fn media_thumbnail(&self, message: &Message) -> gtk::Widget {
+        let picture = gtk::Picture::builder()
+            .content_fit(gtk::ContentFit::Cover)
+            .build();
+        let overlay = gtk::Overlay::builder()
+            .child(&picture)
+            .overflow(gtk::Overflow::Hidden)
+            .build();
+        overlay.add_css_class("shared-media-thumbnail");
+
+        let (minithumbnail, file) = match message.content().0 {
+            MessageContent::MessagePhoto(mut data) => {
+                // The "m" size is the smallest one that still looks good in the grid
+                let size = match data.photo.sizes.iter().position(|s| s.r#type == "m") {
+                    Some(pos) => Some(data.photo.sizes.swap_remove(pos)),
+                    None => data.photo.sizes.pop(),
+                };
+                (data.photo.minithumbnail, size.map(|s| s.photo))
+            }
+            MessageContent::MessageVideo(data) => {
+                let duration_label = gtk::Label::builder()
+                    .label(format_duration(data.video.duration))
+                    .halign(gtk::Align::End)
+                    .valign(gtk::Align::End)
+                    .build();
+                duration_label.add_css_class("shared-media-duration");
+                overlay.add_overlay(&duration_label);
+
+                let thumbnail = data
+                    .video
+                    .thumbnail
+                    .filter(|t| matches!(t.format, ThumbnailFormat::Jpeg));
+                (data.video.minithumbnail, thumbnail.map(|t| t.file))
+            }
+            _ => (None, None),
+        };
+
+        if let Some(texture) = minithumbnail.and_then(minithumbnail_texture) {
+            picture.set_paintable(Some(&texture));
+        }
+
+        if let Some(file) = file {
+            let session = message.chat().session();
+            spawn(clone!(@weak picture => async move {
+                let path = if file.local.is_downloading_completed {
+                    file.local.path
+                } else {
+                    match session.download_file(file.id).await {
+                        Ok(file) => file.local.path,
+                        Err(e) => {
+                            log::warn!("Failed to download a thumbnail: {e:?}");
+                            return;
+                        }
+                    }
+                };
+
+                let result = gio::spawn_blocking(move || decode_image_from_path(&path))
+                    .await
+                    .unwrap();
+                match result {
+                    Ok(texture) => picture.set_paintable(Some(&texture)),
+                    Err(e) => log::warn!("Error decoding a thumbnail: {e:?}"),
+                }
+            }));
+        }
+
+        overlay.upcast()
+    }
+}
+
+fn minithumbnail_texture(minithumbnail: Minithumbnail) -> Option<gdk::Texture> {
+    gdk::Texture::from_bytes(&glib::Bytes::from_owned(glib::base64_decode(
+        &minithumbnail.data,
+    )))
+    .ok()
+}
+
+fn date_text(date: i32) -> String {
+    glib::DateTime::from_unix_utc(date as i64)
+        .and_then(|t| t.to_local())
+        .and_then(|t| t.format("%x"))
+        .map(Into::into)
+        .unwrap_or_default()
+}
+
+/// Returns the links in the text or caption of a message.
+fn message_links(message: &Message) -> Vec<String> {
+    let formatted_text: Option<FormattedText> = match message.content().0 {
+        MessageContent::MessageText(data) => Some(data.text),
+        MessageContent::MessageAnimation(data) => Some(data.caption),
+        MessageContent::MessageAudio(data) => Some(data.caption),
+        MessageContent::MessageDocument(data) => Some(data.caption),
+        MessageContent::MessagePhoto(data) => Some(data.caption),
+        MessageContent::MessageVideo(data) => Some(data.caption),
+        MessageContent::MessageVoiceNote(data) => Some(data.caption),
+        _ => None,
+    };
+
+    let Some(formatted_text) = formatted_text else {
+        return Vec::new();
+    };
+
+    // The offsets of the entities are in UTF-16 code units
+    let text: Vec<u16> = formatted_text.text.encode_utf16().collect();
+    formatted_text
+        .entities
+        .into_iter()
+        .filter_map(|entity| match entity.r#type {
+            TextEntityType::Url => text
+                .get(entity.offset as usize..(entity.offset + entity.length) as usize)
+                .map(String::from_utf16_lossy),
+            TextEntityType::TextUrl(data) => Some(data.url),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,584 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gdk;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+
+use crate::components::Avatar;
+use crate::i18n::gettext_f;
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::Message;
+use crate::tdlib::MessageSender;
+use crate::utils::spawn;
+
+/// The number of results that are loaded at once.
+const RESULTS_PAGE_SIZE: i32 = 50;
+/// The maximum number of chat members that are suggested as senders.
+const MAX_SENDERS: i32 = 50;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-chat-search-bar.ui")]
+    pub(crate) struct ChatSearchBar {
+        pub(super) chat: RefCell<Option<Chat>>,
+        pub(super) message_thread_id: Cell<i64>,
+        pub(super) sender: RefCell<Option<MessageSender>>,
+        /// The last message sent on the day chosen by the date filter, or 0 if there's none.
+        pub(super) date_message_id: Cell<i64>,
+        /// The messages found so far, from the newest to the oldest.
+        pub(super) results: RefCell<Vec<Message>>,
+        /// The index of the result that was jumped to.
+        pub(super) index: Cell<Option<usize>>,
+        pub(super) can_load_more: Cell<bool>,
+        /// Incremented for every new search, so that the results of outdated ones are dropped.
+        pub(super) search_id: Cell<u32>,
+        /// The senders shown in the sender list box.
+        pub(super) senders: RefCell<Vec<MessageSender>>,
+        #[template_child]
+        pub(super) search_bar: TemplateChild<gtk::SearchBar>,
+        #[template_child]
+        pub(super) sender_chip: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) sender_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) date_chip: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) date_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) results_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) sender_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub(super) sender_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) sender_list_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) date_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub(super) calendar: TemplateChild<gtk::Calendar>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ChatSearchBar {
+        const NAME: &'static str = "ContentChatSearchBar";
+        type Type = super::ChatSearchBar;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+            klass.set_css_name("chatsearchbar");
+
+            klass.install_action_async(
+                "chat-search-bar.older-result",
+                None,
+                |widget, _, _| async move {
+                    widget.show_older_result().await;
+                },
+            );
+            klass.install_action("chat-search-bar.newer-result", None, |widget, _, _| {
+                widget.show_newer_result();
+            });
+            klass.install_action("chat-search-bar.clear-sender", None, |widget, _, _| {
+                widget.set_sender(None);
+            });
+            klass.install_action("chat-search-bar.clear-date", None, |widget, _, _| {
+                widget.clear_date_filter();
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ChatSearchBar {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Chat>("chat")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecInt64::builder("message-thread-id")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecBoolean::builder("search-mode-enabled")
+                        .explicit_notify()
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.set_chat(value.get().unwrap()),
+                "message-thread-id" => obj.set_message_thread_id(value.get().unwrap()),
+                "search-mode-enabled" => obj.set_search_mode_enabled(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                "message-thread-id" => obj.message_thread_id().to_value(),
+                "search-mode-enabled" => obj.search_mode_enabled().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            self.search_bar.connect_entry(&*self.search_entry);
+            self.search_bar.connect_search_mode_enabled_notify(
+                clone!(@weak obj => move |search_bar| {
+                    if search_bar.is_search_mode() {
+                        obj.imp().search_entry.grab_focus();
+                    } else {
+                        obj.reset();
+                    }
+                    obj.notify("search-mode-enabled");
+                }),
+            );
+
+            // Step through the results with the arrow keys, like in the history, where the
+            // older messages are above
+            let key_events = gtk::EventControllerKey::new();
+            key_events.connect_key_pressed(
+                clone!(@weak obj => @default-return gtk::Inhibit(false), move |_, key, _, _| {
+                    match key {
+                        gdk::Key::Up | gdk::Key::KP_Up => {
+                            obj.spawn_show_older_result();
+                            gtk::Inhibit(true)
+                        }
+                        gdk::Key::Down | gdk::Key::KP_Down => {
+                            obj.show_newer_result();
+                            gtk::Inhibit(true)
+                        }
+                        _ => gtk::Inhibit(false),
+                    }
+                }),
+            );
+            self.search_entry.add_controller(key_events);
+
+            obj.update_results();
+        }
+
+        fn dispose(&self) {
+            self.search_bar.unparent();
+        }
+    }
+
+    impl WidgetImpl for ChatSearchBar {}
+
+    #[gtk::template_callbacks]
+    impl ChatSearchBar {
+        #[template_callback]
+        fn on_search_changed(&self) {
+            self.obj().search();
+        }
+
+        #[template_callback]
+        fn on_search_activate(&self) {
+            self.obj().spawn_show_older_result();
+        }
+
+        #[template_callback]
+        fn on_next_match(&self) {
+            self.on_search_activate();
+        }
+
+        #[template_callback]
+        fn on_previous_match(&self) {
+            self.obj().show_newer_result();
+        }
+
+        #[template_callback]
+        fn on_sender_popover_show(&self) {
+            self.obj().load_senders();
+        }
+
+        #[template_callback]
+        fn on_sender_search_changed(&self) {
+            self.obj().load_senders();
+        }
+
+        #[template_callback]
+        fn on_sender_row_activated(&self, row: &gtk::ListBoxRow) {
+            let sender = self.senders.borrow().get(row.index() as usize).cloned();
+
+            if let Some(sender) = sender {
+                self.sender_popover.popdown();
+                self.obj().set_sender(Some(sender));
+            }
+        }
+
+        #[template_callback]
+        fn on_day_selected(&self) {
+            self.date_popover.popdown();
+
+            let obj = self.obj();
+            let date = self.calendar.date();
+            spawn(clone!(@weak obj => async move {
+                obj.set_date_filter(date).await;
+            }));
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A bar to search for the messages of the shown chat or message thread, which moves the
+    /// chat history to the found messages one by one.
+    pub(crate) struct ChatSearchBar(ObjectSubclass<imp::ChatSearchBar>)
+        @extends gtk::Widget;
+}
+
+impl ChatSearchBar {
+    pub(crate) fn chat(&self) -> Option<Chat> {
+        self.imp().chat.borrow().clone()
+    }
+
+    pub(crate) fn set_chat(&self, chat: Option<Chat>) {
+        if self.chat() == chat {
+            return;
+        }
+
+        // Closing the search bar also clears the search
+        self.imp().chat.replace(chat);
+        self.set_search_mode_enabled(false);
+
+        self.notify("chat");
+    }
+
+    pub(crate) fn message_thread_id(&self) -> i64 {
+        self.imp().message_thread_id.get()
+    }
+
+    pub(crate) fn set_message_thread_id(&self, message_thread_id: i64) {
+        if self.message_thread_id() == message_thread_id {
+            return;
+        }
+
+        self.imp().message_thread_id.set(message_thread_id);
+        self.set_search_mode_enabled(false);
+
+        self.notify("message-thread-id");
+    }
+
+    pub(crate) fn search_mode_enabled(&self) -> bool {
+        self.imp().search_bar.is_search_mode()
+    }
+
+    pub(crate) fn set_search_mode_enabled(&self, search_mode_enabled: bool) {
+        // The property is notified by the handler of the search bar
+        self.imp().search_bar.set_search_mode(search_mode_enabled);
+    }
+
+    /// Clears the query, the filters and the results.
+    fn reset(&self) {
+        let imp = self.imp();
+
+        imp.sender.replace(None);
+        imp.sender_chip.set_visible(false);
+        imp.date_message_id.set(0);
+        imp.date_chip.set_visible(false);
+
+        if imp.search_entry.text().is_empty() {
+            self.search();
+        } else {
+            // This starts a new search through the "search-changed" signal
+            imp.search_entry.set_text("");
+        }
+    }
+
+    fn is_searching(&self) -> bool {
+        let imp = self.imp();
+        !imp.search_entry.text().is_empty() || imp.sender.borrow().is_some()
+    }
+
+    /// Starts a new search and moves the history to the newest result.
+    fn search(&self) {
+        let imp = self.imp();
+
+        imp.search_id.set(imp.search_id.get().wrapping_add(1));
+        imp.results.borrow_mut().clear();
+        imp.index.set(None);
+        imp.can_load_more.set(self.is_searching());
+        self.update_results();
+
+        if self.is_searching() {
+            self.spawn_show_older_result();
+        }
+    }
+
+    /// Loads the next page of results. Returns `false` if the results were changed in the
+    /// meantime.
+    async fn load_more_results(&self) -> bool {
+        let imp = self.imp();
+
+        let Some(chat) = self.chat() else {
+            return false;
+        };
+
+        let search_id = imp.search_id.get();
+        let last_message_id = imp.results.borrow().last().map(Message::id);
+        let (from_message_id, offset) = match last_message_id {
+            Some(message_id) => (message_id, 0),
+            // The results of the date filter start from its message, which is also returned
+            None if imp.date_message_id.get() != 0 => (imp.date_message_id.get() + 1, 0),
+            None => (0, 0),
+        };
+
+        let result = chat
+            .search_messages(
+                imp.search_entry.text().to_string(),
+                imp.sender
+                    .borrow()
+                    .as_ref()
+                    .map(MessageSender::to_td_object),
+                from_message_id,
+                offset,
+                RESULTS_PAGE_SIZE,
+                None,
+                self.message_thread_id(),
+            )
+            .await;
+
+        // The same page may have been requested twice
+        if imp.search_id.get() != search_id
+            || imp.results.borrow().last().map(Message::id) != last_message_id
+        {
+            return false;
+        }
+
+        match result {
+            Ok(messages) => {
+                imp.can_load_more
+                    .set(messages.len() == RESULTS_PAGE_SIZE as usize);
+                imp.results.borrow_mut().extend(messages);
+            }
+            Err(e) => {
+                log::warn!("Error searching for messages: {:?}", e);
+                imp.can_load_more.set(false);
+            }
+        }
+
+        true
+    }
+
+    fn spawn_show_older_result(&self) {
+        spawn(clone!(@weak self as obj => async move {
+            obj.show_older_result().await;
+        }));
+    }
+
+    async fn show_older_result(&self) {
+        let imp = self.imp();
+
+        let index = imp.index.get().map_or(0, |index| index + 1);
+        if index >= imp.results.borrow().len() && imp.can_load_more.get() {
+            if !self.load_more_results().await {
+                return;
+            }
+        }
+
+        if index < imp.results.borrow().len() {
+            imp.index.set(Some(index));
+            self.show_result();
+        } else {
+            self.update_results();
+        }
+    }
+
+    fn show_newer_result(&self) {
+        let imp = self.imp();
+
+        if let Some(index) = imp.index.get().filter(|index| *index > 0) {
+            imp.index.set(Some(index - 1));
+            self.show_result();
+        }
+    }
+
+    fn show_result(&self) {
+        let imp = self.imp();
+
+        self.update_results();
+
+        let message = imp
+            .index
+            .get()
+            .and_then(|index| imp.results.borrow().get(index).cloned());
+        if let Some(message) = message {
+            self.activate_action(
+                "chat-history.scroll-to-message",
+                Some(&message.id().to_variant()),
+            )
+            .unwrap();
+        }
+    }
+
+    fn update_results(&self) {
+        let imp = self.imp();
+
+        let n_results = imp.results.borrow().len();
+        let index = imp.index.get();
+
+        let label = match index {
+            Some(index) => gettext_f(
+                "{current} of {total}",
+                &[
+                    ("current", &(index + 1).to_string()),
+                    ("total", &n_results.to_string()),
+                ],
+            ),
+            None if self.is_searching() && !imp.can_load_more.get() => gettext("No Results"),
+            None => String::new(),
+        };
+        imp.results_label.set_label(&label);
+
+        self.action_set_enabled(
+            "chat-search-bar.older-result",
+            index.map_or(0, |index| index + 1) < n_results || imp.can_load_more.get(),
+        );
+        self.action_set_enabled(
+            "chat-search-bar.newer-result",
+            index.map_or(false, |index| index > 0),
+        );
+    }
+
+    fn set_sender(&self, sender: Option<MessageSender>) {
+        let imp = self.imp();
+
+        if let Some(ref sender) = sender {
+            imp.sender_label
+                .set_label(&strings::message_sender(sender, true));
+        }
+        imp.sender_chip.set_visible(sender.is_some());
+        imp.sender.replace(sender);
+
+        self.search();
+    }
+
+    /// Limits the results to the messages sent until the end of the specified day. If there's no
+    /// search going on, the history is moved to that day instead.
+    async fn set_date_filter(&self, day: glib::DateTime) {
+        let imp = self.imp();
+
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        let end_of_day =
+            glib::DateTime::from_local(day.year(), day.month(), day.day_of_month(), 23, 59, 59.0)
+                .unwrap();
+
+        let message = match chat.message_by_date(end_of_day.to_unix() as i32).await {
+            Ok(message) => message,
+            Err(e) => {
+                log::warn!("Error getting the message of a date: {:?}", e);
+                return;
+            }
+        };
+
+        // Another chat may have been opened in the meantime
+        if self.chat() != Some(chat) {
+            return;
+        }
+
+        // Translators: This is the date format of the date filter of the message search
+        imp.date_label
+            .set_label(&day.format(&gettext("%B %e, %Y")).unwrap());
+        imp.date_chip.set_visible(true);
+        imp.date_message_id.set(message.id());
+
+        if self.is_searching() {
+            self.search();
+        } else {
+            self.activate_action(
+                "chat-history.scroll-to-message",
+                Some(&message.id().to_variant()),
+            )
+            .unwrap();
+        }
+    }
+
+    fn clear_date_filter(&self) {
+        let imp = self.imp();
+
+        imp.date_message_id.set(0);
+        imp.date_chip.set_visible(false);
+        self.search();
+    }
+
+    /// Shows the members of the chat that match the query of the sender entry.
+    fn load_senders(&self) {
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        let query = self.imp().sender_entry.text().to_string();
+        spawn(clone!(@weak self as obj => async move {
+            let result = chat.search_members(query.clone(), MAX_SENDERS).await;
+
+            let imp = obj.imp();
+            if obj.chat() != Some(chat) || imp.sender_entry.text().as_str() != query {
+                return;
+            }
+
+            match result {
+                Ok(senders) => obj.set_senders(senders),
+                Err(e) => log::warn!("Error searching for chat members: {:?}", e),
+            }
+        }));
+    }
+
+    fn set_senders(&self, senders: Vec<MessageSender>) {
+        let imp = self.imp();
+
+        while let Some(row) = imp.sender_list_box.first_child() {
+            imp.sender_list_box.remove(&row);
+        }
+
+        for sender in &senders {
+            let avatar = Avatar::new();
+            avatar.set_size(32);
+            avatar.set_item(Some(match sender {
+                MessageSender::User(user) => user.clone().upcast(),
+                MessageSender::Chat(chat) => chat.clone().upcast(),
+            }));
+
+            let label = gtk::Label::builder()
+                .label(strings::message_sender(sender, true))
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .xalign(0.0)
+                .build();
+
+            let row = gtk::Box::builder().spacing(12).build();
+            row.append(&avatar);
+            row.append(&label);
+
+            imp.sender_list_box.append(&row);
+        }
+
+        imp.senders.replace(senders);
+    }
+}
@@ -0,0 +1,257 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::CompositeTemplate;
+use once_cell::unsync::OnceCell;
+
+use crate::i18n::gettext_f;
+use crate::tdlib::Chat;
+
+/// The maximum number of options of a poll.
+const MAX_OPTIONS: usize = 10;
+/// The minimum number of options of a poll.
+const MIN_OPTIONS: usize = 2;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-create-poll-window.ui")]
+    pub(crate) struct CreatePollWindow {
+        pub(super) chat: OnceCell<Chat>,
+        pub(super) message_thread_id: Cell<i64>,
+        /// The rows of the options, together with their remove buttons.
+        pub(super) option_rows: RefCell<Vec<(adw::EntryRow, gtk::Button)>>,
+        #[template_child]
+        pub(super) question_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) options_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) anonymous_switch: TemplateChild<gtk::Switch>,
+        #[template_child]
+        pub(super) multiple_answers_switch: TemplateChild<gtk::Switch>,
+        #[template_child]
+        pub(super) quiz_switch: TemplateChild<gtk::Switch>,
+        #[template_child]
+        pub(super) correct_option_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub(super) correct_option_list: TemplateChild<gtk::StringList>,
+        #[template_child]
+        pub(super) explanation_row: TemplateChild<adw::EntryRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CreatePollWindow {
+        const NAME: &'static str = "ContentCreatePollWindow";
+        type Type = super::CreatePollWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action("create-poll-window.add-option", None, |widget, _, _| {
+                widget.add_option();
+            });
+            klass.install_action_async(
+                "create-poll-window.create",
+                None,
+                |widget, _, _| async move {
+                    widget.create_poll().await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for CreatePollWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            for _ in 0..MIN_OPTIONS {
+                obj.add_option();
+            }
+
+            self.question_row
+                .connect_changed(clone!(@weak obj => move |_| {
+                    obj.update_create_action();
+                }));
+            self.quiz_switch
+                .connect_active_notify(clone!(@weak obj => move |_| {
+                    obj.update_create_action();
+                }));
+        }
+    }
+
+    impl WidgetImpl for CreatePollWindow {}
+    impl WindowImpl for CreatePollWindow {}
+    impl AdwWindowImpl for CreatePollWindow {}
+}
+
+glib::wrapper! {
+    pub(crate) struct CreatePollWindow(ObjectSubclass<imp::CreatePollWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl CreatePollWindow {
+    pub(crate) fn new(parent: &gtk::Window, chat: Chat, message_thread_id: i64) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        let imp = obj.imp();
+
+        imp.chat.set(chat).unwrap();
+        imp.message_thread_id.set(message_thread_id);
+
+        obj
+    }
+
+    fn add_option(&self) {
+        let imp = self.imp();
+
+        let row = adw::EntryRow::new();
+
+        let remove_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(gettext("Remove Option"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        remove_button.connect_clicked(clone!(@weak self as obj, @weak row => move |_| {
+            obj.remove_option(&row);
+        }));
+        row.add_suffix(&remove_button);
+
+        row.connect_changed(clone!(@weak self as obj => move |_| {
+            obj.update_options();
+        }));
+
+        imp.options_group.add(&row);
+        imp.option_rows
+            .borrow_mut()
+            .push((row.clone(), remove_button));
+
+        self.update_options();
+        row.grab_focus();
+    }
+
+    fn remove_option(&self, row: &adw::EntryRow) {
+        let imp = self.imp();
+
+        imp.options_group.remove(row);
+        imp.option_rows.borrow_mut().retain(|(r, _)| r != row);
+
+        self.update_options();
+    }
+
+    /// Returns the options that were filled in.
+    fn options(&self) -> Vec<String> {
+        self.imp()
+            .option_rows
+            .borrow()
+            .iter()
+            .map(|(row, _)| row.text().trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+
+    fn update_options(&self) {
+        let imp = self.imp();
+
+        {
+            let option_rows = imp.option_rows.borrow();
+
+            // Keep at least the minimum number of options
+            let can_remove = option_rows.len() > MIN_OPTIONS;
+
+            for (i, (row, remove_button)) in option_rows.iter().enumerate() {
+                row.set_title(&gettext_f("Option {num}", &[("num", &(i + 1).to_string())]));
+                remove_button.set_sensitive(can_remove);
+            }
+
+            self.action_set_enabled(
+                "create-poll-window.add-option",
+                option_rows.len() < MAX_OPTIONS,
+            );
+        }
+
+        // Offer the options that were filled in as the correct answer of the quiz
+        let options = self.options();
+        let selected = imp.correct_option_row.selected();
+        let options_ref: Vec<&str> = options.iter().map(String::as_str).collect();
+        imp.correct_option_list
+            .splice(0, imp.correct_option_list.n_items(), &options_ref);
+        if !options.is_empty() {
+            imp.correct_option_row
+                .set_selected(selected.min(options.len() as u32 - 1));
+        }
+
+        self.update_create_action();
+    }
+
+    fn update_create_action(&self) {
+        let imp = self.imp();
+
+        let has_question = !imp.question_row.text().trim().is_empty();
+        let n_options = self.options().len();
+        let has_correct_option =
+            !imp.quiz_switch.is_active() || imp.correct_option_row.selected_item().is_some();
+
+        self.action_set_enabled(
+            "create-poll-window.create",
+            has_question && n_options >= MIN_OPTIONS && has_correct_option,
+        );
+    }
+
+    async fn create_poll(&self) {
+        use tdlib::enums::*;
+        use tdlib::types::*;
+
+        let imp = self.imp();
+        let chat = imp.chat.get().unwrap();
+
+        let r#type = if imp.quiz_switch.is_active() {
+            PollType::Quiz(PollTypeQuiz {
+                correct_option_id: imp.correct_option_row.selected() as i32,
+                explanation: FormattedText {
+                    text: imp.explanation_row.text().trim().to_string(),
+                    entities: vec![],
+                },
+            })
+        } else {
+            PollType::Regular(PollTypeRegular {
+                allow_multiple_answers: imp.multiple_answers_switch.is_active(),
+            })
+        };
+
+        let content = InputMessageContent::InputMessagePoll(InputMessagePoll {
+            question: imp.question_row.text().trim().to_string(),
+            options: self.options(),
+            is_anonymous: imp.anonymous_switch.is_active(),
+            r#type,
+            open_period: 0,
+            close_date: 0,
+            is_closed: false,
+        });
+
+        let result = chat
+            .session()
+            .backend()
+            .send_message(chat.id(), imp.message_thread_id.get(), 0, None, content)
+            .await;
+
+        match result {
+            Ok(_) => self.close(),
+            Err(e) => log::warn!("Error creating a poll: {:?}", e),
+        }
+    }
+}
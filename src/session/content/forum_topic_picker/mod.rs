@@ -0,0 +1,228 @@
+mod row;
+
+use std::cell::RefCell;
+
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use glib::subclass::Signal;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+
+use self::row::ForumTopicRow;
+use crate::expressions;
+use crate::tdlib::Chat;
+use crate::tdlib::ForumTopic;
+use crate::utils::spawn;
+
+const TOPICS_PAGE_SIZE: i32 = 30;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    using Adw 1;
+
+    template $ContentForumTopicPicker : Adw.Bin {
+        Adw.ToolbarView {
+            [top]
+            Adw.HeaderBar {
+                title-widget: Adw.WindowTitle window_title {};
+            }
+
+            content: ScrolledWindow {
+                vexpand: true;
+                hscrollbar-policy: never;
+                edge-reached => $on_edge_reached() swapped;
+
+                Adw.ClampScrollable {
+                    maximum-size: 800;
+                    tightening-threshold: 600;
+
+                    ListView list_view {
+                        single-click-activate: true;
+                        activate => $on_list_activate() swapped;
+
+                        styles [
+                            "navigation-sidebar",
+                        ]
+
+                        factory: BuilderListItemFactory {
+                            template ListItem {
+                                child: $ContentForumTopicRow {
+                                    topic: bind template.item as <$ForumTopic>;
+                                };
+                            }
+                        };
+                    }
+                }
+            };
+        }
+    }
+    "#)]
+    pub(crate) struct ForumTopicPicker {
+        pub(super) chat: RefCell<Option<Chat>>,
+        #[template_child]
+        pub(super) window_title: TemplateChild<adw::WindowTitle>,
+        #[template_child]
+        pub(super) list_view: TemplateChild<gtk::ListView>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ForumTopicPicker {
+        const NAME: &'static str = "ContentForumTopicPicker";
+        type Type = super::ForumTopicPicker;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            ForumTopicRow::static_type();
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ForumTopicPicker {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Chat>("chat")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.set_chat(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("topic-activated")
+                    .param_types([ForumTopic::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.window_title.set_subtitle(&gettext("Topics"));
+            self.obj().setup_expressions();
+        }
+    }
+
+    impl WidgetImpl for ForumTopicPicker {}
+    impl BinImpl for ForumTopicPicker {}
+
+    #[gtk::template_callbacks]
+    impl ForumTopicPicker {
+        #[template_callback]
+        fn on_list_activate(&self, pos: u32) {
+            let topic = self
+                .list_view
+                .model()
+                .and_then(|model| model.item(pos))
+                .and_downcast::<ForumTopic>()
+                .unwrap();
+
+            self.obj().emit_by_name::<()>("topic-activated", &[&topic]);
+        }
+
+        #[template_callback]
+        fn on_edge_reached(&self, pos: gtk::PositionType) {
+            if pos == gtk::PositionType::Bottom {
+                self.obj().load_more_topics();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct ForumTopicPicker(ObjectSubclass<imp::ForumTopicPicker>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ForumTopicPicker {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl ForumTopicPicker {
+    fn setup_expressions(&self) {
+        let chat_expression = Self::this_expression("chat");
+
+        // Chat title
+        expressions::chat_display_name(&chat_expression).bind(
+            &*self.imp().window_title,
+            "title",
+            Some(self),
+        );
+    }
+
+    pub(crate) fn chat(&self) -> Option<Chat> {
+        self.imp().chat.borrow().clone()
+    }
+
+    pub(crate) fn set_chat(&self, chat: Option<Chat>) {
+        if self.chat() == chat {
+            return;
+        }
+
+        let imp = self.imp();
+
+        let model = chat
+            .as_ref()
+            .map(|chat| gtk::NoSelection::new(Some(chat.forum_topics().clone())));
+        imp.list_view.set_model(model.as_ref());
+
+        imp.chat.replace(chat);
+        self.notify("chat");
+
+        self.reload_topics();
+    }
+
+    /// Loads the first page of topics of the chat again.
+    pub(crate) fn reload_topics(&self) {
+        if let Some(chat) = self.chat() {
+            let topics = chat.forum_topics().clone();
+            spawn(async move {
+                if let Err(e) = topics.reload(TOPICS_PAGE_SIZE).await {
+                    log::warn!("Couldn't load forum topics: {:?}", e);
+                }
+            });
+        }
+    }
+
+    fn load_more_topics(&self) {
+        if let Some(chat) = self.chat() {
+            let topics = chat.forum_topics().clone();
+            spawn(async move {
+                if let Err(e) = topics.load_more(TOPICS_PAGE_SIZE).await {
+                    log::warn!("Couldn't load more forum topics: {:?}", e);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::cell::RefCell;
+
+use glib::Properties;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+
+use crate::tdlib::ForumTopic;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, Properties, CompositeTemplate)]
+    #[properties(wrapper_type = super::ForumTopicRow)]
+    #[template(string = r#"
+    template $ContentForumTopicRow {
+        layout-manager: BoxLayout {
+            spacing: 6;
+        };
+
+        Inscription {
+            hexpand: true;
+            text-overflow: ellipsize_end;
+            text: bind template.topic as <$ForumTopic>.name;
+        }
+
+        Image {
+            icon-name: "view-pin-symbolic";
+            visible: bind template.topic as <$ForumTopic>.is-pinned;
+
+            styles [
+                "dim-label",
+            ]
+        }
+
+        Image {
+            icon-name: "changes-prevent-symbolic";
+            visible: bind template.topic as <$ForumTopic>.is-closed;
+
+            styles [
+                "dim-label",
+            ]
+        }
+
+        Label {
+            valign: center;
+            label: bind template.topic as <$ForumTopic>.unread-count;
+            visible: bind template.topic as <$ForumTopic>.unread-count;
+
+            styles [
+                "unread-count",
+            ]
+        }
+    }
+    "#)]
+    pub(crate) struct ForumTopicRow {
+        #[property(get, set)]
+        pub(super) topic: RefCell<Option<ForumTopic>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ForumTopicRow {
+        const NAME: &'static str = "ContentForumTopicRow";
+        type Type = super::ForumTopicRow;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            ForumTopic::static_type();
+            klass.bind_template();
+            klass.set_css_name("forumtopicrow");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ForumTopicRow {
+        fn properties() -> &'static [glib::ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            self.derived_set_property(id, value, pspec)
+        }
+
+        fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            self.derived_property(id, pspec)
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for ForumTopicRow {}
+}
+
+glib::wrapper! {
+    pub(crate) struct ForumTopicRow(ObjectSubclass<imp::ForumTopicRow>)
+        @extends gtk::Widget;
+}
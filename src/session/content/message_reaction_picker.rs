@@ -0,0 +1,131 @@
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use tdlib::enums::ReactionType;
+
+use crate::tdlib::Message;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessageReactionPicker {
+        layout-manager: BinLayout {};
+
+        Box box_ {
+            spacing: 3;
+            halign: center;
+        }
+    }
+    "#)]
+    pub(crate) struct MessageReactionPicker {
+        pub(super) message: glib::WeakRef<Message>,
+        #[template_child]
+        pub(super) box_: TemplateChild<gtk::Box>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageReactionPicker {
+        const NAME: &'static str = "MessageReactionPicker";
+        type Type = super::MessageReactionPicker;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_css_name("messagereactionpicker");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageReactionPicker {
+        fn dispose(&self) {
+            self.box_.unparent();
+        }
+    }
+
+    impl WidgetImpl for MessageReactionPicker {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageReactionPicker(ObjectSubclass<imp::MessageReactionPicker>)
+        @extends gtk::Widget;
+}
+
+impl Default for MessageReactionPicker {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl MessageReactionPicker {
+    /// Shows the reactions that can be added to the specified message. The picker is hidden
+    /// until they're known or if there's none.
+    pub(crate) fn set_message(&self, message: Option<&Message>) {
+        let imp = self.imp();
+
+        imp.message.set(message);
+
+        while let Some(child) = imp.box_.first_child() {
+            imp.box_.remove(&child);
+        }
+        self.set_visible(false);
+
+        if let Some(message) = message {
+            spawn(clone!(@weak self as obj, @weak message => async move {
+                match message.available_reactions().await {
+                    // Ignore the result if the picker was reused for another message meanwhile
+                    Ok(reactions) if obj.imp().message.upgrade().as_ref() == Some(&message) => {
+                        obj.populate(&message, reactions);
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Error getting the available reactions: {:?}", e),
+                }
+            }));
+        }
+    }
+
+    fn populate(&self, message: &Message, reactions: Vec<ReactionType>) {
+        let imp = self.imp();
+        let chosen_reactions = message.interaction_info().reactions();
+
+        // Custom emojis can't be rendered yet, so only the standard ones are offered
+        for reaction in reactions {
+            let ReactionType::Emoji(ref data) = reaction else {
+                continue;
+            };
+
+            let button = gtk::Button::builder()
+                .label(data.emoji.as_str())
+                .css_classes(["flat", "reaction"])
+                .build();
+
+            if chosen_reactions.is_chosen(&reaction) {
+                button.add_css_class("chosen");
+            }
+
+            button.connect_clicked(clone!(@weak message => move |button| {
+                if let Some(popover) = button.ancestor(gtk::Popover::static_type()) {
+                    popover.downcast::<gtk::Popover>().unwrap().popdown();
+                }
+
+                let reaction = reaction.clone();
+                spawn(async move {
+                    if let Err(e) = message.toggle_reaction(reaction).await {
+                        log::warn!("Error toggling a reaction: {:?}", e);
+                    }
+                });
+            }));
+
+            imp.box_.append(&button);
+        }
+
+        self.set_visible(imp.box_.first_child().is_some());
+    }
+}
@@ -0,0 +1,311 @@
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums::MessageContent;
+use tdlib::types::Audio;
+
+use super::base::MessageBaseExt;
+use crate::audio_player::AudioPlayer;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageBubble;
+use crate::session::AudioPlaylist;
+use crate::strings;
+use crate::tdlib::Message;
+use crate::utils::format_duration;
+use crate::utils::parse_formatted_text;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-message-audio.ui")]
+    pub(crate) struct MessageAudio {
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message: RefCell<Option<Message>>,
+        pub(super) playlist_signal_group: OnceCell<glib::SignalGroup>,
+        pub(super) player_signal_group: OnceCell<glib::SignalGroup>,
+        #[template_child]
+        pub(super) message_bubble: TemplateChild<MessageBubble>,
+        #[template_child]
+        pub(super) cover_picture: TemplateChild<gtk::Picture>,
+        #[template_child]
+        pub(super) play_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) performer_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) progress_scale: TemplateChild<gtk::Scale>,
+        #[template_child]
+        pub(super) time_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageAudio {
+        const NAME: &'static str = "MessageAudio";
+        type Type = super::MessageAudio;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action("message-audio.play-pause", None, |widget, _, _| {
+                widget.play_pause();
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageAudio {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            obj.create_signal_groups();
+
+            self.progress_scale.connect_change_value(
+                clone!(@weak obj => @default-return gtk::Inhibit(false), move |_, _, value| {
+                    obj.seek(value);
+                    gtk::Inhibit(false)
+                }),
+            );
+        }
+    }
+
+    impl WidgetImpl for MessageAudio {}
+    impl MessageBaseImpl for MessageAudio {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageAudio(ObjectSubclass<imp::MessageAudio>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessageAudio {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        if let Some(old_message) = imp.message.take() {
+            let handler_id = imp.handler_id.take().unwrap();
+            old_message.disconnect(handler_id);
+        }
+
+        imp.message_bubble.update_from_message(&message, false);
+
+        let handler_id =
+            message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                obj.update_audio(message);
+            }));
+        imp.handler_id.replace(Some(handler_id));
+        self.update_audio(&message);
+
+        let playlist = message.chat().session().audio_playlist().clone();
+        imp.playlist_signal_group
+            .get()
+            .unwrap()
+            .set_target(Some(&playlist));
+        imp.player_signal_group
+            .get()
+            .unwrap()
+            .set_target(Some(playlist.player()));
+
+        imp.message.replace(Some(message));
+        self.update_state();
+        self.notify("message");
+    }
+}
+
+impl MessageAudio {
+    fn create_signal_groups(&self) {
+        let imp = self.imp();
+
+        let playlist_signal_group = glib::SignalGroup::new(AudioPlaylist::static_type());
+        playlist_signal_group.connect_notify_local(
+            Some("message"),
+            clone!(@weak self as obj => move |_, _| {
+                obj.update_state();
+            }),
+        );
+        imp.playlist_signal_group
+            .set(playlist_signal_group)
+            .unwrap();
+
+        let player_signal_group = glib::SignalGroup::new(AudioPlayer::static_type());
+        for property in ["is-playing", "position", "duration"] {
+            player_signal_group.connect_notify_local(
+                Some(property),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_state();
+                }),
+            );
+        }
+        imp.player_signal_group.set(player_signal_group).unwrap();
+    }
+
+    fn update_audio(&self, message: &Message) {
+        let MessageContent::MessageAudio(data) = message.content().0 else {
+            return;
+        };
+
+        let imp = self.imp();
+
+        imp.message_bubble
+            .set_label(parse_formatted_text(data.caption));
+        imp.title_label
+            .set_label(&strings::audio_title(&data.audio));
+        imp.performer_label
+            .set_label(&strings::audio_performer(&data.audio));
+
+        self.load_cover(message, data.audio);
+    }
+
+    fn load_cover(&self, message: &Message, audio: Audio) {
+        let imp = self.imp();
+
+        let Some(thumbnail) = audio.album_cover_thumbnail else {
+            imp.cover_picture.set_visible(false);
+            imp.cover_picture.set_paintable(gdk::Paintable::NONE);
+            return;
+        };
+
+        imp.cover_picture.set_visible(true);
+        if thumbnail.file.local.is_downloading_completed {
+            imp.cover_picture
+                .set_filename(Some(&thumbnail.file.local.path));
+            return;
+        }
+
+        if let Some(minithumbnail) = audio.album_cover_minithumbnail {
+            let minithumbnail = gdk::Texture::from_bytes(&glib::Bytes::from_owned(
+                glib::base64_decode(&minithumbnail.data),
+            ))
+            .unwrap();
+            imp.cover_picture.set_paintable(Some(&minithumbnail));
+        }
+
+        let session = message.chat().session();
+        spawn(clone!(@weak self as obj, @weak message => async move {
+            if let Ok(file) = session.download_file(thumbnail.file.id).await {
+                // The row may have been recycled for another message in the meantime
+                let imp = obj.imp();
+                if imp.message.borrow().as_ref() == Some(&message) {
+                    imp.cover_picture.set_filename(Some(&file.local.path));
+                }
+            }
+        }));
+    }
+
+    fn play_pause(&self) {
+        let Some(message) = self.imp().message.borrow().clone() else {
+            return;
+        };
+        message
+            .chat()
+            .session()
+            .audio_playlist()
+            .play_message(&message);
+    }
+
+    fn seek(&self, fraction: f64) {
+        let Some(message) = self.imp().message.borrow().clone() else {
+            return;
+        };
+
+        // Only the message that is being played can be seeked
+        let playlist = message.chat().session().audio_playlist().clone();
+        if playlist.message().as_ref() != Some(&message) {
+            return;
+        }
+
+        let player = playlist.player();
+        let duration = player.duration();
+        if duration > 0 {
+            player.seek((duration as f64 * fraction.clamp(0.0, 1.0)) as i64);
+        }
+    }
+
+    fn update_state(&self) {
+        let imp = self.imp();
+
+        let Some(message) = imp.message.borrow().clone() else {
+            return;
+        };
+        let MessageContent::MessageAudio(data) = message.content().0 else {
+            return;
+        };
+
+        let playlist = message.chat().session().audio_playlist().clone();
+        let player = playlist.player();
+        let is_current = playlist.message().as_ref() == Some(&message);
+
+        imp.play_image
+            .set_icon_name(Some(if is_current && player.is_playing() {
+                "media-playback-pause-symbolic"
+            } else {
+                "media-playback-start-symbolic"
+            }));
+
+        // Show the progress only for the message that is being played
+        if is_current && player.duration() > 0 {
+            let position = (player.position() / 1_000_000) as i32;
+            let duration = (player.duration() / 1_000_000) as i32;
+
+            imp.progress_scale.set_visible(true);
+            imp.progress_scale
+                .set_value(player.position() as f64 / player.duration() as f64);
+            imp.time_label.set_label(&format!(
+                "{} / {}",
+                format_duration(position),
+                format_duration(duration)
+            ));
+        } else {
+            imp.progress_scale.set_visible(false);
+            imp.time_label
+                .set_label(&format_duration(data.audio.duration));
+        }
+    }
+}
@@ -60,7 +60,7 @@ mod imp {
             let menu = chat_history
                 .downcast_ref::<ChatHistory>()
                 .unwrap()
-                .message_menu();
+                .message_menu(&obj.property("message"));
 
             menu.set_pointing_to(Some(&gdk::Rectangle::new(x, y, 0, 0)));
             menu.unparent();
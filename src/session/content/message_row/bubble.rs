@@ -11,7 +11,9 @@ use once_cell::sync::Lazy;
 
 use crate::session::content::message_row::MessageIndicators;
 use crate::session::content::message_row::MessageLabel;
+use crate::session::content::message_row::MessageReactions;
 use crate::session::content::message_row::MessageReply;
+use crate::session::content::message_row::MessageThreadButton;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatType;
 use crate::tdlib::Message;
@@ -56,6 +58,14 @@ mod imp {
                 $MessageLabel message_label {
                     visible: false;
                 }
+
+                $MessageReactions reactions {
+                    visible: false;
+                }
+
+                $MessageThreadButton thread_button {
+                    visible: false;
+                }
             }
 
             [overlay]
@@ -80,6 +90,10 @@ mod imp {
         #[template_child]
         pub(super) message_label: TemplateChild<MessageLabel>,
         #[template_child]
+        pub(super) reactions: TemplateChild<MessageReactions>,
+        #[template_child]
+        pub(super) thread_button: TemplateChild<MessageThreadButton>,
+        #[template_child]
         pub(super) indicators: TemplateChild<MessageIndicators>,
     }
 
@@ -166,6 +180,8 @@ impl MessageBubble {
         let imp = self.imp();
 
         imp.indicators.set_message(message.clone().upcast());
+        imp.reactions.set_message(Some(message));
+        imp.thread_button.set_message(Some(message));
 
         let is_channel = if let ChatType::Supergroup(data) = message.chat().type_() {
             data.is_channel()
@@ -245,6 +261,8 @@ impl MessageBubble {
 
         imp.indicators
             .set_message(sponsored_message.clone().upcast());
+        imp.reactions.set_message(None);
+        imp.thread_button.set_message(None);
 
         self.remove_css_class("outgoing");
 
@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+use tdlib::functions;
+use tdlib::types::Contact;
+
+use super::base::MessageBaseExt;
+use crate::components::Avatar;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageBubble;
+use crate::strings;
+use crate::tdlib::Message;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-message-contact.ui")]
+    pub(crate) struct MessageContact {
+        pub(super) message: RefCell<Option<Message>>,
+        #[template_child]
+        pub(super) message_bubble: TemplateChild<MessageBubble>,
+        #[template_child]
+        pub(super) avatar: TemplateChild<Avatar>,
+        #[template_child]
+        pub(super) name_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) phone_number_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageContact {
+        const NAME: &'static str = "MessageContact";
+        type Type = super::MessageContact;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            Avatar::static_type();
+            klass.bind_template();
+
+            klass.install_action("message-contact.send-message", None, |widget, _, _| {
+                widget.send_message();
+            });
+            klass.install_action_async(
+                "message-contact.add-to-contacts",
+                None,
+                |widget, _, _| async move {
+                    widget.add_to_contacts().await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageContact {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for MessageContact {}
+    impl MessageBaseImpl for MessageContact {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageContact(ObjectSubclass<imp::MessageContact>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessageContact {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        imp.message_bubble.update_from_message(&message, false);
+
+        // The content of contact messages can't be edited
+        if let Some(contact) = contact(&message) {
+            let name = strings::contact_name(&contact);
+
+            if contact.user_id != 0 {
+                let user = message.chat().session().user(contact.user_id);
+                imp.avatar.set_item(Some(user.upcast()));
+            } else {
+                imp.avatar.set_item(None);
+            }
+            imp.avatar.set_custom_text(Some(&name));
+
+            imp.name_label.set_label(&name);
+            imp.phone_number_label.set_label(&contact.phone_number);
+            imp.phone_number_label
+                .set_visible(!contact.phone_number.is_empty());
+
+            // Both actions need to know the Telegram account of the contact
+            let is_user = contact.user_id != 0;
+            self.action_set_enabled("message-contact.send-message", is_user);
+            self.action_set_enabled("message-contact.add-to-contacts", is_user);
+        }
+
+        imp.message.replace(Some(message));
+        self.notify("message");
+    }
+}
+
+impl MessageContact {
+    fn send_message(&self) {
+        let Some(message) = self.imp().message.borrow().clone() else {
+            return;
+        };
+        if let Some(contact) = contact(&message) {
+            message.chat().session().select_chat(contact.user_id);
+        }
+    }
+
+    async fn add_to_contacts(&self) {
+        let Some(message) = self.imp().message.borrow().clone() else {
+            return;
+        };
+        let Some(contact) = contact(&message) else {
+            return;
+        };
+
+        let client_id = message.chat().session().client_id();
+        match functions::add_contact(contact, false, client_id).await {
+            Ok(_) => self.action_set_enabled("message-contact.add-to-contacts", false),
+            Err(e) => log::warn!("Error adding a contact: {:?}", e),
+        }
+    }
+}
+
+fn contact(message: &Message) -> Option<Contact> {
+    match message.content().0 {
+        MessageContent::MessageContact(data) => Some(data.contact),
+        _ => None,
+    }
+}
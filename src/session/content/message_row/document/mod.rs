@@ -2,7 +2,7 @@ use std::cell::RefCell;
 mod file_status;
 mod status_indicator;
 
-use file_status::FileStatus;
+pub(crate) use file_status::FileStatus;
 use file_status::FileStatus::*;
 use glib::clone;
 use gtk::gdk;
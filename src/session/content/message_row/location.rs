@@ -0,0 +1,251 @@
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+use tdlib::types::Location;
+
+use super::base::MessageBaseExt;
+use crate::i18n::gettext_f;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageBubble;
+use crate::tdlib::Message;
+use crate::utils::format_duration;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-message-location.ui")]
+    pub(crate) struct MessageLocation {
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message: RefCell<Option<Message>>,
+        /// The location that is opened by the "open in maps" action.
+        pub(super) location: RefCell<Option<Location>>,
+        pub(super) countdown_source_id: RefCell<Option<glib::SourceId>>,
+        #[template_child]
+        pub(super) message_bubble: TemplateChild<MessageBubble>,
+        #[template_child]
+        pub(super) icon_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) address_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) coordinates_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) live_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageLocation {
+        const NAME: &'static str = "MessageLocation";
+        type Type = super::MessageLocation;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action("message-location.open-in-maps", None, |widget, _, _| {
+                widget.open_in_maps();
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageLocation {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.obj().stop_countdown();
+        }
+    }
+
+    impl WidgetImpl for MessageLocation {}
+    impl MessageBaseImpl for MessageLocation {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageLocation(ObjectSubclass<imp::MessageLocation>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessageLocation {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        if let Some(old_message) = imp.message.take() {
+            let handler_id = imp.handler_id.take().unwrap();
+            old_message.disconnect(handler_id);
+        }
+
+        imp.message_bubble.update_from_message(&message, false);
+
+        // Live locations are updated by editing the message
+        let handler_id =
+            message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                obj.update_content(message);
+            }));
+        imp.handler_id.replace(Some(handler_id));
+
+        imp.message.replace(Some(message.clone()));
+        self.update_content(&message);
+
+        self.notify("message");
+    }
+}
+
+impl MessageLocation {
+    fn update_content(&self, message: &Message) {
+        let imp = self.imp();
+
+        self.stop_countdown();
+
+        let location = match message.content().0 {
+            MessageContent::MessageLocation(data) => {
+                let is_live = data.live_period > 0;
+
+                imp.title_label.set_label(&if is_live {
+                    gettext("Live Location")
+                } else {
+                    gettext("Location")
+                });
+                imp.address_label.set_visible(false);
+                imp.icon_image.set_icon_name(Some(if is_live {
+                    "find-location-symbolic"
+                } else {
+                    "mark-location-symbolic"
+                }));
+
+                imp.live_label.set_visible(is_live);
+                if is_live {
+                    self.start_countdown(message.date() + data.live_period);
+                }
+
+                data.location
+            }
+            MessageContent::MessageVenue(data) => {
+                imp.title_label.set_label(&data.venue.title);
+                imp.address_label.set_label(&data.venue.address);
+                imp.address_label
+                    .set_visible(!data.venue.address.is_empty());
+                imp.icon_image.set_icon_name(Some("mark-location-symbolic"));
+                imp.live_label.set_visible(false);
+
+                data.venue.location
+            }
+            _ => unreachable!(),
+        };
+
+        imp.coordinates_label
+            .set_label(&format_coordinates(&location));
+        imp.location.replace(Some(location));
+    }
+
+    /// Updates the remaining time of a live location every second, until it expires at the
+    /// specified unix time.
+    fn start_countdown(&self, expiration_date: i32) {
+        if self.update_live_label(expiration_date) {
+            let source_id = glib::timeout_add_seconds_local(
+                1,
+                clone!(@weak self as obj => @default-return glib::Continue(false), move || {
+                    let is_live = obj.update_live_label(expiration_date);
+                    if !is_live {
+                        obj.imp().countdown_source_id.take();
+                    }
+                    glib::Continue(is_live)
+                }),
+            );
+            self.imp().countdown_source_id.replace(Some(source_id));
+        }
+    }
+
+    fn stop_countdown(&self) {
+        if let Some(source_id) = self.imp().countdown_source_id.take() {
+            source_id.remove();
+        }
+    }
+
+    /// Returns whether the live location is still being updated.
+    fn update_live_label(&self, expiration_date: i32) -> bool {
+        let now = glib::DateTime::now_utc().unwrap().to_unix() as i32;
+        let remaining = expiration_date - now;
+
+        let label = if remaining > 0 {
+            gettext_f(
+                "Updated live, ends in {time}",
+                &[("time", &format_duration(remaining))],
+            )
+        } else {
+            gettext("Live location ended")
+        };
+        self.imp().live_label.set_label(&label);
+
+        remaining > 0
+    }
+
+    fn open_in_maps(&self) {
+        let Some(location) = self.imp().location.borrow().clone() else {
+            return;
+        };
+
+        let uri = format!("geo:{},{}", location.latitude, location.longitude);
+        if let Err(e) = gio::AppInfo::launch_default_for_uri(&uri, gio::AppLaunchContext::NONE) {
+            log::warn!("Error opening a location: {}", e);
+        }
+    }
+}
+
+fn format_coordinates(location: &Location) -> String {
+    let latitude = if location.latitude >= 0.0 {
+        format!("{:.5}° N", location.latitude)
+    } else {
+        format!("{:.5}° S", -location.latitude)
+    };
+    let longitude = if location.longitude >= 0.0 {
+        format!("{:.5}° E", location.longitude)
+    } else {
+        format!("{:.5}° W", -location.longitude)
+    };
+
+    format!("{latitude}, {longitude}")
+}
@@ -1,14 +1,22 @@
+mod audio;
 mod base;
 mod bubble;
+mod contact;
 mod document;
 mod indicators;
 mod label;
+mod location;
 mod media_picture;
 mod photo;
+mod poll;
+mod reactions;
 mod reply;
 mod sticker;
 mod text;
+mod thread_button;
 mod video;
+mod video_note;
+mod voice_note;
 
 use std::cell::RefCell;
 
@@ -23,19 +31,28 @@ use once_cell::sync::Lazy;
 use tdlib::enums::MessageContent;
 use tdlib::enums::StickerFormat;
 
+use self::audio::MessageAudio;
 use self::base::MessageBase;
 use self::base::MessageBaseExt;
 use self::base::MessageBaseImpl;
 use self::bubble::MessageBubble;
+use self::contact::MessageContact;
+use self::document::FileStatus;
 use self::document::MessageDocument;
 use self::indicators::MessageIndicators;
 use self::label::MessageLabel;
+use self::location::MessageLocation;
 use self::media_picture::MediaPicture;
 use self::photo::MessagePhoto;
+use self::poll::MessagePoll;
+use self::reactions::MessageReactions;
 use self::reply::MessageReply;
 use self::sticker::MessageSticker;
 use self::text::MessageText;
+use self::thread_button::MessageThreadButton;
 use self::video::MessageVideo;
+use self::video_note::MessageVideoNote;
+use self::voice_note::MessageVoiceNote;
 use crate::components::Avatar;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatType;
@@ -62,6 +79,7 @@ mod imp {
     pub(crate) struct MessageRow {
         /// A `Message` or `SponsoredMessage`
         pub(super) message: RefCell<Option<glib::Object>>,
+        pub(super) is_pinned_handler_id: RefCell<Option<glib::SignalHandlerId>>,
         pub(super) content: RefCell<Option<gtk::Widget>>,
         pub(super) avatar: RefCell<Option<Avatar>>,
     }
@@ -80,6 +98,15 @@ mod imp {
                 widget.reply()
             });
             klass.install_action("message-row.edit", None, move |widget, _, _| widget.edit());
+            klass.install_action("message-row.view-thread", None, move |widget, _, _| {
+                widget.view_thread()
+            });
+            klass.install_action_async("message-row.pin", None, |widget, _, _| async move {
+                widget.toggle_is_pinned().await;
+            });
+            klass.install_action_async("message-row.unpin", None, |widget, _, _| async move {
+                widget.toggle_is_pinned().await;
+            });
             klass.install_action("message-row.revoke-delete", None, move |widget, _, _| {
                 widget.show_delete_dialog(true)
             });
@@ -122,6 +149,14 @@ mod imp {
         }
 
         fn dispose(&self) {
+            if let Some(handler_id) = self.is_pinned_handler_id.take() {
+                self.message
+                    .borrow()
+                    .as_ref()
+                    .unwrap()
+                    .disconnect(handler_id);
+            }
+
             if let Some(avatar) = self.avatar.borrow().as_ref() {
                 avatar.unparent();
             }
@@ -171,6 +206,24 @@ impl MessageRow {
         }
     }
 
+    fn view_thread(&self) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            self.activate_action(
+                "content.open-message-thread",
+                Some(&(message.chat().id(), message.id()).to_variant()),
+            )
+            .unwrap();
+        }
+    }
+
+    async fn toggle_is_pinned(&self) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            if let Err(e) = message.toggle_is_pinned().await {
+                log::warn!("Error toggling the pinned state of a message: {:?}", e);
+            }
+        }
+    }
+
     fn show_delete_dialog(&self, revoke: bool) {
         let window: gtk::Window = self.root().and_then(|root| root.downcast().ok()).unwrap();
 
@@ -217,7 +270,23 @@ impl MessageRow {
             return;
         }
 
+        if let Some(handler_id) = imp.is_pinned_handler_id.take() {
+            imp.message
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .disconnect(handler_id);
+        }
+
         if let Some(message) = message.downcast_ref::<Message>() {
+            let handler_id = message.connect_notify_local(
+                Some("is-pinned"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_actions();
+                }),
+            );
+            imp.is_pinned_handler_id.replace(Some(handler_id));
+
             let show_avatar = if message.is_outgoing() {
                 false
             } else if message.chat().is_own_chat() {
@@ -315,12 +384,20 @@ impl MessageRow {
         self.action_set_enabled("message-row.edit", self.can_edit_message());
 
         if let Some(message) = self.message().downcast_ref::<Message>() {
+            self.action_set_enabled("message-row.view-thread", message.can_get_message_thread());
             self.action_set_enabled("message-row.delete", message.can_be_deleted_only_for_self());
             self.action_set_enabled(
                 "message-row.revoke-delete",
                 message.can_be_deleted_for_all_users(),
             );
+
+            let can_pin_messages = message.chat().can_pin_messages();
+            self.action_set_enabled("message-row.pin", can_pin_messages && !message.is_pinned());
+            self.action_set_enabled("message-row.unpin", can_pin_messages && message.is_pinned());
         } else {
+            self.action_set_enabled("message-row.view-thread", false);
+            self.action_set_enabled("message-row.pin", false);
+            self.action_set_enabled("message-row.unpin", false);
             self.action_set_enabled("message-row.delete", false);
             self.action_set_enabled("message-row.revoke-delete", false);
         }
@@ -335,28 +412,51 @@ impl MessageRow {
             };
 
             match message_.content().0 {
-                // FIXME: Re-enable MessageVideo when
-                // https://github.com/paper-plane-developers/paper-plane/issues/410 is fixed
-                MessageContent::MessageAnimation(_) /*| MessageContent::MessageVideo(_)*/ => {
+                MessageContent::MessageAnimation(_) | MessageContent::MessageVideo(_) => {
                     self.update_specific_content::<_, MessageVideo>(message_.clone());
                 }
                 MessageContent::MessageAnimatedEmoji(data)
-                    if data.animated_emoji.sticker.clone().map(
-                        |s| matches!(s.format, StickerFormat::Webp | StickerFormat::Tgs)
-                    ).unwrap_or_default() => {
+                    if data
+                        .animated_emoji
+                        .sticker
+                        .clone()
+                        .map(|s| matches!(s.format, StickerFormat::Webp | StickerFormat::Tgs))
+                        .unwrap_or_default() =>
+                {
                     self.update_specific_content::<_, MessageSticker>(message_.clone());
                 }
                 MessageContent::MessagePhoto(_) => {
                     self.update_specific_content::<_, MessagePhoto>(message_.clone());
                 }
                 MessageContent::MessageSticker(data)
-                    if matches!(data.sticker.format, StickerFormat::Webp | StickerFormat::Tgs) =>
+                    if matches!(
+                        data.sticker.format,
+                        StickerFormat::Webp | StickerFormat::Tgs
+                    ) =>
                 {
                     self.update_specific_content::<_, MessageSticker>(message_.clone());
                 }
+                MessageContent::MessageAudio(_) => {
+                    self.update_specific_content::<_, MessageAudio>(message_.clone());
+                }
                 MessageContent::MessageDocument(_) => {
                     self.update_specific_content::<_, MessageDocument>(message_.clone());
                 }
+                MessageContent::MessageContact(_) => {
+                    self.update_specific_content::<_, MessageContact>(message_.clone());
+                }
+                MessageContent::MessageLocation(_) | MessageContent::MessageVenue(_) => {
+                    self.update_specific_content::<_, MessageLocation>(message_.clone());
+                }
+                MessageContent::MessagePoll(_) => {
+                    self.update_specific_content::<_, MessagePoll>(message_.clone());
+                }
+                MessageContent::MessageVideoNote(_) => {
+                    self.update_specific_content::<_, MessageVideoNote>(message_.clone());
+                }
+                MessageContent::MessageVoiceNote(_) => {
+                    self.update_specific_content::<_, MessageVoiceNote>(message_.clone());
+                }
                 _ => {
                     self.update_specific_content::<_, MessageText>(message);
                 }
@@ -407,12 +507,7 @@ impl MessageRow {
 
 fn can_send_messages_in_chat(chat: &Chat) -> bool {
     use tdlib::enums::ChatMemberStatus::*;
-    let member_status = match chat.type_() {
-        ChatType::Supergroup(supergroup) => Some(supergroup.status()),
-        ChatType::BasicGroup(supergroup) => Some(supergroup.status()),
-        _ => None,
-    };
-    member_status
+    chat.member_status()
         .map(|s| match s.0 {
             Creator(_) => true,
             Administrator(_) => true,
@@ -0,0 +1,344 @@
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+use tdlib::enums::PollType;
+use tdlib::types::Poll;
+use tdlib::types::PollOption;
+
+use super::base::MessageBaseExt;
+use crate::i18n::ngettext_f;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageBubble;
+use crate::tdlib::Message;
+use crate::utils::parse_formatted_text;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-message-poll.ui")]
+    pub(crate) struct MessagePoll {
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message: RefCell<Option<Message>>,
+        /// The check buttons of the options, when multiple answers can be chosen.
+        pub(super) check_buttons: RefCell<Vec<gtk::CheckButton>>,
+        #[template_child]
+        pub(super) message_bubble: TemplateChild<MessageBubble>,
+        #[template_child]
+        pub(super) question_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) type_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) options_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) voter_count_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) retract_vote_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) vote_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessagePoll {
+        const NAME: &'static str = "MessagePoll";
+        type Type = super::MessagePoll;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.install_action_async("message-poll.vote", None, |widget, _, _| async move {
+                let option_ids = widget
+                    .imp()
+                    .check_buttons
+                    .borrow()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, button)| button.is_active())
+                    .map(|(id, _)| id as i32)
+                    .collect();
+                widget.set_poll_answer(option_ids).await;
+            });
+            klass.install_action_async(
+                "message-poll.retract-vote",
+                None,
+                |widget, _, _| async move {
+                    widget.set_poll_answer(Vec::new()).await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessagePoll {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for MessagePoll {}
+    impl MessageBaseImpl for MessagePoll {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessagePoll(ObjectSubclass<imp::MessagePoll>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessagePoll {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        if let Some(old_message) = imp.message.take() {
+            let handler_id = imp.handler_id.take().unwrap();
+            old_message.disconnect(handler_id);
+        }
+
+        imp.message_bubble.update_from_message(&message, false);
+
+        let handler_id =
+            message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                obj.update_poll(message);
+            }));
+        imp.handler_id.replace(Some(handler_id));
+        self.update_poll(&message);
+
+        imp.message.replace(Some(message));
+        self.notify("message");
+    }
+}
+
+impl MessagePoll {
+    fn update_poll(&self, message: &Message) {
+        let MessageContent::MessagePoll(data) = message.content().0 else {
+            return;
+        };
+
+        let imp = self.imp();
+        let poll = data.poll;
+
+        let is_quiz = matches!(poll.r#type, PollType::Quiz(_));
+        let has_voted = poll.options.iter().any(|option| option.is_chosen);
+        let show_results = has_voted || poll.is_closed;
+        let (allow_multiple_answers, correct_option_id, explanation) = match poll.r#type {
+            PollType::Regular(ref data) => (data.allow_multiple_answers, -1, None),
+            PollType::Quiz(ref data) => (
+                false,
+                data.correct_option_id,
+                Some(data.explanation.clone()),
+            ),
+        };
+
+        imp.question_label.set_label(&poll.question);
+        imp.type_label.set_label(&poll_type(&poll));
+        imp.voter_count_label.set_label(&voter_count(&poll));
+
+        // Quiz answers can't be retracted
+        imp.retract_vote_button
+            .set_visible(has_voted && !poll.is_closed && !is_quiz);
+        imp.vote_button
+            .set_visible(!show_results && allow_multiple_answers);
+        self.action_set_enabled("message-poll.vote", false);
+
+        // The explanation of a quiz is only known after answering it
+        imp.message_bubble.set_label(
+            explanation
+                .filter(|_| has_voted)
+                .map(parse_formatted_text)
+                .unwrap_or_default(),
+        );
+
+        while let Some(child) = imp.options_box.first_child() {
+            imp.options_box.remove(&child);
+        }
+        imp.check_buttons.borrow_mut().clear();
+
+        for (id, option) in poll.options.into_iter().enumerate() {
+            let id = id as i32;
+            let row = if show_results {
+                let is_wrong = is_quiz && option.is_chosen && id != correct_option_id;
+                self.create_result_row(&option, id == correct_option_id, is_wrong)
+            } else if allow_multiple_answers {
+                self.create_check_button(option)
+            } else {
+                self.create_option_button(option, id)
+            };
+            imp.options_box.append(&row);
+        }
+    }
+
+    fn create_option_button(&self, option: PollOption, id: i32) -> gtk::Widget {
+        let label = gtk::Label::builder()
+            .label(&option.text)
+            .wrap(true)
+            .wrap_mode(gtk::pango::WrapMode::WordChar)
+            .xalign(0.0)
+            .build();
+        let button = gtk::Button::builder()
+            .child(&label)
+            .css_classes(["poll-option"])
+            .build();
+
+        button.connect_clicked(clone!(@weak self as obj => move |_| {
+            spawn(async move {
+                obj.set_poll_answer(vec![id]).await;
+            });
+        }));
+
+        button.upcast()
+    }
+
+    fn create_check_button(&self, option: PollOption) -> gtk::Widget {
+        let button = gtk::CheckButton::with_label(&option.text);
+
+        button.connect_toggled(clone!(@weak self as obj => move |_| {
+            let any_active = obj
+                .imp()
+                .check_buttons
+                .borrow()
+                .iter()
+                .any(gtk::CheckButton::is_active);
+            obj.action_set_enabled("message-poll.vote", any_active);
+        }));
+
+        self.imp().check_buttons.borrow_mut().push(button.clone());
+
+        button.upcast()
+    }
+
+    fn create_result_row(
+        &self,
+        option: &PollOption,
+        is_correct: bool,
+        is_wrong: bool,
+    ) -> gtk::Widget {
+        let percentage_label = gtk::Label::builder()
+            .label(format!("{}%", option.vote_percentage))
+            .width_chars(4)
+            .xalign(1.0)
+            .css_classes(["numeric", "heading"])
+            .build();
+        let text_label = gtk::Label::builder()
+            .label(&option.text)
+            .hexpand(true)
+            .wrap(true)
+            .wrap_mode(gtk::pango::WrapMode::WordChar)
+            .xalign(0.0)
+            .build();
+
+        let header = gtk::Box::builder().spacing(6).build();
+        header.append(&percentage_label);
+        header.append(&text_label);
+
+        let progress_bar = gtk::ProgressBar::builder()
+            .fraction(option.vote_percentage as f64 / 100.0)
+            .build();
+
+        let row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(3)
+            .css_classes(["poll-result"])
+            .build();
+        row.append(&header);
+        row.append(&progress_bar);
+
+        if is_correct {
+            row.add_css_class("correct");
+        } else if is_wrong {
+            row.add_css_class("wrong");
+        }
+
+        if option.is_chosen {
+            row.add_css_class("chosen");
+            header.append(&gtk::Image::from_icon_name("object-select-symbolic"));
+        }
+
+        row.upcast()
+    }
+
+    async fn set_poll_answer(&self, option_ids: Vec<i32>) {
+        let message = self.imp().message.borrow().clone();
+        if let Some(message) = message {
+            if let Err(e) = message.set_poll_answer(option_ids).await {
+                log::warn!("Error setting a poll answer: {:?}", e);
+            }
+        }
+    }
+}
+
+fn poll_type(poll: &Poll) -> String {
+    if poll.is_closed {
+        gettext("Final Results")
+    } else {
+        match (&poll.r#type, poll.is_anonymous) {
+            (PollType::Regular(_), true) => gettext("Anonymous Poll"),
+            (PollType::Regular(_), false) => gettext("Public Poll"),
+            (PollType::Quiz(_), true) => gettext("Anonymous Quiz"),
+            (PollType::Quiz(_), false) => gettext("Quiz"),
+        }
+    }
+}
+
+fn voter_count(poll: &Poll) -> String {
+    let count = poll.total_voter_count as u32;
+    if count == 0 {
+        if matches!(poll.r#type, PollType::Quiz(_)) {
+            gettext("No answers yet")
+        } else {
+            gettext("No votes yet")
+        }
+    } else if matches!(poll.r#type, PollType::Quiz(_)) {
+        ngettext_f(
+            "{num} answer",
+            "{num} answers",
+            count,
+            &[("num", &count.to_string())],
+        )
+    } else {
+        ngettext_f(
+            "{num} vote",
+            "{num} votes",
+            count,
+            &[("num", &count.to_string())],
+        )
+    }
+}
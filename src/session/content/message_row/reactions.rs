@@ -0,0 +1,195 @@
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::unsync::OnceCell;
+use tdlib::enums;
+use tdlib::enums::ReactionType;
+
+use crate::tdlib::Message;
+use crate::tdlib::MessageReaction;
+use crate::tdlib::MessageReactionList;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessageReactions {
+        layout-manager: BinLayout {};
+
+        FlowBox flow_box {
+            selection-mode: none;
+            column-spacing: 6;
+            row-spacing: 6;
+            max-children-per-line: 20;
+        }
+    }
+    "#)]
+    pub(crate) struct MessageReactions {
+        pub(super) message: glib::WeakRef<Message>,
+        pub(super) reactions_signal_group: OnceCell<glib::SignalGroup>,
+        #[template_child]
+        pub(super) flow_box: TemplateChild<gtk::FlowBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageReactions {
+        const NAME: &'static str = "MessageReactions";
+        type Type = super::MessageReactions;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_css_name("messagereactions");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageReactions {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.obj().create_signal_group();
+        }
+
+        fn dispose(&self) {
+            self.flow_box.unparent();
+        }
+    }
+
+    impl WidgetImpl for MessageReactions {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageReactions(ObjectSubclass<imp::MessageReactions>)
+        @extends gtk::Widget;
+}
+
+impl MessageReactions {
+    fn create_signal_group(&self) {
+        let reactions_signal_group = glib::SignalGroup::new(MessageReactionList::static_type());
+        reactions_signal_group.connect_local(
+            "items-changed",
+            false,
+            clone!(@weak self as obj => @default-return None, move |_| {
+                obj.update_visibility();
+                None
+            }),
+        );
+        self.imp()
+            .reactions_signal_group
+            .set(reactions_signal_group)
+            .unwrap();
+    }
+
+    pub(crate) fn set_message(&self, message: Option<&Message>) {
+        let imp = self.imp();
+
+        imp.message.set(message);
+
+        let reactions = message.map(|m| m.interaction_info().reactions());
+        imp.reactions_signal_group
+            .get()
+            .unwrap()
+            .set_target(reactions);
+        imp.flow_box.bind_model(
+            reactions,
+            clone!(@weak self as obj => @default-panic, move |item| {
+                obj.create_reaction_button(item.downcast_ref().unwrap())
+            }),
+        );
+
+        self.update_visibility();
+    }
+
+    fn update_visibility(&self) {
+        let has_reactions = self
+            .imp()
+            .message
+            .upgrade()
+            .map(|m| m.interaction_info().reactions().n_items() > 0)
+            .unwrap_or_default();
+
+        self.set_visible(has_reactions);
+    }
+
+    fn create_reaction_button(&self, reaction: &MessageReaction) -> gtk::Widget {
+        let emoji_label = gtk::Label::new(None);
+        let count_label = gtk::Label::new(None);
+
+        let content = gtk::Box::builder().spacing(3).build();
+        content.append(&emoji_label);
+        content.append(&count_label);
+
+        let button = gtk::Button::builder()
+            .child(&content)
+            .css_classes(["reaction"])
+            .build();
+
+        reaction
+            .bind_property("total-count", &count_label, "label")
+            .transform_to(|_, count: i32| Some(count.to_string()))
+            .sync_create()
+            .build();
+
+        update_chosen_style(&button, reaction);
+        reaction.connect_notify_local(
+            Some("is-chosen"),
+            clone!(@weak button => move |reaction, _| {
+                update_chosen_style(&button, reaction);
+            }),
+        );
+
+        match &reaction.type_().0 {
+            ReactionType::Emoji(data) => emoji_label.set_label(&data.emoji),
+            ReactionType::CustomEmoji(data) => {
+                // Custom emojis are represented by the emoji they're associated with, as they
+                // can't be rendered yet
+                if let Some(message) = self.imp().message.upgrade() {
+                    let custom_emoji_id = data.custom_emoji_id;
+                    let backend = message.chat().session().backend();
+                    spawn(clone!(@weak emoji_label => async move {
+                        let result = backend
+                            .get_custom_emoji_stickers(vec![custom_emoji_id])
+                            .await;
+                        match result {
+                            Ok(enums::Stickers::Stickers(data)) => {
+                                if let Some(sticker) = data.stickers.into_iter().next() {
+                                    emoji_label.set_label(&sticker.emoji);
+                                }
+                            }
+                            Err(e) => log::warn!("Error getting a custom emoji: {:?}", e),
+                        }
+                    }));
+                }
+            }
+        }
+
+        button.connect_clicked(clone!(@weak self as obj, @weak reaction => move |_| {
+            if let Some(message) = obj.imp().message.upgrade() {
+                let reaction_type = reaction.type_().0.clone();
+                spawn(async move {
+                    if let Err(e) = message.toggle_reaction(reaction_type).await {
+                        log::warn!("Error toggling a reaction: {:?}", e);
+                    }
+                });
+            }
+        }));
+
+        button.upcast()
+    }
+}
+
+fn update_chosen_style(button: &gtk::Button, reaction: &MessageReaction) {
+    if reaction.is_chosen() {
+        button.add_css_class("chosen");
+    } else {
+        button.remove_css_class("chosen");
+    }
+}
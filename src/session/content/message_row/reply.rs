@@ -22,6 +22,11 @@ mod imp {
     #[properties(wrapper_type = super::MessageReply)]
     #[template(string = r#"
     template $MessageReply : Widget {
+        GestureClick {
+            button: 1;
+            released => $on_released() swapped;
+        }
+
         Separator separator {
             width-request: 2;
         }
@@ -74,6 +79,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            klass.bind_template_instance_callbacks();
             klass.set_layout_manager_type::<gtk::BoxLayout>();
             klass.set_css_name("messagereply");
         }
@@ -119,7 +125,22 @@ glib::wrapper! {
         @extends gtk::Widget;
 }
 
+#[gtk::template_callbacks]
 impl MessageReply {
+    #[template_callback]
+    fn on_released(&self, _n_press: i32, _x: f64, _y: f64) {
+        let message = self.message().unwrap();
+
+        // Replies to messages of other chats can't be shown in this history
+        if message.reply_in_chat_id() == 0 || message.reply_in_chat_id() == message.chat().id() {
+            self.activate_action(
+                "chat-history.scroll-to-message",
+                Some(&message.reply_to_message_id().to_variant()),
+            )
+            .unwrap();
+        }
+    }
+
     pub(crate) fn new(message: &Message) -> Self {
         glib::Object::builder().property("message", message).build()
     }
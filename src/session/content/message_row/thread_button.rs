@@ -0,0 +1,163 @@
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::unsync::OnceCell;
+
+use crate::i18n::ngettext_f;
+use crate::tdlib::ChatType;
+use crate::tdlib::Message;
+use crate::tdlib::MessageInteractionInfo;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessageThreadButton {
+        layout-manager: BinLayout {};
+
+        Button button {
+            action-name: "message-row.view-thread";
+
+            Box {
+                spacing: 6;
+
+                Image {
+                    icon-name: "mail-reply-sender-symbolic";
+                }
+
+                Label label {
+                    hexpand: true;
+                    xalign: 0;
+                    ellipsize: end;
+                }
+
+                Image {
+                    icon-name: "go-next-symbolic";
+                }
+            }
+
+            styles [
+                "flat",
+            ]
+        }
+    }
+    "#)]
+    pub(crate) struct MessageThreadButton {
+        pub(super) message: glib::WeakRef<Message>,
+        pub(super) interaction_info_signal_group: OnceCell<glib::SignalGroup>,
+        #[template_child]
+        pub(super) button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageThreadButton {
+        const NAME: &'static str = "MessageThreadButton";
+        type Type = super::MessageThreadButton;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_css_name("messagethreadbutton");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageThreadButton {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.obj().create_signal_group();
+        }
+
+        fn dispose(&self) {
+            self.button.unparent();
+        }
+    }
+
+    impl WidgetImpl for MessageThreadButton {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageThreadButton(ObjectSubclass<imp::MessageThreadButton>)
+        @extends gtk::Widget;
+}
+
+impl MessageThreadButton {
+    fn create_signal_group(&self) {
+        let interaction_info_signal_group =
+            glib::SignalGroup::new(MessageInteractionInfo::static_type());
+        interaction_info_signal_group.connect_notify_local(
+            Some("reply-count"),
+            clone!(@weak self as obj => move |_, _| {
+                obj.update();
+            }),
+        );
+        self.imp()
+            .interaction_info_signal_group
+            .set(interaction_info_signal_group)
+            .unwrap();
+    }
+
+    pub(crate) fn set_message(&self, message: Option<&Message>) {
+        let imp = self.imp();
+
+        imp.message.set(message);
+        imp.interaction_info_signal_group
+            .get()
+            .unwrap()
+            .set_target(message.map(Message::interaction_info));
+
+        self.update();
+    }
+
+    fn update(&self) {
+        let Some(message) = self
+            .imp()
+            .message
+            .upgrade()
+            .filter(Message::can_get_message_thread)
+        else {
+            self.set_visible(false);
+            return;
+        };
+
+        let is_channel_message =
+            matches!(message.chat().type_(), ChatType::Supergroup(data) if data.is_channel());
+        let reply_count = message.interaction_info().reply_count();
+
+        let label = if is_channel_message {
+            if reply_count == 0 {
+                gettext("Leave a Comment")
+            } else {
+                ngettext_f(
+                    "{num} Comment",
+                    "{num} Comments",
+                    reply_count,
+                    &[("num", &reply_count.to_string())],
+                )
+            }
+        } else {
+            ngettext_f(
+                "{num} Reply",
+                "{num} Replies",
+                reply_count,
+                &[("num", &reply_count.to_string())],
+            )
+        };
+
+        self.imp().label.set_label(&label);
+
+        // Replies to group messages are already reachable from the message menu, so only show
+        // the button once there are replies to look at
+        self.set_visible(is_channel_message || reply_count > 0);
+    }
+}
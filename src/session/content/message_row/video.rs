@@ -9,13 +9,16 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use tdlib::enums::MessageContent;
+use tdlib::types::File;
 
 use super::base::MessageBaseExt;
+use crate::session::content::message_row::FileStatus;
 use crate::session::content::message_row::MediaPicture;
 use crate::session::content::message_row::MessageBase;
 use crate::session::content::message_row::MessageBaseImpl;
 use crate::session::content::message_row::MessageBubble;
 use crate::tdlib::Message;
+use crate::utils::format_duration;
 use crate::utils::parse_formatted_text;
 use crate::utils::spawn;
 use crate::Session;
@@ -39,6 +42,28 @@ mod imp {
 
                     styles ["osd-indicator"]
                 }
+
+                [overlay]
+                Button status_button {
+                    halign: center;
+                    valign: center;
+                    visible: false;
+                    clicked => $on_status_button_clicked() swapped;
+
+                    styles ["osd", "circular", "video-status"]
+
+                    Image status_image {
+                        pixel-size: 24;
+                    }
+                }
+
+                [overlay]
+                MediaControls controls {
+                    valign: end;
+                    visible: false;
+
+                    styles ["osd"]
+                }
             };
         }
     }
@@ -47,12 +72,22 @@ mod imp {
         pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
         pub(super) message: RefCell<Option<Message>>,
         pub(super) is_animation: Cell<bool>,
+        /// The video file of the message, with its latest download state.
+        pub(super) file: RefCell<Option<File>>,
+        /// The stream of the video or animation, once it's loaded.
+        pub(super) media: RefCell<Option<gtk::MediaFile>>,
         #[template_child]
         pub(super) message_bubble: TemplateChild<MessageBubble>,
         #[template_child]
         pub(super) picture: TemplateChild<MediaPicture>,
         #[template_child]
         pub(super) indicator: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) status_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) status_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) controls: TemplateChild<gtk::MediaControls>,
     }
 
     #[glib::object_subclass]
@@ -63,6 +98,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            klass.bind_template_callbacks();
             klass.set_layout_manager_type::<gtk::BinLayout>();
         }
 
@@ -98,8 +134,36 @@ mod imp {
         }
     }
 
-    impl WidgetImpl for MessageVideo {}
+    impl WidgetImpl for MessageVideo {
+        fn map(&self) {
+            self.parent_map();
+
+            // Animations play automatically, but only while they're visible
+            if self.is_animation.get() {
+                if let Some(media) = self.media.borrow().as_ref() {
+                    media.play();
+                }
+            }
+        }
+
+        fn unmap(&self) {
+            if let Some(media) = self.media.borrow().as_ref() {
+                media.pause();
+            }
+
+            self.parent_unmap();
+        }
+    }
+
     impl MessageBaseImpl for MessageVideo {}
+
+    #[gtk::template_callbacks]
+    impl MessageVideo {
+        #[template_callback]
+        fn on_status_button_clicked(&self) {
+            self.obj().handle_status_button_clicked();
+        }
+    }
 }
 
 glib::wrapper! {
@@ -124,15 +188,18 @@ impl MessageBaseExt for MessageVideo {
 
         imp.message_bubble.update_from_message(&message, true);
 
+        // The state of the file of the old message doesn't apply to the new one
+        imp.file.replace(None);
+
         let handler_id =
             message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
                 obj.update_content(message.content().0, &message.chat().session());
             }));
         imp.handler_id.replace(Some(handler_id));
 
+        imp.message.replace(Some(message.clone()));
         self.update_content(message.content().0, &message.chat().session());
 
-        imp.message.replace(Some(message));
         self.notify("message");
     }
 }
@@ -141,24 +208,24 @@ impl MessageVideo {
     fn update_content(&self, content: MessageContent, session: &Session) {
         let imp = self.imp();
 
-        let (caption, file, aspect_ratio, minithumbnail) =
+        let (caption, file, aspect_ratio, minithumbnail, thumbnail, indicator) =
             if let MessageContent::MessageAnimation(data) = content {
-                imp.indicator.set_label("GIF");
-                imp.is_animation.set(true);
                 (
                     data.caption,
                     data.animation.animation,
                     data.animation.width as f64 / data.animation.height as f64,
                     data.animation.minithumbnail,
+                    None,
+                    None,
                 )
             } else if let MessageContent::MessageVideo(data) = content {
-                self.update_remaining_time(data.video.duration as i64);
-                imp.is_animation.set(false);
                 (
                     data.caption,
                     data.video.video,
                     data.video.width as f64 / data.video.height as f64,
                     data.video.minithumbnail,
+                    data.video.thumbnail,
+                    Some(format_duration(data.video.duration)),
                 )
             } else {
                 unreachable!();
@@ -169,31 +236,151 @@ impl MessageVideo {
 
         imp.picture.set_aspect_ratio(aspect_ratio);
 
-        if file.local.is_downloading_completed {
-            self.load_video(&file.local.path);
-        } else {
-            imp.picture.set_paintable(
-                minithumbnail
-                    .and_then(|m| {
-                        gdk::Texture::from_bytes(&glib::Bytes::from_owned(glib::base64_decode(
-                            &m.data,
-                        )))
+        // Keep playing and downloading if only the caption changed
+        let is_same_file = imp.file.borrow().as_ref().map(|f| f.id) == Some(file.id);
+        if is_same_file {
+            return;
+        }
+
+        imp.is_animation.set(indicator.is_none());
+        imp.indicator
+            .set_label(indicator.as_deref().unwrap_or("GIF"));
+
+        self.set_media(None);
+        imp.picture.set_paintable(
+            minithumbnail
+                .and_then(|m| {
+                    gdk::Texture::from_bytes(&glib::Bytes::from_owned(glib::base64_decode(&m.data)))
                         .ok()
-                    })
-                    .as_ref(),
-            );
+                })
+                .as_ref(),
+        );
+
+        if imp.is_animation.get() {
+            imp.file.replace(Some(file.clone()));
+            imp.status_button.set_visible(false);
 
-            let file_id = file.id;
+            if file.local.is_downloading_completed {
+                self.load_video(&file.local.path);
+            } else {
+                let file_id = file.id;
+                spawn(clone!(@weak self as obj, @weak session => async move {
+                    obj.download_video(file_id, &session).await;
+                }));
+            }
+        } else {
+            // Videos can be large, so they're only downloaded on request and a thumbnail is
+            // shown in the meantime
+            if let Some(thumbnail) = thumbnail {
+                self.load_thumbnail(thumbnail.file, session);
+            }
+            self.update_file(file);
+        }
+    }
+
+    fn load_thumbnail(&self, file: File, session: &Session) {
+        let message = self.imp().message.borrow().clone();
+
+        if file.local.is_downloading_completed {
+            self.set_thumbnail(&file.local.path);
+        } else {
             spawn(clone!(@weak self as obj, @weak session => async move {
-                obj.download_video(file_id, &session).await;
+                if let Ok(file) = session.download_file(file.id).await {
+                    if obj.imp().message.borrow().as_ref() == message.as_ref() {
+                        obj.set_thumbnail(&file.local.path);
+                    }
+                }
             }));
         }
     }
 
+    fn set_thumbnail(&self, path: &str) {
+        let imp = self.imp();
+
+        // The video may have started in the meantime
+        if imp.media.borrow().is_none() {
+            match gdk::Texture::from_filename(path) {
+                Ok(texture) => imp.picture.set_paintable(Some(&texture)),
+                Err(e) => log::warn!("Error loading a video thumbnail: {e}"),
+            }
+        }
+    }
+
+    /// Updates the download state of the video.
+    fn update_file(&self, file: File) {
+        let imp = self.imp();
+
+        let status = FileStatus::from(&file);
+        let icon_name = match status {
+            FileStatus::Downloading(_) | FileStatus::Uploading(_) => "media-playback-stop-symbolic",
+            FileStatus::CanBeDownloaded => "folder-download-symbolic",
+            FileStatus::Downloaded => "media-playback-start-symbolic",
+        };
+        imp.status_image.set_icon_name(Some(icon_name));
+        imp.status_button.set_visible(imp.media.borrow().is_none());
+
+        match status {
+            FileStatus::Downloading(progress) | FileStatus::Uploading(progress) => {
+                let size = file.size.max(file.expected_size) as u64;
+                imp.indicator.set_label(&format!(
+                    "{} / {}",
+                    glib::format_size((size as f64 * progress) as u64),
+                    glib::format_size(size)
+                ));
+            }
+            _ => {
+                if let Some(MessageContent::MessageVideo(data)) = imp
+                    .message
+                    .borrow()
+                    .as_ref()
+                    .map(|message| message.content().0)
+                {
+                    imp.indicator
+                        .set_label(&format_duration(data.video.duration));
+                }
+            }
+        }
+
+        imp.file.replace(Some(file));
+    }
+
+    fn handle_status_button_clicked(&self) {
+        let imp = self.imp();
+
+        let Some(file) = imp.file.borrow().clone() else {
+            return;
+        };
+        let Some(message) = imp.message.borrow().clone() else {
+            return;
+        };
+        let session = message.chat().session();
+
+        match FileStatus::from(&file) {
+            FileStatus::CanBeDownloaded => {
+                session.download_file_with_updates(
+                    file.id,
+                    clone!(@weak self as obj, @weak message => move |file| {
+                        // The row may have been recycled for another message in the meantime
+                        if obj.imp().message.borrow().as_ref() == Some(&message) {
+                            obj.update_file(file);
+                        }
+                    }),
+                );
+            }
+            FileStatus::Downloading(_) => session.cancel_download_file(file.id),
+            FileStatus::Uploading(_) => {}
+            FileStatus::Downloaded => self.play_video(&file.local.path),
+        }
+    }
+
     async fn download_video(&self, file_id: i32, session: &Session) {
+        let message = self.imp().message.borrow().clone();
+
         match session.download_file(file_id).await {
             Ok(file) => {
-                self.load_video(&file.local.path);
+                if self.imp().message.borrow().as_ref() == message.as_ref() {
+                    self.load_video(&file.local.path);
+                }
             }
             Err(e) => {
                 log::warn!("Failed to download a video: {e:?}");
@@ -201,35 +388,49 @@ impl MessageVideo {
         }
     }
 
+    /// Loads an animation, which is played muted and in a loop.
     fn load_video(&self, path: &str) {
-        let imp = self.imp();
-
         let media = gtk::MediaFile::for_filename(path);
         media.set_muted(true);
         media.set_loop(true);
-        media.play();
 
-        if !imp.is_animation.get() {
-            media.connect_timestamp_notify(clone!(@weak self as obj => move |media| {
-                let time = (media.duration() - media.timestamp()) / i64::pow(10, 6);
-                obj.update_remaining_time(time);
-            }));
+        self.set_media(Some(media.clone()));
+
+        if self.is_mapped() {
+            media.play();
         }
+    }
+
+    /// Plays a video with sound, showing the playback controls.
+    fn play_video(&self, path: &str) {
+        let imp = self.imp();
+
+        let media = gtk::MediaFile::for_filename(path);
+        media.connect_timestamp_notify(clone!(@weak self as obj => move |media| {
+            let time = (media.duration() - media.timestamp()) / 1_000_000;
+            obj.imp().indicator.set_label(&format_duration(time as i32));
+        }));
+
+        self.set_media(Some(media.clone()));
+        imp.status_button.set_visible(false);
+        imp.controls.set_media_stream(Some(&media));
+        imp.controls.set_visible(true);
 
-        imp.picture.set_paintable(Some(&media));
+        media.play();
     }
 
-    fn update_remaining_time(&self, time: i64) {
+    fn set_media(&self, media: Option<gtk::MediaFile>) {
         let imp = self.imp();
-        let seconds = time % 60;
-        let minutes = (time % (60 * 60)) / 60;
-        let hours = time / (60 * 60);
 
-        if hours > 0 {
-            imp.indicator
-                .set_label(&format!("{hours}:{minutes:02}:{seconds:02}"));
+        if let Some(old_media) = imp.media.replace(media.clone()) {
+            old_media.pause();
+        }
+
+        if let Some(media) = media {
+            imp.picture.set_paintable(Some(&media));
         } else {
-            imp.indicator.set_label(&format!("{minutes}:{seconds:02}"));
+            imp.controls.set_media_stream(gtk::MediaStream::NONE);
+            imp.controls.set_visible(false);
         }
     }
 }
@@ -0,0 +1,338 @@
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+
+use super::base::MessageBaseExt;
+use crate::session::content::message_row::MediaPicture;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageIndicators;
+use crate::tdlib::Message;
+use crate::utils::format_duration;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    using Adw 1;
+
+    template $MessageVideoNote : $MessageBase {
+        layout-manager: BinLayout {};
+
+        Overlay {
+            GestureClick {
+                button: 1;
+
+                released => $on_released() swapped;
+            }
+
+            Adw.Clamp {
+                maximum-size: 240;
+                tightening-threshold: 240;
+
+                $MessageMediaPicture picture {
+                    aspect-ratio: 1;
+                }
+            }
+
+            [overlay]
+            Box {
+                halign: start;
+                valign: end;
+                spacing: 3;
+
+                styles ["osd-indicator"]
+
+                Label time_label {}
+
+                Image muted_image {
+                    icon-name: "audio-volume-muted-symbolic";
+                    pixel-size: 12;
+                }
+            }
+
+            [overlay]
+            $MessageIndicators indicators {
+                halign: end;
+                valign: end;
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct MessageVideoNote {
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message: RefCell<Option<Message>>,
+        /// The video of the note, once it's downloaded.
+        pub(super) media: RefCell<Option<gtk::MediaFile>>,
+        #[template_child]
+        pub(super) picture: TemplateChild<MediaPicture>,
+        #[template_child]
+        pub(super) time_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) muted_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) indicators: TemplateChild<MessageIndicators>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageVideoNote {
+        const NAME: &'static str = "MessageVideoNote";
+        type Type = super::MessageVideoNote;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+            klass.set_css_name("messagevideonote");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageVideoNote {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for MessageVideoNote {
+        fn map(&self) {
+            self.parent_map();
+
+            // Autoplay the muted preview only while the note is visible
+            if let Some(media) = self.media.borrow().as_ref() {
+                if media.is_muted() {
+                    media.play();
+                }
+            }
+        }
+
+        fn unmap(&self) {
+            if let Some(media) = self.media.borrow().as_ref() {
+                media.pause();
+            }
+
+            self.parent_unmap();
+        }
+    }
+
+    impl MessageBaseImpl for MessageVideoNote {}
+
+    #[gtk::template_callbacks]
+    impl MessageVideoNote {
+        #[template_callback]
+        fn on_released(&self, _n_press: i32, _x: f64, _y: f64) {
+            self.obj().toggle_sound();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageVideoNote(ObjectSubclass<imp::MessageVideoNote>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessageVideoNote {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        if let Some(old_message) = imp.message.take() {
+            let handler_id = imp.handler_id.take().unwrap();
+            old_message.disconnect(handler_id);
+        }
+
+        imp.indicators.set_message(message.clone().upcast());
+
+        let handler_id =
+            message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                obj.update_content(message);
+            }));
+        imp.handler_id.replace(Some(handler_id));
+
+        imp.message.replace(Some(message.clone()));
+        self.update_content(&message);
+
+        self.notify("message");
+    }
+}
+
+impl MessageVideoNote {
+    fn update_content(&self, message: &Message) {
+        let MessageContent::MessageVideoNote(data) = message.content().0 else {
+            return;
+        };
+
+        let imp = self.imp();
+        let file = data.video_note.video;
+
+        // The content also changes when the note is marked as viewed, which must not interrupt
+        // the playback
+        if let Some(media) = imp.media.borrow().as_ref() {
+            if media.file().and_then(|f| f.path()).map_or(false, |path| {
+                path.to_str() == Some(file.local.path.as_str())
+            }) {
+                return;
+            }
+        }
+
+        self.set_media(None);
+        self.update_time_label(data.video_note.duration as i64);
+
+        if file.local.is_downloading_completed {
+            self.load_video(&file.local.path);
+        } else {
+            imp.picture.set_paintable(
+                data.video_note
+                    .minithumbnail
+                    .and_then(|m| {
+                        gdk::Texture::from_bytes(&glib::Bytes::from_owned(glib::base64_decode(
+                            &m.data,
+                        )))
+                        .ok()
+                    })
+                    .as_ref(),
+            );
+
+            let session = message.chat().session();
+            let file_id = file.id;
+            spawn(clone!(@weak self as obj, @weak message => async move {
+                match session.download_file(file_id).await {
+                    Ok(file) => {
+                        // The row may have been recycled for another message in the meantime
+                        if obj.imp().message.borrow().as_ref() == Some(&message) {
+                            obj.load_video(&file.local.path);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to download a video note: {e:?}");
+                    }
+                }
+            }));
+        }
+    }
+
+    fn load_video(&self, path: &str) {
+        let media = gtk::MediaFile::for_filename(path);
+        media.set_muted(true);
+        media.set_loop(true);
+
+        media.connect_timestamp_notify(clone!(@weak self as obj => move |media| {
+            if !media.is_muted() {
+                obj.update_time_label((media.duration() - media.timestamp()) / 1_000_000);
+            }
+        }));
+        media.connect_ended_notify(clone!(@weak self as obj => move |media| {
+            if media.is_ended() {
+                obj.set_muted(true);
+            }
+        }));
+
+        self.imp().picture.set_paintable(Some(&media));
+        self.set_media(Some(media.clone()));
+
+        if self.is_mapped() {
+            media.play();
+        }
+    }
+
+    fn set_media(&self, media: Option<gtk::MediaFile>) {
+        if let Some(old_media) = self.imp().media.replace(media) {
+            old_media.pause();
+        }
+        self.imp().muted_image.set_visible(true);
+    }
+
+    /// Restarts the note with sound when the muted preview is clicked, otherwise pauses or
+    /// resumes it.
+    fn toggle_sound(&self) {
+        let Some(media) = self.imp().media.borrow().clone() else {
+            return;
+        };
+
+        if media.is_muted() {
+            self.set_muted(false);
+
+            let message = self.imp().message.borrow().clone();
+            if let Some(message) = message {
+                if let MessageContent::MessageVideoNote(data) = message.content().0 {
+                    if !data.is_viewed && !message.is_outgoing() {
+                        spawn(clone!(@weak message => async move {
+                            if let Err(e) = message.open_content().await {
+                                log::warn!("Error marking a video note as viewed: {:?}", e);
+                            }
+                        }));
+                    }
+                }
+            }
+        } else if media.is_playing() {
+            media.pause();
+        } else {
+            media.play();
+        }
+    }
+
+    /// Switches between the looped, muted preview and the playback with sound, which starts
+    /// from the beginning.
+    fn set_muted(&self, muted: bool) {
+        let imp = self.imp();
+        let Some(media) = imp.media.borrow().clone() else {
+            return;
+        };
+
+        media.set_muted(muted);
+        media.set_loop(muted);
+        media.seek(0);
+        if !muted || self.is_mapped() {
+            media.play();
+        }
+
+        imp.muted_image.set_visible(muted);
+        if muted {
+            self.update_time_label(media.duration() / 1_000_000);
+        }
+    }
+
+    fn update_time_label(&self, seconds: i64) {
+        self.imp()
+            .time_label
+            .set_label(&format_duration(seconds as i32));
+    }
+}
@@ -0,0 +1,318 @@
+mod waveform;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+
+use self::waveform::VoiceNoteWaveform;
+use super::base::MessageBaseExt;
+use crate::audio_player::AudioPlayer;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageBubble;
+use crate::tdlib::Message;
+use crate::utils::format_duration;
+use crate::utils::parse_formatted_text;
+use crate::utils::spawn;
+
+/// The playback rates that can be chosen with the speed button.
+const RATES: &[f64] = &[1.0, 1.5, 2.0];
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-message-voice-note.ui")]
+    pub(crate) struct MessageVoiceNote {
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message: RefCell<Option<Message>>,
+        /// The player of the voice note, which is created when it's played for the first time.
+        pub(super) player: RefCell<Option<AudioPlayer>>,
+        /// The duration of the voice note in seconds, as reported by TDLib.
+        pub(super) duration: Cell<i32>,
+        /// The index of the chosen playback rate in `RATES`.
+        pub(super) rate_index: Cell<usize>,
+        #[template_child]
+        pub(super) message_bubble: TemplateChild<MessageBubble>,
+        #[template_child]
+        pub(super) button_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) play_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) waveform: TemplateChild<VoiceNoteWaveform>,
+        #[template_child]
+        pub(super) time_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) unlistened_indicator: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) speed_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageVoiceNote {
+        const NAME: &'static str = "MessageVoiceNote";
+        type Type = super::MessageVoiceNote;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            VoiceNoteWaveform::static_type();
+            klass.bind_template();
+
+            klass.install_action("message-voice-note.play-pause", None, |widget, _, _| {
+                widget.play_pause();
+            });
+            klass.install_action("message-voice-note.cycle-rate", None, |widget, _, _| {
+                widget.cycle_rate();
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageVoiceNote {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            self.waveform
+                .connect_seek(clone!(@weak obj => move |_, fraction| {
+                    obj.seek(fraction);
+                }));
+
+            obj.update_rate_label();
+        }
+    }
+
+    impl WidgetImpl for MessageVoiceNote {}
+    impl MessageBaseImpl for MessageVoiceNote {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageVoiceNote(ObjectSubclass<imp::MessageVoiceNote>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessageVoiceNote {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        if let Some(old_message) = imp.message.take() {
+            let handler_id = imp.handler_id.take().unwrap();
+            old_message.disconnect(handler_id);
+        }
+
+        // Stop the voice note of the previous message
+        if let Some(player) = imp.player.take() {
+            player.pause();
+        }
+        imp.button_stack.set_visible_child_name("icon");
+        self.update_play_button(false);
+
+        imp.message_bubble.update_from_message(&message, false);
+
+        let handler_id =
+            message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                obj.update_voice_note(message);
+            }));
+        imp.handler_id.replace(Some(handler_id));
+        self.update_voice_note(&message);
+
+        imp.message.replace(Some(message));
+        self.update_progress();
+        self.notify("message");
+    }
+}
+
+impl MessageVoiceNote {
+    fn update_voice_note(&self, message: &Message) {
+        let MessageContent::MessageVoiceNote(data) = message.content().0 else {
+            return;
+        };
+
+        let imp = self.imp();
+
+        imp.message_bubble
+            .set_label(parse_formatted_text(data.caption));
+        imp.waveform.set_waveform(&data.voice_note.waveform);
+        imp.unlistened_indicator
+            .set_visible(!data.is_listened && !message.is_outgoing());
+        imp.duration.set(data.voice_note.duration);
+    }
+
+    fn play_pause(&self) {
+        let imp = self.imp();
+
+        if let Some(player) = imp.player.borrow().as_ref() {
+            if player.is_playing() {
+                player.pause();
+            } else {
+                player.play();
+            }
+            return;
+        }
+
+        // The voice note is already being downloaded
+        if imp.button_stack.visible_child_name().as_deref() == Some("spinner") {
+            return;
+        }
+
+        let Some(message) = imp.message.borrow().clone() else {
+            return;
+        };
+        let MessageContent::MessageVoiceNote(data) = message.content().0 else {
+            return;
+        };
+
+        let file = data.voice_note.voice;
+        if file.local.is_downloading_completed {
+            self.play_file(&message, &file.local.path);
+        } else {
+            imp.button_stack.set_visible_child_name("spinner");
+
+            message.chat().session().download_file_with_updates(
+                file.id,
+                clone!(@weak self as obj, @weak message => move |file| {
+                    if !file.local.is_downloading_completed {
+                        return;
+                    }
+
+                    // Only play the voice note if the row still shows the same message
+                    let imp = obj.imp();
+                    if imp.message.borrow().as_ref() == Some(&message) {
+                        imp.button_stack.set_visible_child_name("icon");
+                        obj.play_file(&message, &file.local.path);
+                    }
+                }),
+            );
+        }
+    }
+
+    fn play_file(&self, message: &Message, path: &str) {
+        let imp = self.imp();
+
+        let player = AudioPlayer::new();
+        player.set_rate(RATES[imp.rate_index.get()]);
+        player.connect_is_playing_notify(clone!(@weak self as obj => move |player, _| {
+            obj.update_play_button(player.is_playing());
+        }));
+        player.connect_position_notify(clone!(@weak self as obj => move |_, _| {
+            obj.update_progress();
+        }));
+        player.connect_duration_notify(clone!(@weak self as obj => move |_, _| {
+            obj.update_progress();
+        }));
+        imp.player.replace(Some(player.clone()));
+
+        player.set_file(path);
+        player.play();
+
+        if let MessageContent::MessageVoiceNote(data) = message.content().0 {
+            if !data.is_listened && !message.is_outgoing() {
+                spawn(clone!(@weak message => async move {
+                    if let Err(e) = message.open_content().await {
+                        log::warn!("Error marking a voice note as listened: {:?}", e);
+                    }
+                }));
+            }
+        }
+    }
+
+    fn seek(&self, fraction: f64) {
+        if let Some(player) = self.imp().player.borrow().as_ref() {
+            let duration = player.duration();
+            if duration > 0 {
+                player.seek((duration as f64 * fraction) as i64);
+            }
+        }
+    }
+
+    fn cycle_rate(&self) {
+        let imp = self.imp();
+
+        let rate_index = (imp.rate_index.get() + 1) % RATES.len();
+        imp.rate_index.set(rate_index);
+
+        if let Some(player) = imp.player.borrow().as_ref() {
+            player.set_rate(RATES[rate_index]);
+        }
+
+        self.update_rate_label();
+    }
+
+    fn update_rate_label(&self) {
+        let imp = self.imp();
+        imp.speed_button
+            .set_label(&format!("{}×", RATES[imp.rate_index.get()]));
+    }
+
+    fn update_play_button(&self, is_playing: bool) {
+        self.imp().play_image.set_icon_name(Some(if is_playing {
+            "media-playback-pause-symbolic"
+        } else {
+            "media-playback-start-symbolic"
+        }));
+    }
+
+    fn update_progress(&self) {
+        let imp = self.imp();
+
+        // Show the position while the voice note is being played and its duration otherwise
+        let (position, progress) = match imp.player.borrow().as_ref() {
+            Some(player)
+                if player.duration() > 0 && (player.is_playing() || player.position() > 0) =>
+            {
+                let position = player.position();
+                (
+                    Some((position / 1_000_000) as i32),
+                    position as f64 / player.duration() as f64,
+                )
+            }
+            _ => (None, 0.0),
+        };
+
+        imp.waveform.set_progress(progress.clamp(0.0, 1.0));
+        imp.time_label
+            .set_label(&format_duration(position.unwrap_or(imp.duration.get())));
+    }
+}
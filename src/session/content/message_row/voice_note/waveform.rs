@@ -0,0 +1,201 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::clone;
+use glib::subclass::Signal;
+use gtk::gdk;
+use gtk::glib;
+use gtk::graphene;
+use gtk::gsk;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use once_cell::sync::Lazy;
+
+/// The width of a bar of the waveform.
+const BAR_WIDTH: f32 = 3.0;
+/// The space between two bars of the waveform.
+const BAR_SPACING: f32 = 2.0;
+/// The maximum value of a sample of a waveform encoded by Telegram.
+const MAX_SAMPLE: u8 = 31;
+const HEIGHT: i32 = 24;
+const MIN_WIDTH: i32 = 80;
+const NATURAL_WIDTH: i32 = 200;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::VoiceNoteWaveform)]
+    pub(crate) struct VoiceNoteWaveform {
+        /// The samples of the waveform, in the range from 0 to 31.
+        pub(super) samples: RefCell<Vec<u8>>,
+        /// The fraction of the voice note that was already played.
+        #[property(get, set = Self::set_progress, minimum = 0.0, maximum = 1.0, explicit_notify)]
+        pub(super) progress: Cell<f64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for VoiceNoteWaveform {
+        const NAME: &'static str = "MessageVoiceNoteWaveform";
+        type Type = super::VoiceNoteWaveform;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("voicenotewaveform");
+        }
+    }
+
+    impl ObjectImpl for VoiceNoteWaveform {
+        fn properties() -> &'static [glib::ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            self.derived_set_property(id, value, pspec)
+        }
+
+        fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            self.derived_property(id, pspec)
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("seek")
+                    .param_types([f64::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.set_cursor_from_name(Some("pointer"));
+
+            let gesture = gtk::GestureDrag::new();
+            gesture.connect_drag_begin(clone!(@weak obj => move |_, x, _| {
+                obj.emit_seek(x);
+            }));
+            gesture.connect_drag_update(clone!(@weak obj => move |gesture, offset_x, _| {
+                if let Some((x, _)) = gesture.start_point() {
+                    obj.emit_seek(x + offset_x);
+                }
+            }));
+            obj.add_controller(gesture);
+        }
+    }
+
+    impl WidgetImpl for VoiceNoteWaveform {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            match orientation {
+                gtk::Orientation::Horizontal => (MIN_WIDTH, NATURAL_WIDTH, -1, -1),
+                _ => (HEIGHT, HEIGHT, -1, -1),
+            }
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+            let width = widget.width() as f32;
+            let height = widget.height() as f32;
+
+            let samples = self.samples.borrow();
+            let n_bars = ((width + BAR_SPACING) / (BAR_WIDTH + BAR_SPACING)) as usize;
+            if n_bars == 0 {
+                return;
+            }
+
+            let played_color = widget.color();
+            let color = gdk::RGBA::new(
+                played_color.red(),
+                played_color.green(),
+                played_color.blue(),
+                played_color.alpha() * 0.4,
+            );
+            let played_width = width * self.progress.get() as f32;
+
+            for i in 0..n_bars {
+                // Use the loudest sample of the ones covered by the bar
+                let start = i * samples.len() / n_bars;
+                let end = ((i + 1) * samples.len() / n_bars).max(start + 1);
+                let sample = samples
+                    .get(start..end.min(samples.len()))
+                    .map_or(0, |samples| {
+                        samples.iter().copied().max().unwrap_or_default()
+                    });
+
+                let bar_height =
+                    (height * sample as f32 / MAX_SAMPLE as f32).clamp(BAR_WIDTH, height);
+                let x = i as f32 * (BAR_WIDTH + BAR_SPACING);
+                let rect =
+                    graphene::Rect::new(x, (height - bar_height) / 2.0, BAR_WIDTH, bar_height);
+
+                snapshot.push_rounded_clip(&gsk::RoundedRect::from_rect(rect, BAR_WIDTH / 2.0));
+                snapshot.append_color(
+                    if x < played_width {
+                        &played_color
+                    } else {
+                        &color
+                    },
+                    &rect,
+                );
+                snapshot.pop();
+            }
+        }
+    }
+
+    impl VoiceNoteWaveform {
+        fn set_progress(&self, progress: f64) {
+            if self.progress.replace(progress) != progress {
+                let obj = self.obj();
+                obj.queue_draw();
+                obj.notify_progress();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A widget that draws the waveform of a voice note and that can be clicked or dragged to seek.
+    pub(crate) struct VoiceNoteWaveform(ObjectSubclass<imp::VoiceNoteWaveform>)
+        @extends gtk::Widget;
+}
+
+impl VoiceNoteWaveform {
+    /// Sets the waveform of the voice note, as received from TDLib: a base64 encoded list of
+    /// 5-bit samples.
+    pub(crate) fn set_waveform(&self, waveform: &str) {
+        let bytes = glib::base64_decode(waveform);
+
+        // The samples are packed in little-endian bit order
+        let samples = (0..bytes.len() * 8 / 5)
+            .map(|i| {
+                let bit_offset = i * 5;
+                let byte = bit_offset / 8;
+                let value =
+                    bytes[byte] as u16 | bytes.get(byte + 1).map_or(0, |next| (*next as u16) << 8);
+                ((value >> (bit_offset % 8)) & MAX_SAMPLE as u16) as u8
+            })
+            .collect();
+
+        self.imp().samples.replace(samples);
+        self.queue_draw();
+    }
+
+    fn emit_seek(&self, x: f64) {
+        let width = self.width();
+        if width > 0 {
+            let fraction = (x / width as f64).clamp(0.0, 1.0);
+            self.emit_by_name::<()>("seek", &[&fraction]);
+        }
+    }
+
+    pub(crate) fn connect_seek<F: Fn(&Self, f64) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("seek", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let fraction = values[1].get::<f64>().unwrap();
+            f(&obj, fraction);
+            None
+        })
+    }
+}
@@ -0,0 +1,219 @@
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums::MessageContent;
+
+use crate::audio_player::AudioPlayer;
+use crate::session::AudioPlaylist;
+use crate::strings;
+use crate::utils::format_duration;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-mini-player.ui")]
+    pub(crate) struct MiniPlayer {
+        pub(super) playlist: glib::WeakRef<AudioPlaylist>,
+        pub(super) playlist_signal_group: OnceCell<glib::SignalGroup>,
+        pub(super) player_signal_group: OnceCell<glib::SignalGroup>,
+        #[template_child]
+        pub(super) revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub(super) play_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) performer_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) time_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) progress_bar: TemplateChild<gtk::ProgressBar>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MiniPlayer {
+        const NAME: &'static str = "ContentMiniPlayer";
+        type Type = super::MiniPlayer;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_css_name("miniplayer");
+
+            klass.install_action("mini-player.play-pause", None, |widget, _, _| {
+                if let Some(playlist) = widget.playlist() {
+                    playlist.play_pause();
+                }
+            });
+            klass.install_action_async("mini-player.next", None, |widget, _, _| async move {
+                if let Some(playlist) = widget.playlist() {
+                    playlist.play_next().await;
+                }
+            });
+            klass.install_action("mini-player.close", None, |widget, _, _| {
+                if let Some(playlist) = widget.playlist() {
+                    playlist.stop();
+                }
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MiniPlayer {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<AudioPlaylist>("playlist")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "playlist" => self.obj().set_playlist(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "playlist" => self.obj().playlist().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.create_signal_groups();
+            obj.update();
+        }
+
+        fn dispose(&self) {
+            self.revealer.unparent();
+        }
+    }
+
+    impl WidgetImpl for MiniPlayer {}
+}
+
+glib::wrapper! {
+    /// A bar that shows the audio message that is being played by the audio playlist of a
+    /// session, which keeps being visible when switching chats.
+    pub(crate) struct MiniPlayer(ObjectSubclass<imp::MiniPlayer>)
+        @extends gtk::Widget;
+}
+
+impl MiniPlayer {
+    fn create_signal_groups(&self) {
+        let imp = self.imp();
+
+        let playlist_signal_group = glib::SignalGroup::new(AudioPlaylist::static_type());
+        playlist_signal_group.connect_notify_local(
+            Some("message"),
+            clone!(@weak self as obj => move |_, _| {
+                obj.update();
+            }),
+        );
+        imp.playlist_signal_group
+            .set(playlist_signal_group)
+            .unwrap();
+
+        let player_signal_group = glib::SignalGroup::new(AudioPlayer::static_type());
+        for property in ["is-playing", "position", "duration"] {
+            player_signal_group.connect_notify_local(
+                Some(property),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_state();
+                }),
+            );
+        }
+        imp.player_signal_group.set(player_signal_group).unwrap();
+    }
+
+    pub(crate) fn playlist(&self) -> Option<AudioPlaylist> {
+        self.imp().playlist.upgrade()
+    }
+
+    pub(crate) fn set_playlist(&self, playlist: Option<&AudioPlaylist>) {
+        if self.playlist().as_ref() == playlist {
+            return;
+        }
+
+        let imp = self.imp();
+        imp.playlist.set(playlist);
+        imp.playlist_signal_group
+            .get()
+            .unwrap()
+            .set_target(playlist);
+        imp.player_signal_group
+            .get()
+            .unwrap()
+            .set_target(playlist.map(AudioPlaylist::player));
+
+        self.update();
+        self.notify("playlist");
+    }
+
+    fn update(&self) {
+        let imp = self.imp();
+
+        let audio = self
+            .playlist()
+            .and_then(|playlist| playlist.message())
+            .and_then(|message| match message.content().0 {
+                MessageContent::MessageAudio(data) => Some(data.audio),
+                _ => None,
+            });
+
+        // Keep the labels while the revealer is being closed
+        if let Some(ref audio) = audio {
+            imp.title_label.set_label(&strings::audio_title(audio));
+            imp.performer_label
+                .set_label(&strings::audio_performer(audio));
+        }
+        imp.revealer.set_reveal_child(audio.is_some());
+
+        self.update_state();
+    }
+
+    fn update_state(&self) {
+        let imp = self.imp();
+
+        let Some(playlist) = self.playlist() else {
+            return;
+        };
+        let player = playlist.player();
+
+        imp.play_image.set_icon_name(Some(if player.is_playing() {
+            "media-playback-pause-symbolic"
+        } else {
+            "media-playback-start-symbolic"
+        }));
+
+        let position = player.position();
+        let duration = player.duration();
+        if duration > 0 {
+            imp.progress_bar
+                .set_fraction((position as f64 / duration as f64).clamp(0.0, 1.0));
+            imp.time_label.set_label(&format!(
+                "{} / {}",
+                format_duration((position / 1_000_000) as i32),
+                format_duration((duration / 1_000_000) as i32)
+            ));
+        } else {
+            imp.progress_bar.set_fraction(0.0);
+            imp.time_label.set_label("");
+        }
+    }
+}
@@ -5,13 +5,20 @@ mod chat_history_item;
 mod chat_history_model;
 mod chat_history_row;
 mod chat_info_window;
+mod chat_search_bar;
+mod create_poll_window;
 mod event_row;
+mod forum_topic_picker;
+mod message_reaction_picker;
 mod message_row;
+mod mini_player;
+mod pinned_message_bar;
 mod send_media_window;
 
 use std::cell::RefCell;
 
 use adw::subclass::prelude::BinImpl;
+use glib::clone;
 use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
@@ -27,10 +34,19 @@ use self::chat_history_model::ChatHistoryError;
 use self::chat_history_model::ChatHistoryModel;
 use self::chat_history_row::ChatHistoryRow;
 use self::chat_info_window::ChatInfoWindow;
+use self::chat_search_bar::ChatSearchBar;
+use self::create_poll_window::CreatePollWindow;
 use self::event_row::EventRow;
+use self::forum_topic_picker::ForumTopicPicker;
+use self::message_reaction_picker::MessageReactionPicker;
 use self::message_row::MessageRow;
+use self::mini_player::MiniPlayer;
+use self::pinned_message_bar::PinnedMessageBar;
 use self::send_media_window::SendMediaWindow;
 use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::tdlib::ForumTopic;
+use crate::utils::spawn;
 
 mod imp {
     use super::*;
@@ -45,6 +61,10 @@ mod imp {
         pub(super) unselected_chat_view: TemplateChild<adw::ToolbarView>,
         #[template_child]
         pub(super) chat_history: TemplateChild<ChatHistory>,
+        #[template_child]
+        pub(super) thread_history: TemplateChild<ChatHistory>,
+        #[template_child]
+        pub(super) forum_topic_picker: TemplateChild<ForumTopicPicker>,
     }
 
     #[glib::object_subclass]
@@ -55,7 +75,24 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             ChatHistory::static_type();
+            ForumTopicPicker::static_type();
             klass.bind_template();
+            klass.bind_template_callbacks();
+
+            klass.install_action("content.show-topics", None, move |widget, _, _| {
+                widget.show_topics();
+            });
+            klass.install_action_async(
+                "content.open-message-thread",
+                Some("(xx)"),
+                |widget, _, variant| async move {
+                    let (chat_id, message_id) = variant.and_then(|v| v.get()).unwrap();
+                    widget.open_message_thread(chat_id, message_id).await;
+                },
+            );
+            klass.install_action("content.close-message-thread", None, move |widget, _, _| {
+                widget.close_message_thread();
+            });
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -97,6 +134,15 @@ mod imp {
 
     impl WidgetImpl for Content {}
     impl BinImpl for Content {}
+
+    #[gtk::template_callbacks]
+    impl Content {
+        #[template_callback]
+        fn on_topic_activated(&self, topic: ForumTopic) {
+            self.chat_history.set_topic(Some(topic));
+            self.stack.set_visible_child(&*self.chat_history);
+        }
+    }
 }
 
 glib::wrapper! {
@@ -116,7 +162,12 @@ impl Content {
     }
 
     pub(crate) fn handle_paste_action(&self) {
-        self.imp().chat_history.handle_paste_action();
+        let imp = self.imp();
+        if imp.stack.visible_child().as_ref() == Some(imp.thread_history.upcast_ref()) {
+            imp.thread_history.handle_paste_action();
+        } else {
+            imp.chat_history.handle_paste_action();
+        }
     }
 
     pub(crate) fn chat(&self) -> Option<Chat> {
@@ -129,14 +180,97 @@ impl Content {
         }
 
         let imp = self.imp();
-        if chat.is_some() {
-            imp.stack.set_visible_child(&imp.chat_history.get());
-        } else {
-            imp.stack.set_visible_child(&imp.unselected_chat_view.get());
+        imp.thread_history.set_chat(None);
+        match chat {
+            Some(ref chat) if chat.is_forum() => {
+                imp.chat_history.set_chat(None);
+                imp.forum_topic_picker.set_chat(Some(chat.clone()));
+                imp.stack.set_visible_child(&imp.forum_topic_picker.get());
+            }
+            Some(ref chat) => {
+                imp.chat_history.set_chat(Some(chat.clone()));
+                imp.stack.set_visible_child(&imp.chat_history.get());
+            }
+            None => {
+                imp.chat_history.set_chat(None);
+                imp.stack.set_visible_child(&imp.unselected_chat_view.get());
+            }
         }
 
         imp.chat.replace(chat);
 
         self.notify("chat");
     }
+
+    /// Shows the history of the current chat scrolled to the specified message. In forum chats,
+    /// the message is shown in its topic if the topic is loaded, or else in the open topic.
+    pub(crate) fn scroll_to_message(&self, message_id: i64) {
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        spawn(clone!(@weak self as obj => async move {
+            let imp = obj.imp();
+
+            if chat.is_forum() {
+                let message = match chat.fetch_message(message_id).await {
+                    Ok(message) => message,
+                    Err(e) => {
+                        log::warn!("Error fetching a message: {:?}", e);
+                        return;
+                    }
+                };
+
+                // Another chat may have been selected in the meantime
+                if obj.chat().as_ref() != Some(&chat) {
+                    return;
+                }
+
+                match chat.forum_topics().topic_of(&message) {
+                    Some(topic) => imp.chat_history.set_topic(Some(topic)),
+                    None if imp.chat_history.topic().is_none() => return,
+                    None => {}
+                }
+            }
+
+            imp.stack.set_visible_child(&imp.chat_history.get());
+            imp.chat_history.scroll_to_message(message_id).await;
+        }));
+    }
+
+    /// Shows the message thread that contains the replies to the specified message.
+    async fn open_message_thread(&self, chat_id: i64, message_id: i64) {
+        let Some(shown_chat) = self.chat() else {
+            return;
+        };
+
+        let chat = shown_chat.session().chat(chat_id);
+        match chat.get_message_thread(message_id).await {
+            // Another chat may have been selected in the meantime
+            Ok(_) if self.chat() != Some(shown_chat) => {}
+            Ok((thread_chat, message_thread_id)) => {
+                let is_comments =
+                    matches!(chat.type_(), ChatType::Supergroup(data) if data.is_channel());
+
+                let imp = self.imp();
+                imp.thread_history
+                    .set_message_thread(thread_chat, message_thread_id, is_comments);
+                imp.stack.set_visible_child(&imp.thread_history.get());
+            }
+            Err(e) => log::warn!("Failed to get message thread: {:?}", e),
+        }
+    }
+
+    fn close_message_thread(&self) {
+        let imp = self.imp();
+        imp.stack.set_visible_child(&imp.chat_history.get());
+        imp.thread_history.set_chat(None);
+    }
+
+    /// Shows the topics of the current forum chat, so that another topic can be opened.
+    fn show_topics(&self) {
+        let imp = self.imp();
+        imp.forum_topic_picker.reload_topics();
+        imp.stack.set_visible_child(&imp.forum_topic_picker.get());
+    }
 }
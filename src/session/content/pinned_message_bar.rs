@@ -0,0 +1,285 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::SearchMessagesFilter;
+
+use crate::i18n::gettext_f;
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::Message;
+use crate::utils::spawn;
+
+/// The maximum number of pinned messages that are shown.
+const MAX_PINNED_MESSAGES: i32 = 100;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/content-pinned-message-bar.ui")]
+    pub(crate) struct PinnedMessageBar {
+        pub(super) chat: RefCell<Option<Chat>>,
+        pub(super) chat_handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message_thread_id: Cell<i64>,
+        /// The pinned messages, from the newest to the oldest.
+        pub(super) messages: RefCell<Vec<Message>>,
+        /// The index of the pinned message that is currently shown.
+        pub(super) index: Cell<usize>,
+        #[template_child]
+        pub(super) revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) text_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) unpin_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PinnedMessageBar {
+        const NAME: &'static str = "ContentPinnedMessageBar";
+        type Type = super::PinnedMessageBar;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_css_name("pinnedmessagebar");
+
+            klass.install_action("pinned-message-bar.open", None, |widget, _, _| {
+                widget.open_message();
+            });
+            klass.install_action_async(
+                "pinned-message-bar.unpin",
+                None,
+                |widget, _, _| async move {
+                    widget.unpin_message().await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PinnedMessageBar {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Chat>("chat")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecInt64::builder("message-thread-id")
+                        .explicit_notify()
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.set_chat(value.get().unwrap()),
+                "message-thread-id" => obj.set_message_thread_id(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                "message-thread-id" => obj.message_thread_id().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            if let Some(handler_id) = self.chat_handler_id.take() {
+                self.chat.borrow().as_ref().unwrap().disconnect(handler_id);
+            }
+
+            self.revealer.unparent();
+        }
+    }
+
+    impl WidgetImpl for PinnedMessageBar {}
+}
+
+glib::wrapper! {
+    /// A bar that shows the pinned messages of a chat, one at a time. Clicking it jumps to the
+    /// shown message and then cycles to the previous pinned message.
+    pub(crate) struct PinnedMessageBar(ObjectSubclass<imp::PinnedMessageBar>)
+        @extends gtk::Widget;
+}
+
+impl PinnedMessageBar {
+    pub(crate) fn chat(&self) -> Option<Chat> {
+        self.imp().chat.borrow().clone()
+    }
+
+    pub(crate) fn set_chat(&self, chat: Option<Chat>) {
+        if self.chat() == chat {
+            return;
+        }
+
+        let imp = self.imp();
+
+        if let Some(handler_id) = imp.chat_handler_id.take() {
+            self.chat().unwrap().disconnect(handler_id);
+        }
+
+        if let Some(ref chat) = chat {
+            let handler_id =
+                chat.connect_pinned_messages_changed(clone!(@weak self as obj => move |_| {
+                    obj.reload();
+                }));
+            imp.chat_handler_id.replace(Some(handler_id));
+        }
+
+        imp.chat.replace(chat);
+        self.clear();
+        self.reload();
+
+        self.notify("chat");
+    }
+
+    pub(crate) fn message_thread_id(&self) -> i64 {
+        self.imp().message_thread_id.get()
+    }
+
+    pub(crate) fn set_message_thread_id(&self, message_thread_id: i64) {
+        if self.message_thread_id() == message_thread_id {
+            return;
+        }
+
+        self.imp().message_thread_id.set(message_thread_id);
+        self.clear();
+        self.reload();
+
+        self.notify("message-thread-id");
+    }
+
+    fn message(&self) -> Option<Message> {
+        let imp = self.imp();
+        imp.messages.borrow().get(imp.index.get()).cloned()
+    }
+
+    /// Loads the pinned messages of the chat again.
+    fn reload(&self) {
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        let message_thread_id = self.message_thread_id();
+        spawn(clone!(@weak self as obj => async move {
+            let result = chat
+                .search_messages(
+                    String::new(),
+                    None,
+                    0,
+                    0,
+                    MAX_PINNED_MESSAGES,
+                    Some(SearchMessagesFilter::Pinned),
+                    message_thread_id,
+                )
+                .await;
+
+            // The bar may have been switched to another chat in the meantime
+            if obj.chat().as_ref() != Some(&chat) || obj.message_thread_id() != message_thread_id
+            {
+                return;
+            }
+
+            match result {
+                Ok(messages) => obj.set_messages(messages),
+                Err(e) => log::warn!("Error loading the pinned messages: {:?}", e),
+            }
+        }));
+    }
+
+    /// Hides the pinned messages of the previous chat or message thread.
+    fn clear(&self) {
+        self.imp().messages.borrow_mut().clear();
+        self.update();
+    }
+
+    fn set_messages(&self, messages: Vec<Message>) {
+        let imp = self.imp();
+
+        // Keep showing the same message, if it's still pinned
+        let index = self
+            .message()
+            .and_then(|current| messages.iter().position(|m| m == &current))
+            .unwrap_or_default();
+
+        imp.messages.replace(messages);
+        imp.index.set(index);
+
+        self.update();
+    }
+
+    fn update(&self) {
+        let imp = self.imp();
+
+        let Some(message) = self.message() else {
+            imp.revealer.set_reveal_child(false);
+            return;
+        };
+
+        let n_messages = imp.messages.borrow().len();
+        let title = if n_messages > 1 {
+            // The oldest pinned message is the first one
+            gettext_f(
+                "Pinned Message #{number}",
+                &[("number", &(n_messages - imp.index.get()).to_string())],
+            )
+        } else {
+            gettext("Pinned Message")
+        };
+        imp.title_label.set_label(&title);
+        imp.text_label
+            .set_label(&strings::message_content(&message).replace('\n', " "));
+
+        imp.unpin_button
+            .set_visible(message.chat().can_pin_messages());
+        imp.revealer.set_reveal_child(true);
+    }
+
+    /// Jumps to the shown message and moves on to the previous pinned message.
+    fn open_message(&self) {
+        let imp = self.imp();
+
+        let Some(message) = self.message() else {
+            return;
+        };
+
+        self.activate_action(
+            "chat-history.scroll-to-message",
+            Some(&message.id().to_variant()),
+        )
+        .unwrap();
+
+        let n_messages = imp.messages.borrow().len();
+        imp.index.set((imp.index.get() + 1) % n_messages);
+        self.update();
+    }
+
+    async fn unpin_message(&self) {
+        if let Some(message) = self.message() {
+            if let Err(e) = message.toggle_is_pinned().await {
+                log::warn!("Error unpinning a message: {:?}", e);
+            }
+        }
+    }
+}
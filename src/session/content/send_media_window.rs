@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::cell::RefCell;
 
 use adw::subclass::prelude::*;
@@ -17,6 +18,7 @@ mod imp {
     #[template(resource = "/app/drey/paper-plane/ui/content-send-media-window.ui")]
     pub(crate) struct SendMediaWindow {
         pub(super) chat: OnceCell<Chat>,
+        pub(super) message_thread_id: Cell<i64>,
         pub(super) path: OnceCell<String>,
         pub(super) emoji_chooser: RefCell<Option<gtk::EmojiChooser>>,
         #[template_child]
@@ -90,7 +92,12 @@ glib::wrapper! {
 }
 
 impl SendMediaWindow {
-    pub(crate) fn new(parent: &gtk::Window, chat: Chat, path: String) -> Self {
+    pub(crate) fn new(
+        parent: &gtk::Window,
+        chat: Chat,
+        message_thread_id: i64,
+        path: String,
+    ) -> Self {
         let obj: Self = glib::Object::builder()
             .property("transient-for", parent)
             .build();
@@ -100,6 +107,7 @@ impl SendMediaWindow {
         imp.caption_entry.set_chat(Some(chat.clone()));
 
         imp.chat.set(chat).unwrap();
+        imp.message_thread_id.set(message_thread_id);
         imp.path.set(path).unwrap();
 
         obj
@@ -130,7 +138,6 @@ impl SendMediaWindow {
 
         let chat = imp.chat.get().unwrap();
         let chat_id = chat.id();
-        let client_id = chat.session().client_id();
         let path = imp.path.get().unwrap().clone();
 
         let paintable = imp.picture.paintable().unwrap();
@@ -160,7 +167,10 @@ impl SendMediaWindow {
         };
 
         // TODO: maybe show an error dialog when this fails?
-        if tdlib::functions::send_message(chat_id, 0, 0, None, content, client_id)
+        if chat
+            .session()
+            .backend()
+            .send_message(chat_id, imp.message_thread_id.get(), 0, None, content)
             .await
             .is_ok()
         {
@@ -1,12 +1,15 @@
+mod audio_playlist;
 mod contacts_window;
 mod content;
 mod preferences_window;
+mod profile_window;
 mod sidebar;
 
 use std::cell::Cell;
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::collections::hash_map::HashMap;
+use std::rc::Rc;
 
 use adw::subclass::prelude::BinImpl;
 use glib::clone;
@@ -27,15 +30,20 @@ use tdlib::types::ChatPosition as TdChatPosition;
 use tdlib::types::Error as TdError;
 use tdlib::types::File;
 
+pub(crate) use self::audio_playlist::AudioPlaylist;
 use self::contacts_window::ContactsWindow;
 use self::content::Content;
 use self::preferences_window::PreferencesWindow;
+use self::profile_window::ProfileWindow;
 use self::sidebar::Sidebar;
+use crate::backend::Backend;
+use crate::backend::TdlibBackend;
 use crate::session_manager::DatabaseInfo;
 use crate::tdlib::BasicGroup;
 use crate::tdlib::BoxedScopeNotificationSettings;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatList;
+use crate::tdlib::Message;
 use crate::tdlib::SecretChat;
 use crate::tdlib::Supergroup;
 use crate::tdlib::User;
@@ -53,6 +61,8 @@ mod imp {
     #[template(resource = "/app/drey/paper-plane/ui/session.ui")]
     pub(crate) struct Session {
         pub(super) client_id: Cell<i32>,
+        pub(super) my_id: Cell<i64>,
+        pub(super) backend: OnceCell<Rc<dyn Backend>>,
         pub(super) database_info: OnceCell<BoxedDatabaseInfo>,
         pub(super) me: WeakRef<User>,
         pub(super) main_chat_list: OnceCell<ChatList>,
@@ -70,6 +80,7 @@ mod imp {
         pub(super) channel_chats_notification_settings:
             RefCell<Option<BoxedScopeNotificationSettings>>,
         pub(super) downloading_files: RefCell<HashMap<i32, Vec<Sender<File>>>>,
+        pub(super) audio_playlist: AudioPlaylist,
         #[template_child]
         pub(super) split_view: TemplateChild<adw::NavigationSplitView>,
         #[template_child]
@@ -95,6 +106,10 @@ mod imp {
                 let preferences = PreferencesWindow::new(parent_window.as_ref(), widget);
                 preferences.present();
             });
+            klass.install_action("session.edit-profile", None, move |widget, _, _| {
+                let parent_window = widget.root().and_then(|r| r.downcast().ok());
+                ProfileWindow::new(parent_window.as_ref(), widget).present();
+            });
             klass.install_action("session.show-contacts", None, move |widget, _, _| {
                 let parent = widget.root().and_then(|r| r.downcast().ok());
                 let contacts = ContactsWindow::new(parent.as_ref(), widget.clone());
@@ -195,13 +210,30 @@ glib::wrapper! {
 
 impl Session {
     pub(crate) fn new(client_id: i32, database_info: DatabaseInfo) -> Self {
-        glib::Object::builder()
+        Self::with_backend(
+            client_id,
+            database_info,
+            Rc::new(TdlibBackend::new(client_id)),
+        )
+    }
+
+    /// Creates a session that sends its message and file requests through the specified backend.
+    pub(crate) fn with_backend(
+        client_id: i32,
+        database_info: DatabaseInfo,
+        backend: Rc<dyn Backend>,
+    ) -> Self {
+        let obj: Self = glib::Object::builder()
             .property("client-id", client_id)
             .property("database-info", BoxedDatabaseInfo(database_info))
-            .build()
+            .build();
+        obj.imp().backend.set(backend).unwrap();
+        obj
     }
 
     pub(crate) fn handle_update(&self, update: Update) {
+        self.backend().handle_update(&update);
+
         match update {
             Update::NewChat(data) => {
                 // No need to update the chat positions here, tdlib sends
@@ -242,7 +274,10 @@ impl Session {
             Update::DeleteMessages(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::ChatAction(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::MessageContent(ref data) => self.chat(data.chat_id).handle_update(update),
+            Update::MessageContentOpened(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::MessageEdited(ref data) => self.chat(data.chat_id).handle_update(update),
+            Update::ForumTopicInfo(ref data) => self.chat(data.chat_id).handle_update(update),
+            Update::MessageIsPinned(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::MessageInteractionInfo(ref data) => {
                 self.chat(data.chat_id).handle_update(update)
             }
@@ -263,6 +298,13 @@ impl Session {
             Update::File(update) => {
                 self.handle_file_update(update.file);
             }
+            Update::Option(update) => {
+                if let ("my_id", enums::OptionValue::Integer(value)) =
+                    (update.name.as_str(), update.value)
+                {
+                    self.imp().my_id.set(value.value);
+                }
+            }
             Update::ScopeNotificationSettings(update) => {
                 let settings = Some(BoxedScopeNotificationSettings(update.notification_settings));
                 match update.scope {
@@ -340,18 +382,18 @@ impl Session {
         self.try_chat(chat_id).expect("Failed to get expected Chat")
     }
 
+    /// Returns the `User` of the specified id, if present.
+    pub(crate) fn try_user(&self, user_id: i64) -> Option<User> {
+        self.imp().users.borrow().get(&user_id).cloned()
+    }
+
     /// Returns the `User` of the specified id. Panics if the user is not present.
     ///
     /// Note that TDLib guarantees that types are always returned before their ids,
     /// so if you use an id returned by TDLib, it should be expected that the
     /// relative `User` exists in the list.
     pub(crate) fn user(&self, user_id: i64) -> User {
-        self.imp()
-            .users
-            .borrow()
-            .get(&user_id)
-            .expect("Failed to get expected User")
-            .clone()
+        self.try_user(user_id).expect("Failed to get expected User")
     }
 
     /// Returns the `BasicGroup` of the specified id. Panics if the basic group is not present.
@@ -427,11 +469,34 @@ impl Session {
         })
     }
 
+    /// Searches the messages of all chats, from the newest to the oldest. Returns the found
+    /// messages and the offset of the next page, which is empty if there are no more results.
+    pub(crate) async fn search_messages(
+        &self,
+        query: String,
+        offset: String,
+        limit: i32,
+    ) -> Result<(Vec<Message>, String), TdError> {
+        let result = self
+            .backend()
+            .search_messages(None, query, offset, limit, None, 0, 0)
+            .await;
+
+        result.map(|data| {
+            let enums::FoundMessages::FoundMessages(data) = data;
+            let messages = data
+                .messages
+                .into_iter()
+                .map(|message| self.chat(message.chat_id).message_from_td_object(message))
+                .collect();
+            (messages, data.next_offset)
+        })
+    }
+
     /// Downloads a file of the specified id. This will only return when the file
     /// downloading has completed or has failed.
     pub(crate) async fn download_file(&self, file_id: i32) -> Result<File, TdError> {
-        let client_id = self.client_id();
-        let result = functions::download_file(file_id, 5, 0, 0, true, client_id).await;
+        let result = self.backend().download_file(file_id, 5, 0, 0, true).await;
 
         result.map(|data| {
             let tdlib::enums::File::File(file) = data;
@@ -457,9 +522,9 @@ impl Session {
             Entry::Vacant(entry) => {
                 entry.insert(vec![sender]);
 
-                let client_id = self.client_id();
+                let backend = self.backend();
                 spawn(clone!(@weak self as obj => async move {
-                    let result = functions::download_file(file_id, 5, 0, 0, false, client_id).await;
+                    let result = backend.download_file(file_id, 5, 0, 0, false).await;
                     match result {
                         Ok(enums::File::File(file)) => {
                             obj.handle_file_update(file);
@@ -474,9 +539,9 @@ impl Session {
     }
 
     pub(crate) fn cancel_download_file(&self, file_id: i32) {
-        let client_id = self.client_id();
+        let backend = self.backend();
         spawn(async move {
-            if let Err(e) = functions::cancel_download_file(file_id, false, client_id).await {
+            if let Err(e) = backend.cancel_download_file(file_id, false).await {
                 log::warn!("Error canceling a file: {:?}", e);
             }
         });
@@ -494,6 +559,13 @@ impl Session {
         }
     }
 
+    /// Selects the chat of a message and scrolls its history to the message.
+    pub(crate) fn select_message(&self, message: &Message) {
+        let imp = self.imp();
+        imp.sidebar.set_selected_chat(Some(message.chat()));
+        imp.content.scroll_to_message(message.id());
+    }
+
     pub(crate) fn handle_paste_action(&self) {
         self.imp().content.handle_paste_action();
     }
@@ -546,6 +618,21 @@ impl Session {
         self.imp().client_id.get()
     }
 
+    /// Returns the backend through which the message and file requests of this session are sent.
+    pub(crate) fn backend(&self) -> Rc<dyn Backend> {
+        self.imp().backend.get().unwrap().clone()
+    }
+
+    /// Returns the playlist through which the audio messages of this session are played.
+    pub(crate) fn audio_playlist(&self) -> &AudioPlaylist {
+        &self.imp().audio_playlist
+    }
+
+    /// Returns the id of the logged in user, as announced by the `my_id` option.
+    pub(crate) fn my_id(&self) -> i64 {
+        self.imp().my_id.get()
+    }
+
     pub(crate) fn database_info(&self) -> &BoxedDatabaseInfo {
         self.imp().database_info.get().unwrap()
     }
@@ -622,8 +709,7 @@ impl Session {
     }
 
     pub(crate) fn fetch_chats(&self) {
-        let client_id = self.imp().client_id.get();
-        self.main_chat_list().fetch(client_id);
+        self.main_chat_list().fetch(self.backend());
     }
 
     pub(crate) fn set_sessions(&self, sessions: gtk::SelectionModel) {
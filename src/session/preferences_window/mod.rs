@@ -1,3 +1,7 @@
+mod privacy_page;
+mod privacy_setting_row;
+mod sessions_page;
+
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gettextrs::gettext;
@@ -8,6 +12,8 @@ use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use once_cell::unsync::OnceCell;
 
+use self::privacy_page::PrivacyPage;
+use self::sessions_page::SessionsPage;
 use crate::config::APP_ID;
 use crate::utils::spawn;
 use crate::Session;
@@ -94,6 +100,8 @@ mod imp {
             }
 
             obj.setup_bindings();
+            obj.add(&PrivacyPage::new(obj.session()));
+            obj.add(&SessionsPage::new(obj.session()));
 
             spawn(clone!(@weak obj => async move {
                 obj.calculate_cache_size().await;
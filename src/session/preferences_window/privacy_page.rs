@@ -0,0 +1,262 @@
+use std::cell::Cell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums;
+use tdlib::enums::UserPrivacySetting;
+use tdlib::functions;
+
+use super::privacy_setting_row::PrivacySettingRow;
+use crate::components::Avatar;
+use crate::strings;
+use crate::tdlib::MessageSender;
+use crate::utils::spawn;
+use crate::Session;
+
+/// The number of blocked users that are fetched at once.
+const BLOCKED_USERS_LIMIT: i32 = 100;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/preferences-privacy-page.ui")]
+    pub(crate) struct PrivacyPage {
+        pub(super) session: OnceCell<Session>,
+        pub(super) n_blocked_users: Cell<u32>,
+        #[template_child]
+        pub(super) privacy_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) blocked_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) spinner: TemplateChild<gtk::Spinner>,
+        #[template_child]
+        pub(super) empty_row: TemplateChild<adw::ActionRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PrivacyPage {
+        const NAME: &'static str = "PreferencesPrivacyPage";
+        type Type = super::PrivacyPage;
+        type ParentType = adw::PreferencesPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PrivacyPage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Session>("session")
+                    .construct_only()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.session.set(value.get().unwrap()).unwrap(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => obj.session().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            obj.setup_privacy_rows();
+
+            spawn(clone!(@weak obj => async move {
+                obj.load_blocked_users().await;
+            }));
+        }
+    }
+
+    impl WidgetImpl for PrivacyPage {}
+    impl PreferencesPageImpl for PrivacyPage {}
+}
+
+glib::wrapper! {
+    /// A page of the preferences with the privacy settings of the user and the users they have
+    /// blocked.
+    pub(crate) struct PrivacyPage(ObjectSubclass<imp::PrivacyPage>)
+        @extends gtk::Widget, adw::PreferencesPage;
+}
+
+impl PrivacyPage {
+    pub(crate) fn new(session: &Session) -> Self {
+        glib::Object::builder().property("session", session).build()
+    }
+
+    fn setup_privacy_rows(&self) {
+        let settings = [
+            (
+                UserPrivacySetting::ShowStatus,
+                gettext("Last Seen & Online"),
+                gettext("Who can see my last seen time"),
+            ),
+            (
+                UserPrivacySetting::ShowProfilePhoto,
+                gettext("Profile Photo"),
+                gettext("Who can see my profile photos"),
+            ),
+            (
+                UserPrivacySetting::ShowPhoneNumber,
+                gettext("Phone Number"),
+                gettext("Who can see my phone number"),
+            ),
+            (
+                UserPrivacySetting::ShowLinkInForwardedMessages,
+                gettext("Forwarded Messages"),
+                gettext("Who can link to my account when forwarding my messages"),
+            ),
+            (
+                UserPrivacySetting::AllowCalls,
+                gettext("Calls"),
+                gettext("Who can call me"),
+            ),
+            (
+                UserPrivacySetting::AllowChatInvites,
+                gettext("Group Invites"),
+                gettext("Who can add me to groups and channels"),
+            ),
+        ];
+
+        let imp = self.imp();
+        for (setting, title, visibility_title) in settings {
+            imp.privacy_group.add(&PrivacySettingRow::new(
+                self.session(),
+                setting,
+                &title,
+                &visibility_title,
+            ));
+        }
+    }
+
+    async fn load_blocked_users(&self) {
+        let imp = self.imp();
+        let session = self.session();
+        let mut offset = 0;
+
+        loop {
+            let result = functions::get_blocked_message_senders(
+                offset,
+                BLOCKED_USERS_LIMIT,
+                session.client_id(),
+            )
+            .await;
+
+            match result {
+                Ok(enums::MessageSenders::MessageSenders(data)) => {
+                    let n_received = data.senders.len() as i32;
+                    for sender in &data.senders {
+                        let sender = MessageSender::from_td_object(sender, session);
+                        imp.blocked_group.add(&self.blocked_user_row(&sender));
+                        imp.n_blocked_users.set(imp.n_blocked_users.get() + 1);
+                    }
+
+                    offset += n_received;
+                    if n_received == 0 || offset >= data.total_count {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Error getting the blocked users: {:?}", e);
+                    break;
+                }
+            }
+        }
+
+        imp.spinner.set_visible(false);
+        self.update_empty_row();
+    }
+
+    fn blocked_user_row(&self, sender: &MessageSender) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&strings::message_sender(
+                sender, true,
+            )))
+            .build();
+
+        let avatar = Avatar::new();
+        avatar.set_size(32);
+        avatar.set_item(Some(match sender {
+            MessageSender::User(user) => user.clone().upcast(),
+            MessageSender::Chat(chat) => chat.clone().upcast(),
+        }));
+        row.add_prefix(&avatar);
+
+        if let MessageSender::User(user) = sender {
+            let username = user.username();
+            if !username.is_empty() {
+                row.set_subtitle(&format!("@{username}"));
+            }
+        }
+
+        let button = gtk::Button::builder()
+            .label(gettext("Unblock"))
+            .valign(gtk::Align::Center)
+            .build();
+        button.connect_clicked(
+            clone!(@weak self as obj, @weak row, @strong sender => move |button| {
+                button.set_sensitive(false);
+                spawn(clone!(@weak obj, @weak row, @weak button, @strong sender => async move {
+                    obj.unblock(&sender, &row, &button).await;
+                }));
+            }),
+        );
+        row.add_suffix(&button);
+
+        row
+    }
+
+    async fn unblock(&self, sender: &MessageSender, row: &adw::ActionRow, button: &gtk::Button) {
+        let client_id = self.session().client_id();
+        match functions::toggle_message_sender_is_blocked(sender.to_td_object(), false, client_id)
+            .await
+        {
+            Ok(_) => {
+                let imp = self.imp();
+                imp.blocked_group.remove(row);
+                imp.n_blocked_users.set(imp.n_blocked_users.get() - 1);
+                self.update_empty_row();
+            }
+            Err(e) => {
+                log::warn!("Error unblocking a user: {:?}", e);
+                button.set_sensitive(true);
+            }
+        }
+    }
+
+    /// Shows a placeholder row if there are no blocked users.
+    fn update_empty_row(&self) {
+        let imp = self.imp();
+        imp.empty_row.set_visible(imp.n_blocked_users.get() == 0);
+    }
+
+    pub(crate) fn session(&self) -> &Session {
+        self.imp().session.get().unwrap()
+    }
+}
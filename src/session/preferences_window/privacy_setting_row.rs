@@ -0,0 +1,404 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use once_cell::unsync::OnceCell;
+use tdlib::enums;
+use tdlib::enums::UserPrivacySetting;
+use tdlib::enums::UserPrivacySettingRule;
+use tdlib::functions;
+use tdlib::types;
+
+use crate::components::Avatar;
+use crate::i18n::ngettext_f;
+use crate::session::contacts_window::ContactsWindow;
+use crate::strings;
+use crate::utils::spawn;
+use crate::Session;
+
+/// Who is allowed by a privacy setting, apart from the exceptions. The values are in the same
+/// order as in the combo row.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum Visibility {
+    #[default]
+    Everybody,
+    Contacts,
+    Nobody,
+}
+
+impl Visibility {
+    fn from_position(position: u32) -> Self {
+        match position {
+            0 => Self::Everybody,
+            1 => Self::Contacts,
+            _ => Self::Nobody,
+        }
+    }
+
+    fn position(self) -> u32 {
+        match self {
+            Self::Everybody => 0,
+            Self::Contacts => 1,
+            Self::Nobody => 2,
+        }
+    }
+
+    fn title(self) -> String {
+        match self {
+            Self::Everybody => gettext("Everybody"),
+            Self::Contacts => gettext("My Contacts"),
+            Self::Nobody => gettext("Nobody"),
+        }
+    }
+
+    fn rule(self) -> UserPrivacySettingRule {
+        match self {
+            Self::Everybody => UserPrivacySettingRule::AllowAll,
+            Self::Contacts => UserPrivacySettingRule::AllowContacts,
+            Self::Nobody => UserPrivacySettingRule::RestrictAll,
+        }
+    }
+}
+
+/// The rules of a privacy setting, split in the parts that can be edited.
+#[derive(Debug, Default, Clone)]
+struct Rules {
+    visibility: Visibility,
+    allowed_users: Vec<i64>,
+    restricted_users: Vec<i64>,
+    /// The rules about the members of chats, which can't be edited but must be kept.
+    chat_member_rules: Vec<UserPrivacySettingRule>,
+}
+
+impl Rules {
+    fn from_td_object(rules: Vec<UserPrivacySettingRule>) -> Self {
+        let mut result = Self {
+            // Nobody is allowed if no rule matches
+            visibility: Visibility::Nobody,
+            ..Default::default()
+        };
+
+        // The rules are applied in order, so only the first general rule matters
+        let mut has_visibility = false;
+        for rule in rules {
+            match rule {
+                UserPrivacySettingRule::AllowAll if !has_visibility => {
+                    result.visibility = Visibility::Everybody;
+                    has_visibility = true;
+                }
+                UserPrivacySettingRule::AllowContacts if !has_visibility => {
+                    result.visibility = Visibility::Contacts;
+                    has_visibility = true;
+                }
+                UserPrivacySettingRule::RestrictAll if !has_visibility => {
+                    result.visibility = Visibility::Nobody;
+                    has_visibility = true;
+                }
+                UserPrivacySettingRule::AllowUsers(data) => {
+                    result.allowed_users.extend(data.user_ids)
+                }
+                UserPrivacySettingRule::RestrictUsers(data) => {
+                    result.restricted_users.extend(data.user_ids)
+                }
+                rule @ (UserPrivacySettingRule::AllowChatMembers(_)
+                | UserPrivacySettingRule::RestrictChatMembers(_)) => {
+                    result.chat_member_rules.push(rule)
+                }
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    fn to_td_object(&self) -> types::UserPrivacySettingRules {
+        let mut rules = Vec::new();
+
+        // Exceptions must come before the general rule to be applied
+        if !self.restricted_users.is_empty() && self.visibility != Visibility::Nobody {
+            rules.push(UserPrivacySettingRule::RestrictUsers(
+                types::UserPrivacySettingRuleRestrictUsers {
+                    user_ids: self.restricted_users.clone(),
+                },
+            ));
+        }
+        if !self.allowed_users.is_empty() && self.visibility != Visibility::Everybody {
+            rules.push(UserPrivacySettingRule::AllowUsers(
+                types::UserPrivacySettingRuleAllowUsers {
+                    user_ids: self.allowed_users.clone(),
+                },
+            ));
+        }
+        rules.extend(self.chat_member_rules.iter().cloned());
+        rules.push(self.visibility.rule());
+
+        types::UserPrivacySettingRules { rules }
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct PrivacySettingRow {
+        pub(super) session: OnceCell<Session>,
+        pub(super) setting: OnceCell<UserPrivacySetting>,
+        pub(super) rules: RefCell<Rules>,
+        /// Whether the rules were loaded, as saving the default rules would wipe the exceptions.
+        pub(super) is_loaded: Cell<bool>,
+        /// Whether the rules are being shown, so that changing the combo row doesn't save them.
+        pub(super) is_updating: Cell<bool>,
+        pub(super) visibility_row: OnceCell<adw::ComboRow>,
+        /// The rows of the exceptions, which are recreated whenever they change.
+        pub(super) exception_rows: RefCell<Vec<gtk::Widget>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PrivacySettingRow {
+        const NAME: &'static str = "PreferencesPrivacySettingRow";
+        type Type = super::PrivacySettingRow;
+        type ParentType = adw::ExpanderRow;
+    }
+
+    impl ObjectImpl for PrivacySettingRow {}
+    impl WidgetImpl for PrivacySettingRow {}
+    impl ListBoxRowImpl for PrivacySettingRow {}
+    impl PreferencesRowImpl for PrivacySettingRow {}
+    impl ExpanderRowImpl for PrivacySettingRow {}
+}
+
+glib::wrapper! {
+    /// A row to choose who is allowed by a privacy setting of the user, with the users that are
+    /// exceptions to it. Changes are saved immediately.
+    pub(crate) struct PrivacySettingRow(ObjectSubclass<imp::PrivacySettingRow>)
+        @extends gtk::Widget, gtk::ListBoxRow, adw::PreferencesRow, adw::ExpanderRow;
+}
+
+impl PrivacySettingRow {
+    pub(crate) fn new(
+        session: &Session,
+        setting: UserPrivacySetting,
+        title: &str,
+        visibility_title: &str,
+    ) -> Self {
+        // The titles are translated, so they can't be trusted to be valid markup
+        let obj: Self = glib::Object::builder()
+            .property("title", title)
+            .property("use-markup", false)
+            // The row is insensitive until the rules are loaded
+            .property("sensitive", false)
+            .build();
+        let imp = obj.imp();
+
+        let visibility_row = adw::ComboRow::builder()
+            .title(visibility_title)
+            .use_markup(false)
+            .model(&gtk::StringList::new(&[
+                &Visibility::Everybody.title(),
+                &Visibility::Contacts.title(),
+                &Visibility::Nobody.title(),
+            ]))
+            .build();
+        visibility_row.connect_selected_notify(clone!(@weak obj => move |row| {
+            if !obj.imp().is_updating.get() {
+                obj.imp().rules.borrow_mut().visibility = Visibility::from_position(row.selected());
+                obj.update_rows();
+                obj.save();
+            }
+        }));
+        obj.add_row(&visibility_row);
+
+        imp.visibility_row.set(visibility_row).unwrap();
+        imp.setting.set(setting).unwrap();
+        imp.session.set(session.clone()).unwrap();
+
+        spawn(clone!(@weak obj => async move {
+            obj.load().await;
+        }));
+
+        obj
+    }
+
+    fn session(&self) -> &Session {
+        self.imp().session.get().unwrap()
+    }
+
+    async fn load(&self) {
+        let imp = self.imp();
+        let setting = imp.setting.get().unwrap().clone();
+
+        match functions::get_user_privacy_setting_rules(setting, self.session().client_id()).await {
+            Ok(enums::UserPrivacySettingRules::UserPrivacySettingRules(data)) => {
+                imp.rules.replace(Rules::from_td_object(data.rules));
+                imp.is_loaded.set(true);
+                self.update_rows();
+                self.set_sensitive(true);
+            }
+            Err(e) => log::warn!("Error getting the privacy setting rules: {:?}", e),
+        }
+    }
+
+    fn save(&self) {
+        let imp = self.imp();
+        if !imp.is_loaded.get() {
+            return;
+        }
+
+        let setting = imp.setting.get().unwrap().clone();
+        let rules = imp.rules.borrow().to_td_object();
+        let client_id = self.session().client_id();
+
+        spawn(clone!(@weak self as obj => async move {
+            if let Err(e) = functions::set_user_privacy_setting_rules(setting, rules, client_id).await {
+                log::warn!("Error setting the privacy setting rules: {:?}", e);
+                // Show the rules that are still in effect
+                obj.load().await;
+            }
+        }));
+    }
+
+    /// Shows the current rules in the subtitle, the combo row and the rows of the exceptions.
+    fn update_rows(&self) {
+        let imp = self.imp();
+        let rules = imp.rules.borrow().clone();
+
+        imp.is_updating.set(true);
+        imp.visibility_row
+            .get()
+            .unwrap()
+            .set_selected(rules.visibility.position());
+        imp.is_updating.set(false);
+
+        let mut subtitle = rules.visibility.title();
+        let n_exceptions = match rules.visibility {
+            Visibility::Everybody => rules.restricted_users.len(),
+            Visibility::Contacts => rules.restricted_users.len() + rules.allowed_users.len(),
+            Visibility::Nobody => rules.allowed_users.len(),
+        };
+        if n_exceptions > 0 {
+            subtitle.push_str(" · ");
+            subtitle.push_str(&ngettext_f(
+                "{num} exception",
+                "{num} exceptions",
+                n_exceptions as u32,
+                &[("num", &n_exceptions.to_string())],
+            ));
+        }
+        self.set_subtitle(&subtitle);
+
+        for row in imp.exception_rows.take() {
+            self.remove(&row);
+        }
+
+        let mut rows = Vec::new();
+        if rules.visibility != Visibility::Everybody {
+            rows.push(self.exceptions_header_row(true));
+            for user_id in &rules.allowed_users {
+                rows.push(self.exception_row(*user_id, true));
+            }
+        }
+        if rules.visibility != Visibility::Nobody {
+            rows.push(self.exceptions_header_row(false));
+            for user_id in &rules.restricted_users {
+                rows.push(self.exception_row(*user_id, false));
+            }
+        }
+
+        for row in &rows {
+            self.add_row(row);
+        }
+        imp.exception_rows.replace(rows);
+    }
+
+    fn exceptions_header_row(&self, is_allowed: bool) -> gtk::Widget {
+        let title = if is_allowed {
+            gettext("Always Allow")
+        } else {
+            gettext("Never Allow")
+        };
+        let row = adw::ActionRow::builder()
+            .title(&title)
+            .use_markup(false)
+            .build();
+
+        let button = gtk::Button::builder()
+            .icon_name("list-add-symbolic")
+            .tooltip_text(gettext("Add User"))
+            .valign(gtk::Align::Center)
+            .build();
+        button.add_css_class("flat");
+        button.connect_clicked(clone!(@weak self as obj => move |_| {
+            let parent = obj.root().and_then(|root| root.downcast().ok());
+            let picker = ContactsWindow::new_picker(parent.as_ref(), obj.session().clone(), &title);
+            picker.connect_contact_activated(clone!(@weak obj => move |_, user_id| {
+                obj.add_exception(user_id, is_allowed);
+            }));
+            picker.present();
+        }));
+        row.add_suffix(&button);
+
+        row.upcast()
+    }
+
+    fn exception_row(&self, user_id: i64, is_allowed: bool) -> gtk::Widget {
+        let user = self.session().user(user_id);
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&strings::user_display_name(
+                &user, true,
+            )))
+            .build();
+
+        let avatar = Avatar::new();
+        avatar.set_size(32);
+        avatar.set_item(Some(user.upcast()));
+        row.add_prefix(&avatar);
+
+        let button = gtk::Button::builder()
+            .icon_name("list-remove-symbolic")
+            .tooltip_text(gettext("Remove"))
+            .valign(gtk::Align::Center)
+            .build();
+        button.add_css_class("flat");
+        button.connect_clicked(clone!(@weak self as obj => move |_| {
+            obj.remove_exception(user_id, is_allowed);
+        }));
+        row.add_suffix(&button);
+
+        row.upcast()
+    }
+
+    fn add_exception(&self, user_id: i64, is_allowed: bool) {
+        {
+            let mut rules = self.imp().rules.borrow_mut();
+            // A user can't be an exception in both directions
+            rules.allowed_users.retain(|id| *id != user_id);
+            rules.restricted_users.retain(|id| *id != user_id);
+            if is_allowed {
+                rules.allowed_users.push(user_id);
+            } else {
+                rules.restricted_users.push(user_id);
+            }
+        }
+
+        self.update_rows();
+        self.save();
+    }
+
+    fn remove_exception(&self, user_id: i64, is_allowed: bool) {
+        {
+            let mut rules = self.imp().rules.borrow_mut();
+            if is_allowed {
+                rules.allowed_users.retain(|id| *id != user_id);
+            } else {
+                rules.restricted_users.retain(|id| *id != user_id);
+            }
+        }
+
+        self.update_rows();
+        self.save();
+    }
+}
@@ -0,0 +1,355 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums;
+use tdlib::functions;
+use tdlib::types;
+
+use crate::i18n::gettext_f;
+use crate::utils::spawn;
+use crate::Session;
+
+/// The choices for the number of days after which inactive sessions are terminated, in the same
+/// order as in the combo row.
+const INACTIVE_TTL_DAYS: [i32; 5] = [7, 30, 90, 180, 365];
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/preferences-sessions-page.ui")]
+    pub(crate) struct SessionsPage {
+        pub(super) session: OnceCell<Session>,
+        /// The rows of the sessions, which are recreated whenever the sessions are loaded.
+        pub(super) session_rows: RefCell<Vec<(adw::PreferencesGroup, adw::ActionRow)>>,
+        /// Whether the loaded setting is being shown, so that changing the combo row doesn't
+        /// save it.
+        pub(super) is_updating: Cell<bool>,
+        #[template_child]
+        pub(super) current_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) spinner: TemplateChild<gtk::Spinner>,
+        #[template_child]
+        pub(super) other_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) inactive_ttl_row: TemplateChild<adw::ComboRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SessionsPage {
+        const NAME: &'static str = "PreferencesSessionsPage";
+        type Type = super::SessionsPage;
+        type ParentType = adw::PreferencesPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+
+            klass.install_action_async(
+                "sessions-page.terminate",
+                Some("x"),
+                |widget, _, data| async move {
+                    let session_id = data.and_then(|data| data.get()).unwrap();
+                    widget.terminate_session(session_id).await;
+                },
+            );
+            klass.install_action_async(
+                "sessions-page.terminate-all",
+                None,
+                |widget, _, _| async move {
+                    widget.terminate_all_other_sessions().await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SessionsPage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Session>("session")
+                    .construct_only()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.session.set(value.get().unwrap()).unwrap(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => obj.session().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().reload();
+        }
+    }
+
+    impl WidgetImpl for SessionsPage {}
+    impl PreferencesPageImpl for SessionsPage {}
+
+    #[gtk::template_callbacks]
+    impl SessionsPage {
+        #[template_callback]
+        fn on_inactive_ttl_selected(&self) {
+            // The row is insensitive until the setting is loaded
+            if self.inactive_ttl_row.is_sensitive() && !self.is_updating.get() {
+                self.obj().save_inactive_ttl();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A page of the preferences with the devices that are logged in to the account of the user.
+    pub(crate) struct SessionsPage(ObjectSubclass<imp::SessionsPage>)
+        @extends gtk::Widget, adw::PreferencesPage;
+}
+
+impl SessionsPage {
+    pub(crate) fn new(session: &Session) -> Self {
+        glib::Object::builder().property("session", session).build()
+    }
+
+    fn reload(&self) {
+        self.imp().spinner.set_visible(true);
+
+        spawn(clone!(@weak self as obj => async move {
+            obj.load_sessions().await;
+        }));
+    }
+
+    async fn load_sessions(&self) {
+        let imp = self.imp();
+        let result = functions::get_active_sessions(self.session().client_id()).await;
+
+        imp.spinner.set_visible(false);
+
+        match result {
+            Ok(enums::Sessions::Sessions(data)) => {
+                for (group, row) in imp.session_rows.take() {
+                    group.remove(&row);
+                }
+
+                let mut rows = Vec::new();
+                for session in &data.sessions {
+                    let group = if session.is_current {
+                        imp.current_group.get()
+                    } else {
+                        imp.other_group.get()
+                    };
+                    let row = session_row(session);
+                    group.add(&row);
+                    rows.push((group, row));
+                }
+                imp.other_group
+                    .set_visible(data.sessions.iter().any(|session| !session.is_current));
+                imp.session_rows.replace(rows);
+
+                // Select the closest choice, as other clients may set any number of days
+                let ttl = data.inactive_session_ttl_days;
+                let position = (0..INACTIVE_TTL_DAYS.len())
+                    .min_by_key(|i| (INACTIVE_TTL_DAYS[*i] - ttl).abs())
+                    .unwrap();
+                imp.is_updating.set(true);
+                imp.inactive_ttl_row.set_selected(position as u32);
+                imp.inactive_ttl_row.set_sensitive(true);
+                imp.is_updating.set(false);
+            }
+            Err(e) => log::warn!("Error getting the active sessions: {:?}", e),
+        }
+    }
+
+    fn save_inactive_ttl(&self) {
+        let days = INACTIVE_TTL_DAYS[self.imp().inactive_ttl_row.selected() as usize];
+        let client_id = self.session().client_id();
+
+        spawn(async move {
+            if let Err(e) = functions::set_inactive_session_ttl(days, client_id).await {
+                log::warn!("Error setting the inactive session TTL: {:?}", e);
+            }
+        });
+    }
+
+    async fn terminate_session(&self, session_id: i64) {
+        let confirmed = self
+            .confirm(
+                &gettext("Terminate Session"),
+                &gettext("Do you want to terminate this session? The device will be logged out."),
+            )
+            .await;
+        if !confirmed {
+            return;
+        }
+
+        match functions::terminate_session(session_id, self.session().client_id()).await {
+            Ok(_) => self.reload(),
+            Err(e) => log::warn!("Error terminating a session: {:?}", e),
+        }
+    }
+
+    async fn terminate_all_other_sessions(&self) {
+        let confirmed = self
+            .confirm(
+                &gettext("Terminate All Other Sessions"),
+                &gettext(
+                    "Do you want to terminate all the sessions except this one? All the other \
+                     devices will be logged out.",
+                ),
+            )
+            .await;
+        if !confirmed {
+            return;
+        }
+
+        match functions::terminate_all_other_sessions(self.session().client_id()).await {
+            Ok(_) => self.reload(),
+            Err(e) => log::warn!("Error terminating the other sessions: {:?}", e),
+        }
+    }
+
+    /// Asks the user to confirm terminating sessions. Returns whether they confirmed.
+    async fn confirm(&self, heading: &str, body: &str) -> bool {
+        let dialog = adw::MessageDialog::builder()
+            .heading(heading)
+            .body(body)
+            .transient_for(&self.parent_window())
+            .build();
+
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("terminate", &gettext("_Terminate")),
+        ]);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+        dialog.set_response_appearance("terminate", adw::ResponseAppearance::Destructive);
+
+        dialog.choose_future().await == "terminate"
+    }
+
+    fn parent_window(&self) -> gtk::Window {
+        self.root().and_then(|root| root.downcast().ok()).unwrap()
+    }
+
+    pub(crate) fn session(&self) -> &Session {
+        self.imp().session.get().unwrap()
+    }
+}
+
+fn session_row(session: &types::Session) -> adw::ActionRow {
+    let device = [session.device_model.as_str(), platform(session).as_str()]
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let location = [session.region.as_str(), session.country.as_str()]
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut details = vec![format!(
+        "{} {}",
+        session.application_name, session.application_version
+    )];
+    if !location.is_empty() {
+        details.push(location);
+    }
+    if !session.is_current {
+        details.push(last_active(session.last_active_date));
+    }
+
+    let row = adw::ActionRow::builder()
+        .title(glib::markup_escape_text(&device))
+        .subtitle(glib::markup_escape_text(&details.join(" · ")))
+        .subtitle_lines(2)
+        .build();
+
+    let icon = gtk::Image::from_icon_name(session_icon_name(&session.r#type));
+    row.add_prefix(&icon);
+
+    if session.is_password_pending {
+        let label = gtk::Label::builder()
+            .label(gettext("Unconfirmed"))
+            .valign(gtk::Align::Center)
+            .build();
+        label.add_css_class("dim-label");
+        label.add_css_class("caption");
+        row.add_suffix(&label);
+    }
+
+    if !session.is_current {
+        let button = gtk::Button::builder()
+            .icon_name("window-close-symbolic")
+            .tooltip_text(gettext("Terminate Session"))
+            .action_name("sessions-page.terminate")
+            .action_target(&session.id.to_variant())
+            .valign(gtk::Align::Center)
+            .build();
+        button.add_css_class("flat");
+        row.add_suffix(&button);
+    }
+
+    row
+}
+
+fn platform(session: &types::Session) -> String {
+    if session.system_version.is_empty() {
+        session.platform.clone()
+    } else {
+        format!("{} {}", session.platform, session.system_version)
+    }
+}
+
+fn session_icon_name(type_: &enums::SessionType) -> &'static str {
+    use enums::SessionType::*;
+
+    match type_ {
+        Android | Iphone | Ipad => "phone-symbolic",
+        Brave | Chrome | Edge | Firefox | Opera | Safari | Vivaldi => "web-browser-symbolic",
+        Xbox => "input-gaming-symbolic",
+        _ => "computer-symbolic",
+    }
+}
+
+fn last_active(date: i32) -> String {
+    let now = glib::DateTime::now_local().unwrap();
+    let last_active = glib::DateTime::from_unix_local(date as i64).unwrap();
+
+    let format = if now.ymd() == last_active.ymd() {
+        // Translators: This is a time format, without seconds
+        // Here you may want to change to a 24-hours representation, based on your locale.
+        // You can use this site to learn more: https://www.strfti.me/
+        gettext("%l:%M %p")
+    } else {
+        // Translators: This is a date format
+        gettext("%x")
+    };
+
+    gettext_f(
+        "Last activity: {date}",
+        &[("date", last_active.format(&format).unwrap().trim())],
+    )
+}
@@ -0,0 +1,253 @@
+use std::cell::Cell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use glib::clone;
+use glib::subclass::Signal;
+use gtk::cairo;
+use gtk::gdk;
+use gtk::glib;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+
+use crate::utils::temp_dir;
+
+/// The size of the side of the cropped photo, if the crop is larger than this.
+const MAX_PHOTO_SIZE: u32 = 1280;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/profile-crop-window.ui")]
+    pub(crate) struct CropWindow {
+        pub(super) path: OnceCell<String>,
+        /// The size of the image, in pixels.
+        pub(super) image_size: Cell<(f64, f64)>,
+        /// The center of the crop, in pixels of the image.
+        pub(super) center: Cell<(f64, f64)>,
+        /// The center of the crop when the current drag started.
+        pub(super) drag_start_center: Cell<(f64, f64)>,
+        #[template_child]
+        pub(super) picture: TemplateChild<gtk::Picture>,
+        #[template_child]
+        pub(super) crop_area: TemplateChild<gtk::DrawingArea>,
+        #[template_child]
+        pub(super) size_scale: TemplateChild<gtk::Scale>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CropWindow {
+        const NAME: &'static str = "ProfileCropWindow";
+        type Type = super::CropWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+
+            klass.install_action("crop-window.done", None, |widget, _, _| {
+                widget.crop();
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for CropWindow {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("cropped")
+                    .param_types([String::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            self.crop_area.set_cursor_from_name(Some("move"));
+            self.crop_area
+                .set_draw_func(clone!(@weak obj => move |_, cr, width, height| {
+                    obj.draw_crop(cr, width as f64, height as f64);
+                }));
+
+            let gesture = gtk::GestureDrag::new();
+            gesture.connect_drag_begin(clone!(@weak obj => move |_, _, _| {
+                let imp = obj.imp();
+                imp.drag_start_center.set(imp.center.get());
+            }));
+            gesture.connect_drag_update(clone!(@weak obj => move |_, offset_x, offset_y| {
+                let imp = obj.imp();
+                let (_, _, scale) = obj.image_rect();
+                if scale > 0.0 {
+                    let (x, y) = imp.drag_start_center.get();
+                    obj.set_center(x + offset_x / scale, y + offset_y / scale);
+                }
+            }));
+            self.crop_area.add_controller(gesture);
+        }
+    }
+
+    impl WidgetImpl for CropWindow {}
+    impl WindowImpl for CropWindow {}
+    impl AdwWindowImpl for CropWindow {}
+
+    #[gtk::template_callbacks]
+    impl CropWindow {
+        #[template_callback]
+        fn on_size_changed(&self) {
+            let (x, y) = self.center.get();
+            self.obj().set_center(x, y);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A window to choose the square part of an image that is used as a profile photo.
+    pub(crate) struct CropWindow(ObjectSubclass<imp::CropWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl CropWindow {
+    pub(crate) fn new(parent: &impl IsA<gtk::Window>, path: String) -> Result<Self, glib::Error> {
+        let texture = gdk::Texture::from_filename(&path)?;
+
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        let imp = obj.imp();
+
+        let (width, height) = (texture.width() as f64, texture.height() as f64);
+        imp.image_size.set((width, height));
+        imp.center.set((width / 2.0, height / 2.0));
+        imp.picture.set_paintable(Some(&texture));
+        imp.path.set(path).unwrap();
+
+        Ok(obj)
+    }
+
+    pub(crate) fn connect_cropped<F: Fn(&Self, String) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("cropped", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let path = values[1].get::<String>().unwrap();
+            f(&obj, path);
+            None
+        })
+    }
+
+    /// Returns the side of the crop, in pixels of the image.
+    fn crop_size(&self) -> f64 {
+        let imp = self.imp();
+        let (width, height) = imp.image_size.get();
+        width.min(height) * imp.size_scale.value()
+    }
+
+    /// Moves the center of the crop, keeping the crop inside of the image.
+    fn set_center(&self, x: f64, y: f64) {
+        let imp = self.imp();
+        let (width, height) = imp.image_size.get();
+        let half = self.crop_size() / 2.0;
+
+        imp.center
+            .set((x.clamp(half, width - half), y.clamp(half, height - half)));
+        imp.crop_area.queue_draw();
+    }
+
+    /// Returns the position of the image inside of the crop area and the scale at which it's
+    /// shown.
+    fn image_rect(&self) -> (f64, f64, f64) {
+        let imp = self.imp();
+        let (width, height) = imp.image_size.get();
+        let area_width = imp.crop_area.width() as f64;
+        let area_height = imp.crop_area.height() as f64;
+
+        let scale = (area_width / width).min(area_height / height);
+        (
+            (area_width - width * scale) / 2.0,
+            (area_height - height * scale) / 2.0,
+            scale,
+        )
+    }
+
+    /// Dims the parts of the image that are outside of the crop, which is round like an avatar.
+    fn draw_crop(&self, cr: &cairo::Context, width: f64, height: f64) {
+        let (x0, y0, scale) = self.image_rect();
+        let (center_x, center_y) = self.imp().center.get();
+        let radius = self.crop_size() * scale / 2.0;
+        let x = x0 + center_x * scale;
+        let y = y0 + center_y * scale;
+
+        cr.set_fill_rule(cairo::FillRule::EvenOdd);
+        cr.rectangle(0.0, 0.0, width, height);
+        cr.new_sub_path();
+        cr.arc(x, y, radius, 0.0, 2.0 * std::f64::consts::PI);
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.5);
+        if let Err(e) = cr.fill() {
+            log::warn!("Error drawing the photo crop: {:?}", e);
+        }
+
+        cr.arc(x, y, radius, 0.0, 2.0 * std::f64::consts::PI);
+        cr.set_source_rgba(1.0, 1.0, 1.0, 0.8);
+        cr.set_line_width(2.0);
+        if let Err(e) = cr.stroke() {
+            log::warn!("Error drawing the photo crop: {:?}", e);
+        }
+    }
+
+    fn crop(&self) {
+        let imp = self.imp();
+        let (center_x, center_y) = imp.center.get();
+        let size = self.crop_size();
+
+        let result = crop_image(
+            imp.path.get().unwrap(),
+            (center_x - size / 2.0).max(0.0) as u32,
+            (center_y - size / 2.0).max(0.0) as u32,
+            size as u32,
+        );
+
+        match result {
+            Ok(path) => {
+                self.emit_by_name::<()>("cropped", &[&path]);
+                self.close();
+            }
+            Err(e) => log::warn!("Error cropping the photo: {:?}", e),
+        }
+    }
+}
+
+/// Crops a square out of an image and saves it as a JPEG file in the temporary directory,
+/// returning its path.
+fn crop_image(path: &str, x: u32, y: u32, size: u32) -> Result<String, image::ImageError> {
+    let mut image = image::open(path)?.crop_imm(x, y, size, size);
+    if size > MAX_PHOTO_SIZE {
+        image = image.resize(
+            MAX_PHOTO_SIZE,
+            MAX_PHOTO_SIZE,
+            image::imageops::FilterType::Lanczos3,
+        );
+    }
+
+    let path = temp_dir()
+        .map(|dir| dir.join(format!("profile-photo-{}.jpg", glib::monotonic_time())))
+        .ok_or_else(|| {
+            image::ImageError::IoError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "the temporary directory doesn't exist",
+            ))
+        })?;
+
+    image::DynamicImage::ImageRgb8(image.to_rgb8()).save(&path)?;
+
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,402 @@
+mod crop_window;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gio;
+use gtk::glib;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums;
+use tdlib::enums::CheckChatUsernameResult;
+use tdlib::enums::InputChatPhoto;
+use tdlib::enums::InputFile;
+use tdlib::functions;
+use tdlib::types;
+
+use self::crop_window::CropWindow;
+use crate::i18n::gettext_f;
+use crate::utils::spawn;
+use crate::Session;
+
+const PHOTO_MIME_TYPES: &[&str] = &["image/png", "image/jpeg"];
+
+/// The time to wait after the username was last changed before checking it.
+const USERNAME_CHECK_DELAY: Duration = Duration::from_millis(300);
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/profile-window.ui")]
+    pub(crate) struct ProfileWindow {
+        pub(super) session: OnceCell<Session>,
+        /// The bio of the user, as it was when the window was opened.
+        pub(super) bio: RefCell<String>,
+        /// The id of the current profile photo of the user, if they have one.
+        pub(super) photo_id: Cell<Option<i64>>,
+        pub(super) username_check_source: RefCell<Option<glib::SourceId>>,
+        /// Incremented whenever the username changes, so that stale checks can be told apart.
+        pub(super) username_generation: Cell<u32>,
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        pub(super) remove_photo_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) first_name_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) last_name_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) bio_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) username_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) username_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) username_spinner: TemplateChild<gtk::Spinner>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ProfileWindow {
+        const NAME: &'static str = "ProfileWindow";
+        type Type = super::ProfileWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+
+            klass.install_action_async("profile-window.save", None, |widget, _, _| async move {
+                widget.save().await;
+            });
+            klass.install_action_async(
+                "profile-window.set-photo",
+                None,
+                |widget, _, _| async move {
+                    widget.select_photo().await;
+                },
+            );
+            klass.install_action_async(
+                "profile-window.remove-photo",
+                None,
+                |widget, _, _| async move {
+                    widget.remove_photo().await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ProfileWindow {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Session>("session")
+                    .construct_only()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.session.set(value.get().unwrap()).unwrap(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => obj.session().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            let me = obj.session().me();
+
+            self.first_name_row.set_text(&me.first_name());
+            self.last_name_row.set_text(&me.last_name());
+            self.username_row.set_text(&me.username());
+            obj.queue_username_check();
+
+            spawn(clone!(@weak obj => async move {
+                if let Some(full_info) = obj.fetch_full_info().await {
+                    let bio = full_info.bio.as_ref().map(|bio| bio.text.clone()).unwrap_or_default();
+                    obj.imp().bio_row.set_text(&bio);
+                    obj.imp().bio.replace(bio);
+                    obj.update_photo_id(&full_info);
+                }
+            }));
+        }
+
+        fn dispose(&self) {
+            if let Some(source_id) = self.username_check_source.take() {
+                source_id.remove();
+            }
+        }
+    }
+
+    impl WidgetImpl for ProfileWindow {}
+    impl WindowImpl for ProfileWindow {}
+    impl AdwWindowImpl for ProfileWindow {}
+
+    #[gtk::template_callbacks]
+    impl ProfileWindow {
+        #[template_callback]
+        fn on_username_changed(&self) {
+            self.obj().queue_username_check();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A window to edit the name, bio, username and photo of the logged in user.
+    pub(crate) struct ProfileWindow(ObjectSubclass<imp::ProfileWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl ProfileWindow {
+    pub(crate) fn new(parent_window: Option<&gtk::Window>, session: &Session) -> Self {
+        glib::Object::builder()
+            .property("transient-for", parent_window)
+            .property("session", session)
+            .build()
+    }
+
+    pub(crate) fn session(&self) -> &Session {
+        self.imp().session.get().unwrap()
+    }
+
+    /// Fetches the full info of the user, which contains their bio and the id of their photo.
+    async fn fetch_full_info(&self) -> Option<types::UserFullInfo> {
+        let session = self.session();
+
+        match functions::get_user_full_info(session.me().id(), session.client_id()).await {
+            Ok(enums::UserFullInfo::UserFullInfo(full_info)) => Some(full_info),
+            Err(e) => {
+                log::warn!("Error getting the full info of the user: {:?}", e);
+                None
+            }
+        }
+    }
+
+    fn update_photo_id(&self, full_info: &types::UserFullInfo) {
+        let imp = self.imp();
+        let photo_id = full_info.photo.as_ref().map(|photo| photo.id);
+
+        imp.photo_id.set(photo_id);
+        imp.remove_photo_button.set_visible(photo_id.is_some());
+    }
+
+    async fn reload_photo_id(&self) {
+        if let Some(full_info) = self.fetch_full_info().await {
+            self.update_photo_id(&full_info);
+        }
+    }
+
+    fn set_username_description(&self, description: &str, is_checking: bool) {
+        let imp = self.imp();
+        imp.username_group.set_description(Some(description));
+        imp.username_spinner.set_visible(is_checking);
+        imp.username_spinner.set_spinning(is_checking);
+    }
+
+    /// Checks whether the new username is available once it hasn't been changed for a while.
+    fn queue_username_check(&self) {
+        let imp = self.imp();
+
+        imp.username_generation
+            .set(imp.username_generation.get().wrapping_add(1));
+        if let Some(source_id) = imp.username_check_source.take() {
+            source_id.remove();
+        }
+
+        let username = imp.username_row.text().trim().to_string();
+        if username.is_empty() || username == self.session().me().username() {
+            self.set_username_description(
+                &gettext("Other people can find you by this username"),
+                false,
+            );
+            return;
+        }
+
+        self.set_username_description(&gettext("Checking username…"), true);
+
+        let generation = imp.username_generation.get();
+        let source_id = glib::timeout_add_local_once(
+            USERNAME_CHECK_DELAY,
+            clone!(@weak self as obj => move || {
+                obj.imp().username_check_source.take();
+                spawn(clone!(@weak obj => async move {
+                    obj.check_username(username, generation).await;
+                }));
+            }),
+        );
+        imp.username_check_source.replace(Some(source_id));
+    }
+
+    async fn check_username(&self, username: String, generation: u32) {
+        let session = self.session();
+        let result = functions::check_chat_username(
+            session.me().id(),
+            username.clone(),
+            session.client_id(),
+        )
+        .await;
+
+        // The username may have been changed in the meantime
+        if self.imp().username_generation.get() != generation {
+            return;
+        }
+
+        let description = match result {
+            Ok(CheckChatUsernameResult::Ok) => {
+                gettext_f("{username} is available", &[("username", &username)])
+            }
+            Ok(CheckChatUsernameResult::UsernameInvalid) => gettext("This username is invalid"),
+            Ok(CheckChatUsernameResult::UsernameOccupied) => {
+                gettext("This username is already taken")
+            }
+            Ok(CheckChatUsernameResult::UsernamePurchasable) => {
+                gettext("This username is for sale on Fragment")
+            }
+            Ok(CheckChatUsernameResult::PublicChatsTooMany) => {
+                gettext("You have too many public chats")
+            }
+            Ok(CheckChatUsernameResult::PublicGroupsUnavailable) => {
+                gettext("This username can't be used")
+            }
+            Err(e) => {
+                log::warn!("Error checking a username: {:?}", e);
+                gettext("Could not check the username")
+            }
+        };
+
+        self.set_username_description(&description, false);
+    }
+
+    async fn save(&self) {
+        let imp = self.imp();
+        let session = self.session();
+        let client_id = session.client_id();
+        let me = session.me();
+
+        let first_name = imp.first_name_row.text().trim().to_string();
+        let last_name = imp.last_name_row.text().trim().to_string();
+        let bio = imp.bio_row.text().trim().to_string();
+        let username = imp.username_row.text().trim().to_string();
+
+        if first_name.is_empty() {
+            imp.toast_overlay
+                .add_toast(adw::Toast::new(&gettext("The first name can't be empty")));
+            return;
+        }
+
+        self.action_set_enabled("profile-window.save", false);
+
+        let mut result = Ok(());
+        if first_name != me.first_name() || last_name != me.last_name() {
+            result = functions::set_name(first_name, last_name, client_id).await;
+        }
+        if result.is_ok() && bio != *imp.bio.borrow() {
+            result = functions::set_bio(bio, client_id).await;
+        }
+        if result.is_ok() && username != me.username() {
+            result = functions::set_username(username, client_id).await;
+        }
+
+        match result {
+            Ok(()) => self.close(),
+            Err(e) => {
+                log::warn!("Error saving the profile: {:?}", e);
+                imp.toast_overlay
+                    .add_toast(adw::Toast::new(&gettext("Could not save the profile")));
+                self.action_set_enabled("profile-window.save", true);
+            }
+        }
+    }
+
+    async fn select_photo(&self) {
+        let dialog = gtk::FileDialog::new();
+        let filter = gtk::FileFilter::new();
+        let filters = gio::ListStore::new(gtk::FileFilter::static_type());
+
+        filter.set_name(Some(&gettext("Images")));
+        for mime in PHOTO_MIME_TYPES {
+            filter.add_mime_type(mime);
+        }
+
+        filters.append(&filter);
+        dialog.set_filters(&filters);
+
+        let Ok(file) = dialog.open_future(Some(self)).await else {
+            return;
+        };
+        let Some(path) = file.path().and_then(|path| path.to_str().map(String::from)) else {
+            return;
+        };
+
+        match CropWindow::new(self, path) {
+            Ok(crop_window) => {
+                crop_window.connect_cropped(clone!(@weak self as obj => move |_, path| {
+                    spawn(clone!(@weak obj => async move {
+                        obj.set_photo(path).await;
+                    }));
+                }));
+                crop_window.present();
+            }
+            Err(e) => {
+                log::warn!("Error loading the profile photo: {:?}", e);
+                self.imp()
+                    .toast_overlay
+                    .add_toast(adw::Toast::new(&gettext("Could not load the image")));
+            }
+        }
+    }
+
+    async fn set_photo(&self, path: String) {
+        let photo = InputChatPhoto::Static(types::InputChatPhotoStatic {
+            photo: InputFile::Local(types::InputFileLocal { path }),
+        });
+
+        match functions::set_profile_photo(photo, false, self.session().client_id()).await {
+            Ok(()) => self.reload_photo_id().await,
+            Err(e) => {
+                log::warn!("Error setting the profile photo: {:?}", e);
+                self.imp()
+                    .toast_overlay
+                    .add_toast(adw::Toast::new(&gettext("Could not set the photo")));
+            }
+        }
+    }
+
+    async fn remove_photo(&self) {
+        let imp = self.imp();
+        let Some(photo_id) = imp.photo_id.get() else {
+            return;
+        };
+
+        match functions::delete_profile_photo(photo_id, self.session().client_id()).await {
+            Ok(()) => self.reload_photo_id().await,
+            Err(e) => {
+                log::warn!("Error removing the profile photo: {:?}", e);
+                imp.toast_overlay
+                    .add_toast(adw::Toast::new(&gettext("Could not remove the photo")));
+            }
+        }
+    }
+}
@@ -230,9 +230,10 @@ impl Sidebar {
             let item = chat.session().main_chat_list().find_chat_item(chat.id());
             imp.selection.set_selected_item(item.map(|i| i.upcast()));
 
+            // The unread messages are read as they are shown in the chat history
             if chat.is_marked_as_unread() {
                 spawn(async move {
-                    if let Err(e) = chat.mark_as_read().await {
+                    if let Err(e) = chat.remove_unread_mark().await {
                         log::warn!("Error on toggling chat's unread state: {e:?}");
                     }
                 });
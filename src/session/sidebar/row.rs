@@ -573,7 +573,7 @@ impl Row {
     }
 }
 
-fn sender_label(message: Message) -> Option<String> {
+pub(crate) fn sender_label(message: Message) -> Option<String> {
     use MessageContent::*;
 
     // Just use a sender label for specific messages.
@@ -645,7 +645,7 @@ fn draft_message_text(message: DraftMessage) -> String {
     }
 }
 
-fn timestamp_text(date: i64) -> glib::GString {
+pub(crate) fn timestamp_text(date: i64) -> glib::GString {
     let datetime_now = glib::DateTime::now_local().unwrap();
     let datetime = glib::DateTime::from_unix_utc(date)
         .and_then(|t| t.to_local())
@@ -0,0 +1,186 @@
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+
+use crate::session::sidebar::row::sender_label;
+use crate::session::sidebar::row::timestamp_text;
+use crate::strings;
+use crate::tdlib::Message;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $SidebarSearchMessageRow {
+        $ComponentsAvatar {
+            size: 32;
+            item: bind template.message as <$Message>.chat;
+        }
+
+        Box {
+            orientation: vertical;
+            spacing: 3;
+            valign: center;
+
+            Box {
+                spacing: 6;
+
+                Inscription title_label {
+                    hexpand: true;
+                    text-overflow: ellipsize_end;
+                }
+
+                Label timestamp_label {
+                    single-line-mode: true;
+
+                    styles [
+                        "dim-label",
+                        "numeric",
+                        "caption",
+                    ]
+                }
+            }
+
+            Box {
+                spacing: 3;
+
+                Label sender_label {
+                    ellipsize: end;
+                    xalign: 0;
+
+                    styles ["small-body"]
+                }
+
+                Inscription text_label {
+                    hexpand: true;
+                    text-overflow: ellipsize_end;
+
+                    styles [
+                        "dim-label",
+                        "small-body",
+                    ]
+                }
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct MessageRow {
+        pub(super) message: RefCell<Option<Message>>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Inscription>,
+        #[template_child]
+        pub(super) timestamp_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) sender_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) text_label: TemplateChild<gtk::Inscription>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageRow {
+        const NAME: &'static str = "SidebarSearchMessageRow";
+        type Type = super::MessageRow;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.set_css_name("sidebarsearchmessagerow");
+            klass.set_layout_manager_type::<gtk::BoxLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageRow {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.message().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for MessageRow {}
+}
+
+glib::wrapper! {
+    /// A row of a message found by the search, showing its chat, sender and text.
+    pub(crate) struct MessageRow(ObjectSubclass<imp::MessageRow>)
+        @extends gtk::Widget;
+}
+
+impl MessageRow {
+    pub(crate) fn new(message: &Message) -> Self {
+        glib::Object::builder().property("message", message).build()
+    }
+
+    pub(crate) fn set_message(&self, message: Option<Message>) {
+        if self.message() == message {
+            return;
+        }
+
+        let imp = self.imp();
+
+        if let Some(ref message) = message {
+            let chat = message.chat();
+            imp.title_label.set_text(Some(&if chat.is_own_chat() {
+                gettext("Saved Messages")
+            } else {
+                chat.title()
+            }));
+            imp.timestamp_label
+                .set_label(&timestamp_text(message.date() as i64));
+
+            match sender_label(message.clone()) {
+                Some(sender) => {
+                    imp.sender_label.set_label(&format!("{sender}:"));
+                    imp.sender_label.set_visible(true);
+                }
+                None => imp.sender_label.set_visible(false),
+            }
+
+            imp.text_label
+                .set_text(Some(&strings::message_content(message).replace('\n', " ")));
+        }
+
+        imp.message.replace(message);
+        self.notify("message");
+    }
+
+    pub(crate) fn message(&self) -> Option<Message> {
+        self.imp().message.borrow().clone()
+    }
+}
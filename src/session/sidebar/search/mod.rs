@@ -1,8 +1,10 @@
 mod item_row;
+mod message_row;
 mod row;
 mod section;
 mod section_row;
 
+use std::cell::Cell;
 use std::cell::RefCell;
 
 use gettextrs::gettext;
@@ -18,16 +20,21 @@ use tdlib::enums;
 use tdlib::functions;
 
 use self::item_row::ItemRow;
+use self::message_row::MessageRow;
 use self::row::Row;
 use self::section::Section;
 use self::section::SectionType;
 use self::section_row::SectionRow;
 use crate::session::Sidebar;
 use crate::tdlib::Chat;
+use crate::tdlib::Message;
 use crate::tdlib::User;
 use crate::utils::spawn;
 use crate::Session;
 
+/// The number of found messages that are loaded at a time.
+const MESSAGES_PAGE_SIZE: i32 = 50;
+
 mod imp {
     use super::*;
 
@@ -35,6 +42,12 @@ mod imp {
     #[template(resource = "/app/drey/paper-plane/ui/sidebar-search.ui")]
     pub(crate) struct Search {
         pub(super) session: RefCell<Option<Session>>,
+        /// The list of the found messages of the current search.
+        pub(super) message_list: RefCell<Option<gio::ListStore>>,
+        pub(super) message_query: RefCell<String>,
+        /// The offset of the next page of found messages, if there is one.
+        pub(super) message_next_offset: RefCell<Option<String>>,
+        pub(super) is_loading_messages: Cell<bool>,
         #[template_child]
         pub(super) toolbar_view: TemplateChild<adw::ToolbarView>,
         #[template_child]
@@ -173,9 +186,17 @@ impl Search {
 
         const MAX_KNOWN_CHATS: i32 = 50;
 
-        imp.selection.set_model(Some(&list));
-        list.connect_items_changed(clone!(@weak self as obj => move |list, _, _, _| {
-            obj.imp().stack.set_visible_child_name(if list.n_items() > 0 {
+        // The found messages are kept in their own list, so that they always come after the
+        // chats and further pages can be appended to them
+        let message_list = gio::ListStore::new(glib::Object::static_type());
+        let lists = gio::ListStore::new(gio::ListModel::static_type());
+        lists.append(&list);
+        lists.append(&message_list);
+        let model = gtk::FlattenListModel::new(Some(lists));
+
+        imp.selection.set_model(Some(&model));
+        model.connect_items_changed(clone!(@weak self as obj => move |model, _, _, _| {
+            obj.imp().stack.set_visible_child_name(if model.n_items() > 0 {
                 "results"
             } else {
                 "empty"
@@ -186,6 +207,17 @@ impl Search {
         // before even starting the search.
         imp.stack.set_visible_child_name("results");
 
+        imp.message_list.replace(Some(message_list));
+        imp.message_query.replace(query.clone());
+        imp.message_next_offset
+            .replace((!query.is_empty()).then(String::new));
+        imp.is_loading_messages.set(false);
+
+        // Search messages alongside the chats
+        spawn(clone!(@weak self as obj => async move {
+            obj.load_more_messages().await;
+        }));
+
         if !query.is_empty()
             && gettext("Saved Messages")
                 .to_lowercase()
@@ -226,7 +258,7 @@ impl Search {
         }
 
         // Show the empty page if there are no results after the first part of the search
-        if list.n_items() == 0 {
+        if model.n_items() == 0 {
             imp.stack.set_visible_child_name("empty");
         }
 
@@ -336,6 +368,62 @@ impl Search {
         }
     }
 
+    /// Loads the next page of the messages found by the current search, if there is one.
+    async fn load_more_messages(&self) {
+        let imp = self.imp();
+
+        let Some(list) = imp.message_list.borrow().clone() else {
+            return;
+        };
+        let Some(offset) = imp.message_next_offset.borrow().clone() else {
+            return;
+        };
+        if imp.is_loading_messages.get() {
+            return;
+        }
+
+        imp.is_loading_messages.set(true);
+
+        let query = imp.message_query.borrow().clone();
+        let result = self
+            .session()
+            .unwrap()
+            .search_messages(query, offset, MESSAGES_PAGE_SIZE)
+            .await;
+
+        // Another search may have been started in the meantime
+        if imp.message_list.borrow().as_ref() != Some(&list) {
+            return;
+        }
+
+        imp.is_loading_messages.set(false);
+
+        match result {
+            Ok((messages, next_offset)) => {
+                if list.n_items() == 0 && !messages.is_empty() {
+                    list.append(&Section::new(SectionType::Messages));
+                }
+                list.extend_from_slice(&messages);
+
+                imp.message_next_offset
+                    .replace((!next_offset.is_empty()).then_some(next_offset));
+            }
+            Err(e) => {
+                log::warn!("Error searching messages: {:?}", e);
+                imp.message_next_offset.replace(None);
+            }
+        }
+    }
+
+    #[template_callback]
+    fn on_edge_reached(&self, pos: gtk::PositionType) {
+        if pos == gtk::PositionType::Bottom {
+            spawn(clone!(@weak self as obj => async move {
+                obj.load_more_messages().await;
+            }));
+        }
+    }
+
     #[template_callback]
     async fn list_activate(&self, position: u32) {
         let item = self.imp().selection.item(position).unwrap();
@@ -360,6 +448,8 @@ impl Search {
             {
                 log::warn!("Failed to add recently found chat: {:?}", e);
             }
+        } else if let Some(message) = item.downcast_ref::<Message>() {
+            session.select_message(message);
         } else {
             log::warn!("Unexpected item type: {:?}", item);
         }
@@ -7,10 +7,12 @@ use once_cell::sync::Lazy;
 use once_cell::unsync::OnceCell;
 
 use crate::session::sidebar::search::ItemRow;
+use crate::session::sidebar::search::MessageRow;
 use crate::session::sidebar::search::Section;
 use crate::session::sidebar::search::SectionRow;
 use crate::session::sidebar::search::SectionType;
 use crate::tdlib::Chat;
+use crate::tdlib::Message;
 use crate::tdlib::User;
 
 mod imp {
@@ -18,7 +20,7 @@ mod imp {
 
     #[derive(Debug, Default)]
     pub(crate) struct Row {
-        /// A `Chat` or `User`
+        /// A `Chat`, `User`, `Message` or `Section`
         pub(super) item: RefCell<Option<glib::Object>>,
         pub(super) list_item: OnceCell<gtk::ListItem>,
         pub(super) child: RefCell<Option<gtk::Widget>>,
@@ -100,6 +102,9 @@ impl Row {
         {
             imp.list_item.get().unwrap().set_activatable(true);
             self.update_or_create_item_row(item.clone());
+        } else if let Some(message) = item.as_ref().and_then(|i| i.downcast_ref::<Message>()) {
+            imp.list_item.get().unwrap().set_activatable(true);
+            self.update_or_create_message_row(message);
         } else if let Some(section) = item.as_ref().and_then(|i| i.downcast_ref::<Section>()) {
             imp.list_item.get().unwrap().set_activatable(false);
             self.update_or_create_section_row(section.section_type());
@@ -135,6 +140,23 @@ impl Row {
         }
     }
 
+    fn update_or_create_message_row(&self, message: &Message) {
+        let mut child_ref = self.imp().child.borrow_mut();
+        match child_ref
+            .as_ref()
+            .and_then(|c| c.downcast_ref::<MessageRow>())
+        {
+            Some(message_row) => {
+                message_row.set_message(Some(message.clone()));
+            }
+            None => {
+                let message_row = MessageRow::new(message);
+                message_row.set_parent(self);
+                *child_ref = Some(message_row.upcast());
+            }
+        }
+    }
+
     fn update_or_create_section_row(&self, section_type: SectionType) {
         let mut child_ref = self.imp().child.borrow_mut();
         match child_ref
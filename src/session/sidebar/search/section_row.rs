@@ -135,6 +135,9 @@ impl SectionRow {
             SectionType::Global => {
                 imp.label.set_text(Some(&gettext("Global Search")));
             }
+            SectionType::Messages => {
+                imp.label.set_text(Some(&gettext("Messages")));
+            }
             SectionType::Recent => {
                 imp.label.set_text(Some(&gettext("Recent")));
 
@@ -0,0 +1,58 @@
+use glib::subclass::InitializingObject;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/app/drey/paper-plane/ui/edit-profile-row.ui")]
+    pub(crate) struct EditProfileRow {
+        #[template_child]
+        pub(super) image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for EditProfileRow {
+        const NAME: &'static str = "EditProfileRow";
+        type Type = super::EditProfileRow;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for EditProfileRow {
+        fn dispose(&self) {
+            self.image.unparent();
+            self.label.unparent();
+        }
+    }
+    impl WidgetImpl for EditProfileRow {}
+}
+
+glib::wrapper! {
+    pub(crate) struct EditProfileRow(ObjectSubclass<imp::EditProfileRow>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible;
+}
+
+impl EditProfileRow {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl Default for EditProfileRow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
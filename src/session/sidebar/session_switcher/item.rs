@@ -8,6 +8,7 @@ use gtk::subclass::prelude::*;
 use once_cell::sync::Lazy;
 
 use super::add_account::AddAccountRow;
+use super::edit_profile::EditProfileRow;
 use super::session_entry_row::SessionEntryRow;
 use crate::session::Session;
 
@@ -56,11 +57,12 @@ mod imp {
 #[enum_type(name = "ExtraItem")]
 pub(crate) enum ExtraItem {
     Separator = 0,
-    AddAccount = 1,
+    EditProfile = 1,
+    AddAccount = 2,
 }
 
 impl ExtraItem {
-    const VALUES: [Self; 2] = [Self::Separator, Self::AddAccount];
+    const VALUES: [Self; 3] = [Self::Separator, Self::EditProfile, Self::AddAccount];
 }
 
 impl Default for ExtraItem {
@@ -99,6 +101,7 @@ impl ExtraItemObj {
 pub(crate) enum Item {
     Session(Session, bool),
     Separator,
+    EditProfile,
     AddAccount,
 }
 
@@ -106,6 +109,7 @@ impl From<ExtraItem> for Item {
     fn from(extra_item: ExtraItem) -> Self {
         match extra_item {
             ExtraItem::Separator => Self::Separator,
+            ExtraItem::EditProfile => Self::EditProfile,
             ExtraItem::AddAccount => Self::AddAccount,
         }
     }
@@ -141,6 +145,7 @@ impl Item {
                 session_entry.upcast()
             }
             Self::Separator => gtk::Separator::new(gtk::Orientation::Vertical).upcast(),
+            Self::EditProfile => EditProfileRow::new().upcast(),
             Self::AddAccount => AddAccountRow::new().upcast(),
         }
     }
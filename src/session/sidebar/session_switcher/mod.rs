@@ -1,5 +1,6 @@
 mod add_account;
 mod avatar_with_selection;
+mod edit_profile;
 mod item;
 mod session_entry_row;
 
@@ -50,28 +51,34 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
 
-            self.entries.connect_activate(|list_view, index| {
-                if let Some(Ok(item)) = list_view
-                    .model()
-                    .and_then(|model| model.item(index))
-                    .map(SessionSwitcherItem::try_from)
-                {
-                    match item {
-                        SessionSwitcherItem::Session(session, _) => {
-                            session
-                                .parent()
-                                .unwrap()
-                                .downcast::<gtk::Stack>()
-                                .unwrap()
-                                .set_visible_child(&session);
+            let obj = self.obj();
+            self.entries
+                .connect_activate(clone!(@weak obj => move |list_view, index| {
+                    if let Some(Ok(item)) = list_view
+                        .model()
+                        .and_then(|model| model.item(index))
+                        .map(SessionSwitcherItem::try_from)
+                    {
+                        match item {
+                            SessionSwitcherItem::Session(session, _) => {
+                                session
+                                    .parent()
+                                    .unwrap()
+                                    .downcast::<gtk::Stack>()
+                                    .unwrap()
+                                    .set_visible_child(&session);
+                            }
+                            SessionSwitcherItem::EditProfile => {
+                                obj.popdown();
+                                list_view.activate_action("session.edit-profile", None).unwrap();
+                            }
+                            SessionSwitcherItem::AddAccount => {
+                                /* ignored - as this is handled separately in the AddAccountItem */
+                            }
+                            other => unreachable!("Unexpected item: {:?}", other),
                         }
-                        SessionSwitcherItem::AddAccount => {
-                            /* ignored - as this is handled separately in the AddAccountItem */
-                        }
-                        other => unreachable!("Unexpected item: {:?}", other),
                     }
-                }
-            });
+                }));
         }
 
         fn dispose(&self) {
@@ -29,12 +29,19 @@
 //! # Remembering recently used sessions
 //! In order to remember the order in which the user selected the sessions, the `SessionManager`
 //! uses a gsettings key value pair.
+//!
+//! # Replaying recorded updates
+//! When an update log is replayed (see [`crate::update_log`]), the data directory is ignored and
+//! a client is registered for every client id found in the log. Those clients skip the login
+//! process, just like existing sessions do, and use the recorded `my_id` option instead of asking
+//! TDLib who the logged in user is.
 
 use std::borrow::Borrow;
 use std::cell::Cell;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
+use std::rc::Rc;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -52,7 +59,9 @@ use tdlib::functions;
 use tdlib::types;
 use tdlib::types::UpdateAuthorizationState;
 
+use crate::backend::FakeBackend;
 use crate::tdlib::User;
+use crate::update_log;
 use crate::utils::block_on;
 use crate::utils::data_dir;
 use crate::utils::log_out;
@@ -155,6 +164,11 @@ mod imp {
                 clone!(@weak obj => move |_| obj.on_active_session_changed()),
             );
 
+            // Clients are registered on the fly while replaying updates.
+            if update_log::is_replaying() {
+                return;
+            }
+
             // ####################################################################################
             // # Load the sessions from the data directory.                                       #
             // ####################################################################################
@@ -301,6 +315,11 @@ impl SessionManager {
     fn on_active_session_changed(&self) {
         let imp = self.imp();
 
+        // Replayed sessions are neither on a server nor in the data directory
+        if update_log::is_replaying() {
+            return;
+        }
+
         if let Some(session) = imp
             .sessions
             .visible_child()
@@ -325,6 +344,10 @@ impl SessionManager {
     /// Sets the online status for the active logged in client. This will be called from the
     /// application `Window` when its active state has changed.
     pub(crate) async fn set_active_client_online(&self, value: bool) {
+        if update_log::is_replaying() {
+            return;
+        }
+
         if let Some(client_id) = self.active_logged_in_client_id() {
             let result = set_online(client_id, value).await;
             if let Err(e) = result {
@@ -387,6 +410,33 @@ impl SessionManager {
         });
     }
 
+    /// This function is used to register a client that only exists in a replayed update log. Like
+    /// existing sessions, it will skip the login process.
+    fn add_replayed_client(&self, client_id: i32) {
+        let database_info = DatabaseInfo {
+            directory_base_name: format!("replay{client_id}"),
+            use_test_dc: false,
+        };
+
+        // There's no TDLib client behind a replayed session, so its requests must be answered
+        // by a fake backend
+        let backend = Rc::new(FakeBackend::default());
+        let session = Session::with_backend(client_id, database_info, backend.clone());
+        backend.set_update_handler(clone!(@weak session => move |update| {
+            session.handle_update(update);
+        }));
+
+        self.imp().clients.borrow_mut().insert(
+            client_id,
+            Client {
+                session,
+                state: ClientState::Auth {
+                    maybe_authorized: true,
+                },
+            },
+        );
+    }
+
     /// This function is used to add a new session for a so far unknown account. This means it will
     /// go through the login process.
     pub(crate) fn add_new_session(&self, use_test_dc: bool) {
@@ -491,6 +541,11 @@ impl SessionManager {
     /// Function cleaning up, which is called by the application windows on closing. It sets all
     /// clients offline.
     pub(crate) fn close_clients(&self) {
+        // There are no TDLib clients to close while replaying updates.
+        if update_log::is_replaying() {
+            return;
+        }
+
         // Create a future to close the sessions.
         let close_sessions_future = futures::future::join_all(
             self.imp()
@@ -518,6 +573,10 @@ impl SessionManager {
     }
 
     pub(crate) fn handle_update(&self, update: Update, client_id: i32) {
+        if update_log::is_replaying() && self.client(client_id).is_none() {
+            self.add_replayed_client(client_id);
+        }
+
         match update {
             Update::AuthorizationState(update) => {
                 self.handle_authorization_state(update, client_id);
@@ -542,7 +601,7 @@ impl SessionManager {
 
         if let AuthorizationState::Closed = update.authorization_state {
             let client = imp.clients.borrow_mut().remove(&client_id).unwrap();
-            if let ClientState::LoggingOut = client.state {
+            if matches!(client.state, ClientState::LoggingOut) && !update_log::is_replaying() {
                 let database_dir_base_name = client.database_dir_base_name().to_owned();
                 if let Err(e) = fs::remove_dir_all(data_dir().join(database_dir_base_name)) {
                     log::error!("Error on on removing database directory: {}", e);
@@ -573,7 +632,7 @@ impl SessionManager {
             } else {
                 // Client doesn't need to authorize. So we can skip the login procedure.
                 match &update.authorization_state {
-                    AuthorizationState::WaitTdlibParameters => {
+                    AuthorizationState::WaitTdlibParameters if !update_log::is_replaying() => {
                         let database_info = client.session.database_info().0.clone();
                         spawn(async move {
                             let result = send_tdlib_parameters(client_id, &database_info).await;
@@ -592,11 +651,28 @@ impl SessionManager {
                             .map(|last| client.database_dir_base_name() == last)
                             .unwrap_or_default();
 
-                        spawn(clone!(@weak self as obj => async move {
-                            obj.add_logged_in_session(client_id, client.session, is_last_used)
-                                .await;
-                        }));
+                        if update_log::is_replaying() {
+                            // The replayed updates are external input, so they may lack the user
+                            match client.session.try_user(client.session.my_id()) {
+                                Some(me) => self.add_logged_in_session_(
+                                    client_id,
+                                    &client.session,
+                                    &me,
+                                    true,
+                                ),
+                                None => log::warn!(
+                                    "The replayed updates of client {} don't contain the own user",
+                                    client_id
+                                ),
+                            }
+                        } else {
+                            spawn(clone!(@weak self as obj => async move {
+                                obj.add_logged_in_session(client_id, client.session, is_last_used)
+                                    .await;
+                            }));
+                        }
                     }
+                    _ if update_log::is_replaying() => {}
                     _ => {
                         let database_info = client.session.database_info().0.clone();
 
@@ -665,15 +741,16 @@ impl SessionManager {
         self.add_logged_in_session_(client_id, &session, &session.user(me.id), visible);
     }
 
-    async fn enable_notifications(&self, client_id: i32) {
-        let result = functions::set_option(
-            "notification_group_count_max".to_string(),
-            Some(enums::OptionValue::Integer(types::OptionValueInteger {
-                value: 5,
-            })),
-            client_id,
-        )
-        .await;
+    async fn enable_notifications(&self, session: &Session) {
+        let result = session
+            .backend()
+            .set_option(
+                "notification_group_count_max".to_string(),
+                Some(enums::OptionValue::Integer(types::OptionValueInteger {
+                    value: 5,
+                })),
+            )
+            .await;
 
         if let Err(e) = result {
             log::warn!(
@@ -715,8 +792,8 @@ impl SessionManager {
         }
 
         // Enable notifications for this client
-        spawn(clone!(@weak self as obj => async move {
-            obj.enable_notifications(client_id).await;
+        spawn(clone!(@weak self as obj, @weak session => async move {
+            obj.enable_notifications(&session).await;
         }));
     }
 
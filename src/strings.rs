@@ -2,10 +2,14 @@ use ellipse::Ellipse;
 use gettextrs::gettext;
 use gtk::glib;
 use tdlib::enums::CallDiscardReason;
+use tdlib::enums::PollType;
 use tdlib::enums::UserStatus;
 use tdlib::enums::UserType;
+use tdlib::types::Audio;
+use tdlib::types::Contact;
 use tdlib::types::MessageGame;
 use tdlib::types::MessageGameScore;
+use tdlib::types::Poll;
 
 use crate::i18n::gettext_f;
 use crate::i18n::ngettext_f;
@@ -450,6 +454,25 @@ pub(crate) fn message_sender(sender: &MessageSender, use_full_name: bool) -> Str
     }
 }
 
+/// Returns the title of an audio file, falling back to its file name.
+pub(crate) fn audio_title(audio: &Audio) -> String {
+    if !audio.title.is_empty() {
+        audio.title.clone()
+    } else if !audio.file_name.is_empty() {
+        audio.file_name.clone()
+    } else {
+        gettext("Unknown Track")
+    }
+}
+
+pub(crate) fn audio_performer(audio: &Audio) -> String {
+    if audio.performer.is_empty() {
+        gettext("Unknown Artist")
+    } else {
+        audio.performer.clone()
+    }
+}
+
 pub(crate) fn message_content(message: &Message) -> String {
     use tdlib::enums::MessageContent::*;
     let sender = message.sender();
@@ -471,6 +494,10 @@ pub(crate) fn message_content(message: &Message) -> String {
         MessageVoiceNote(data) => message_voice_note(&data.caption.text),
         MessageAnimatedEmoji(data) => data.emoji,
         MessageDice(data) => data.emoji,
+        MessagePoll(data) => message_poll(&data.poll),
+        MessageContact(data) => contact_name(&data.contact),
+        MessageLocation(data) => message_location(data.live_period),
+        MessageVenue(data) => data.venue.title,
         MessageCall(data) => message_call(
             &data.discard_reason,
             data.is_video,
@@ -509,6 +536,20 @@ pub(crate) fn message_content(message: &Message) -> String {
     }
 }
 
+pub(crate) fn contact_name(contact: &Contact) -> String {
+    format!("{} {}", contact.first_name, contact.last_name)
+        .trim()
+        .to_string()
+}
+
+fn message_location(live_period: i32) -> String {
+    if live_period > 0 {
+        gettext("Live Location")
+    } else {
+        gettext("Location")
+    }
+}
+
 fn message_animation(caption: &str) -> String {
     if caption.is_empty() {
         gettext("GIF")
@@ -549,6 +590,14 @@ fn message_document(file_name: &str, caption: &str) -> String {
     }
 }
 
+fn message_poll(poll: &Poll) -> String {
+    if matches!(poll.r#type, PollType::Quiz(_)) {
+        gettext_f("Quiz, {question}", &[("question", &poll.question)])
+    } else {
+        gettext_f("Poll, {question}", &[("question", &poll.question)])
+    }
+}
+
 fn message_photo(caption: &str) -> String {
     if caption.is_empty() {
         gettext("Photo")
@@ -807,8 +856,8 @@ fn message_game_score(game: &MessageGameScore, chat: &Chat, sender: &MessageSend
 fn message_pin_message(message_id: i64, chat: &Chat, sender: &MessageSender) -> String {
     use tdlib::enums::MessageContent::*;
 
-    // TODO: Add a way to retrieve the message and update the string
-    // in case we don't have the message stored locally.
+    // The pinned message may not be cached yet, in which case the caller needs to fetch it and
+    // then get the string again
     let string = match chat.message(message_id) {
         Some(message) => match message.content().0 {
             MessageText(data) => {
@@ -825,6 +874,9 @@ fn message_pin_message(message_id: i64, chat: &Chat, sender: &MessageSender) ->
             MessageAudio(_) => gettext("{sender} pinned an audio file"),
             MessageDocument(_) => gettext("{sender} pinned a file"),
             MessagePhoto(_) | MessageExpiredPhoto => gettext("{sender} pinned a photo"),
+            MessagePoll(_) => gettext("{sender} pinned a poll"),
+            MessageContact(_) => gettext("{sender} pinned a contact"),
+            MessageLocation(_) | MessageVenue(_) => gettext("{sender} pinned a location"),
             MessageSticker(_) => gettext("{sender} pinned a sticker"),
             MessageVideo(_) | MessageExpiredVideo => gettext("{sender} pinned a video"),
             MessageVideoNote(_) => gettext("{sender} pinned a video message"),
@@ -10,18 +10,21 @@ use gtk::subclass::prelude::*;
 use once_cell::sync::Lazy;
 use once_cell::unsync::OnceCell;
 use tdlib::enums::ChatType as TdChatType;
+use tdlib::enums::MessageSource;
 use tdlib::enums::Update;
-use tdlib::functions;
 use tdlib::types;
 use tdlib::types::Chat as TelegramChat;
 
 use crate::tdlib::Avatar;
 use crate::tdlib::BasicGroup;
+use crate::tdlib::BoxedChatMemberStatus;
 use crate::tdlib::BoxedChatNotificationSettings;
 use crate::tdlib::BoxedChatPermissions;
 use crate::tdlib::BoxedDraftMessage;
 use crate::tdlib::ChatActionList;
+use crate::tdlib::ForumTopicList;
 use crate::tdlib::Message;
+use crate::tdlib::MessageSender;
 use crate::tdlib::SecretChat;
 use crate::tdlib::Supergroup;
 use crate::tdlib::User;
@@ -91,6 +94,7 @@ mod imp {
         pub(super) is_blocked: Cell<bool>,
         pub(super) title: RefCell<String>,
         pub(super) avatar: RefCell<Option<Avatar>>,
+        pub(super) last_read_inbox_message_id: Cell<i64>,
         pub(super) last_read_outbox_message_id: Cell<i64>,
         pub(super) is_marked_as_unread: Cell<bool>,
         pub(super) last_message: RefCell<Option<Message>>,
@@ -99,9 +103,13 @@ mod imp {
         pub(super) draft_message: RefCell<Option<BoxedDraftMessage>>,
         pub(super) notification_settings: RefCell<Option<BoxedChatNotificationSettings>>,
         pub(super) actions: OnceCell<ChatActionList>,
+        pub(super) forum_topics: OnceCell<ForumTopicList>,
         pub(super) session: WeakRef<Session>,
         pub(super) permissions: RefCell<Option<BoxedChatPermissions>>,
         pub(super) messages: RefCell<HashMap<i64, Message>>,
+        /// The number of views that show the chat, as TDLib doesn't count how many times a chat
+        /// is opened.
+        pub(super) open_count: Cell<u32>,
     }
 
     #[glib::object_subclass]
@@ -120,6 +128,7 @@ mod imp {
                     Signal::builder("deleted-message")
                         .param_types([Message::static_type()])
                         .build(),
+                    Signal::builder("pinned-messages-changed").build(),
                 ]
             });
             SIGNALS.as_ref()
@@ -139,6 +148,9 @@ mod imp {
                     glib::ParamSpecBoxed::builder::<Avatar>("avatar")
                         .read_only()
                         .build(),
+                    glib::ParamSpecInt64::builder("last-read-inbox-message-id")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecInt64::builder("last-read-outbox-message-id")
                         .read_only()
                         .build(),
@@ -185,6 +197,7 @@ mod imp {
                 "is-blocked" => obj.is_blocked().to_value(),
                 "title" => obj.title().to_value(),
                 "avatar" => obj.avatar().to_value(),
+                "last-read-inbox-message-id" => obj.last_read_inbox_message_id().to_value(),
                 "last-read-outbox-message-id" => obj.last_read_outbox_message_id().to_value(),
                 "is-marked-as-unread" => obj.is_marked_as_unread().to_value(),
                 "last-message" => obj.last_message().to_value(),
@@ -222,6 +235,8 @@ impl Chat {
         imp.is_blocked.set(td_chat.is_blocked);
         imp.title.replace(td_chat.title);
         imp.avatar.replace(avatar);
+        imp.last_read_inbox_message_id
+            .set(td_chat.last_read_inbox_message_id);
         imp.last_read_outbox_message_id
             .set(td_chat.last_read_outbox_message_id);
         imp.is_marked_as_unread.set(td_chat.is_marked_as_unread);
@@ -265,29 +280,29 @@ impl Chat {
                 self.set_permissions(BoxedChatPermissions(update.permissions))
             }
             ChatPhoto(update) => self.set_avatar(update.photo.map(Into::into)),
-            ChatReadInbox(update) => self.set_unread_count(update.unread_count),
+            ChatReadInbox(update) => {
+                self.set_last_read_inbox_message_id(update.last_read_inbox_message_id);
+                self.set_unread_count(update.unread_count);
+            }
             ChatReadOutbox(update) => {
                 self.set_last_read_outbox_message_id(update.last_read_outbox_message_id);
             }
             ChatTitle(update) => self.set_title(update.title),
+            ForumTopicInfo(update) => self.forum_topics().handle_update(update),
             ChatUnreadMentionCount(update) => {
                 self.set_unread_mention_count(update.unread_mention_count)
             }
             DeleteMessages(data) => {
-                // FIXME: This should be removed after we notify opened and closed chats to TDLib
-                // See discussion here: https://t.me/tdlibchat/65304
-                if !data.from_cache {
-                    let mut messages = imp.messages.borrow_mut();
-                    let deleted_messages: Vec<Message> = data
-                        .message_ids
-                        .into_iter()
-                        .filter_map(|id| messages.remove(&id))
-                        .collect();
-
-                    drop(messages);
-                    for message in deleted_messages {
-                        self.emit_by_name::<()>("deleted-message", &[&message]);
-                    }
+                let mut messages = imp.messages.borrow_mut();
+                let deleted_messages: Vec<Message> = data
+                    .message_ids
+                    .into_iter()
+                    .filter_map(|id| messages.remove(&id))
+                    .collect();
+
+                drop(messages);
+                for message in deleted_messages {
+                    self.emit_by_name::<()>("deleted-message", &[&message]);
                 }
             }
             MessageContent(ref data) => {
@@ -295,6 +310,11 @@ impl Chat {
                     message.handle_update(update);
                 }
             }
+            MessageContentOpened(ref data) => {
+                if let Some(message) = self.message(data.message_id) {
+                    message.handle_update(update);
+                }
+            }
             MessageEdited(ref data) => {
                 if let Some(message) = self.message(data.message_id) {
                     message.handle_update(update);
@@ -305,6 +325,12 @@ impl Chat {
                     message.handle_update(update);
                 }
             }
+            MessageIsPinned(ref data) => {
+                if let Some(message) = self.message(data.message_id) {
+                    message.handle_update(update);
+                }
+                self.emit_by_name::<()>("pinned-messages-changed", &[]);
+            }
             MessageSendSucceeded(data) => {
                 let mut messages = imp.messages.borrow_mut();
                 let old_message = messages.remove(&data.old_message_id);
@@ -377,6 +403,20 @@ impl Chat {
         self.notify("avatar");
     }
 
+    pub(crate) fn last_read_inbox_message_id(&self) -> i64 {
+        self.imp().last_read_inbox_message_id.get()
+    }
+
+    fn set_last_read_inbox_message_id(&self, last_read_inbox_message_id: i64) {
+        if self.last_read_inbox_message_id() == last_read_inbox_message_id {
+            return;
+        }
+        self.imp()
+            .last_read_inbox_message_id
+            .set(last_read_inbox_message_id);
+        self.notify("last-read-inbox-message-id");
+    }
+
     pub(crate) fn last_read_outbox_message_id(&self) -> i64 {
         self.imp().last_read_outbox_message_id.get()
     }
@@ -476,6 +516,18 @@ impl Chat {
             .get_or_init(|| ChatActionList::from(self))
     }
 
+    /// Returns the topics of the chat. They're only loaded on demand and only available if the
+    /// chat is a forum.
+    pub(crate) fn forum_topics(&self) -> &ForumTopicList {
+        self.imp()
+            .forum_topics
+            .get_or_init(|| ForumTopicList::from(self))
+    }
+
+    pub(crate) fn is_forum(&self) -> bool {
+        matches!(self.type_(), ChatType::Supergroup(supergroup) if supergroup.is_forum())
+    }
+
     pub(crate) fn session(&self) -> Session {
         self.imp().session.upgrade().unwrap()
     }
@@ -520,11 +572,97 @@ impl Chat {
         })
     }
 
+    /// Connects to the signal emitted when a message of the chat is pinned or unpinned.
+    pub(crate) fn connect_pinned_messages_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("pinned-messages-changed", true, move |values| {
+            let obj = values[0].get().unwrap();
+            f(obj);
+            None
+        })
+    }
+
+    /// Returns the status of the user in the chat, if the chat is a group or a channel.
+    pub(crate) fn member_status(&self) -> Option<BoxedChatMemberStatus> {
+        match self.type_() {
+            ChatType::BasicGroup(basic_group) => Some(basic_group.status()),
+            ChatType::Supergroup(supergroup) => Some(supergroup.status()),
+            ChatType::Private(_) | ChatType::Secret(_) => None,
+        }
+    }
+
+    /// Returns whether the user is allowed to ban and restrict the members of the chat and to
+    /// change its default permissions.
+    pub(crate) fn can_restrict_members(&self) -> bool {
+        use tdlib::enums::ChatMemberStatus::*;
+
+        match self.member_status().map(|status| status.0) {
+            Some(Creator(_)) => true,
+            Some(Administrator(data)) => data.rights.can_restrict_members,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the user is allowed to add new administrators to the chat.
+    pub(crate) fn can_promote_members(&self) -> bool {
+        use tdlib::enums::ChatMemberStatus::*;
+
+        match self.member_status().map(|status| status.0) {
+            Some(Creator(_)) => true,
+            Some(Administrator(data)) => data.rights.can_promote_members,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the user is allowed to pin and unpin the messages of the chat.
+    pub(crate) fn can_pin_messages(&self) -> bool {
+        use tdlib::enums::ChatMemberStatus::*;
+
+        let permissions = self.permissions().0;
+        match self.type_() {
+            ChatType::Private(_) => true,
+            ChatType::Secret(_) => false,
+            ChatType::BasicGroup(basic_group) => match basic_group.status().0 {
+                Creator(_) => true,
+                Administrator(data) => data.rights.can_pin_messages,
+                Member => permissions.can_pin_messages,
+                Restricted(data) => {
+                    permissions.can_pin_messages && data.permissions.can_pin_messages
+                }
+                Left | Banned(_) => false,
+            },
+            ChatType::Supergroup(supergroup) => match supergroup.status().0 {
+                Creator(_) => true,
+                // Channel posts are pinned by the administrators who can edit them
+                Administrator(data) if supergroup.is_channel() => data.rights.can_edit_messages,
+                Administrator(data) => data.rights.can_pin_messages,
+                Member if !supergroup.is_channel() => permissions.can_pin_messages,
+                Restricted(data) if !supergroup.is_channel() => {
+                    permissions.can_pin_messages && data.permissions.can_pin_messages
+                }
+                _ => false,
+            },
+        }
+    }
+
     /// Returns the `Message` of the specified id, if present in the cache.
     pub(crate) fn message(&self, message_id: i64) -> Option<Message> {
         self.imp().messages.borrow().get(&message_id).cloned()
     }
 
+    /// Returns the cached `Message` of a message received from TDLib, adding it to the cache if
+    /// it isn't there yet.
+    pub(crate) fn message_from_td_object(&self, message: types::Message) -> Message {
+        self.imp()
+            .messages
+            .borrow_mut()
+            .entry(message.id)
+            .or_insert_with(|| Message::new(message, self))
+            .clone()
+    }
+
     /// Returns the `Message` of the specified id, if present in the cache. Otherwise it
     /// fetches it from the server and then it returns the result.
     pub(crate) async fn fetch_message(&self, message_id: i64) -> Result<Message, types::Error> {
@@ -532,8 +670,11 @@ impl Chat {
             return Ok(message);
         }
 
-        let client_id = self.session().client_id();
-        let result = functions::get_message(self.id(), message_id, client_id).await;
+        let result = self
+            .session()
+            .backend()
+            .get_message(self.id(), message_id)
+            .await;
 
         result.map(|r| {
             let tdlib::enums::Message::Message(message) = r;
@@ -547,15 +688,21 @@ impl Chat {
         })
     }
 
+    /// Returns the messages of the chat, starting from the one before `from_message_id` (or from
+    /// the last one, if it's 0) shifted by `offset`. A negative `offset` also returns the newer
+    /// messages, including the `from_message_id` one. The messages are returned from the newest
+    /// to the oldest.
     pub(crate) async fn get_chat_history(
         &self,
         from_message_id: i64,
+        offset: i32,
         limit: i32,
     ) -> Result<Vec<Message>, types::Error> {
-        let client_id = self.session().client_id();
-        let result =
-            functions::get_chat_history(self.id(), from_message_id, 0, limit, false, client_id)
-                .await;
+        let result = self
+            .session()
+            .backend()
+            .get_chat_history(self.id(), from_message_id, offset, limit, false)
+            .await;
 
         let tdlib::enums::Messages::Messages(data) = result?;
 
@@ -574,24 +721,265 @@ impl Chat {
         Ok(loaded_messages)
     }
 
-    pub(crate) async fn mark_as_read(&self) -> Result<(), types::Error> {
-        if let Some(message) = self.last_message() {
-            functions::view_messages(
+    /// Returns the chat and the id of the message thread that contains the replies to the
+    /// specified message. The thread of a channel post is found in the discussion group of the
+    /// channel.
+    pub(crate) async fn get_message_thread(
+        &self,
+        message_id: i64,
+    ) -> Result<(Chat, i64), types::Error> {
+        let session = self.session();
+        let tdlib::enums::MessageThreadInfo::MessageThreadInfo(data) = session
+            .backend()
+            .get_message_thread(self.id(), message_id)
+            .await?;
+
+        Ok((session.chat(data.chat_id), data.message_thread_id))
+    }
+
+    /// Returns the messages of a message thread or forum topic, with the same paging rules as
+    /// [`Self::get_chat_history()`].
+    pub(crate) async fn get_message_thread_history(
+        &self,
+        message_thread_id: i64,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+    ) -> Result<Vec<Message>, types::Error> {
+        let result = self
+            .session()
+            .backend()
+            .get_message_thread_history(
                 self.id(),
-                vec![message.id()],
-                None,
-                true,
-                self.session().client_id(),
+                message_thread_id,
+                from_message_id,
+                offset,
+                limit,
             )
+            .await;
+
+        let tdlib::enums::Messages::Messages(data) = result?;
+
+        let mut messages = self.imp().messages.borrow_mut();
+        let loaded_messages: Vec<Message> = data
+            .messages
+            .into_iter()
+            .flatten()
+            .map(|m| Message::new(m, self))
+            .collect();
+
+        for message in &loaded_messages {
+            messages.insert(message.id(), message.clone());
+        }
+
+        Ok(loaded_messages)
+    }
+
+    /// Searches for the messages of the chat that contain `query`, that match `filter` and that
+    /// were sent by `sender_id`, if specified, starting from the one before `from_message_id` (or
+    /// from the last one, if it's 0) shifted by `offset`. The messages are returned from the
+    /// newest to the oldest.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn search_messages(
+        &self,
+        query: String,
+        sender_id: Option<tdlib::enums::MessageSender>,
+        from_message_id: i64,
+        offset: i32,
+        limit: i32,
+        filter: Option<tdlib::enums::SearchMessagesFilter>,
+        message_thread_id: i64,
+    ) -> Result<Vec<Message>, types::Error> {
+        let result = self
+            .session()
+            .backend()
+            .search_chat_messages(
+                self.id(),
+                query,
+                sender_id,
+                from_message_id,
+                offset,
+                limit,
+                filter,
+                message_thread_id,
+            )
+            .await;
+
+        let tdlib::enums::FoundChatMessages::FoundChatMessages(data) = result?;
+
+        Ok(data
+            .messages
+            .into_iter()
+            .map(|m| self.message_from_td_object(m))
+            .collect())
+    }
+
+    /// Returns the last message that was sent no later than the specified unix time.
+    pub(crate) async fn message_by_date(&self, date: i32) -> Result<Message, types::Error> {
+        let tdlib::enums::Message::Message(message) = self
+            .session()
+            .backend()
+            .get_chat_message_by_date(self.id(), date)
             .await?;
+
+        Ok(self.message_from_td_object(message))
+    }
+
+    /// Fetches a page of the members of a group that match `filter`, along with the total number
+    /// of matching members. Basic groups ignore the filter and the page, as all of their members
+    /// are returned at once.
+    pub(crate) async fn members(
+        &self,
+        filter: Option<tdlib::enums::SupergroupMembersFilter>,
+        offset: i32,
+        limit: i32,
+    ) -> Result<types::ChatMembers, types::Error> {
+        let backend = self.session().backend();
+
+        match self.type_() {
+            ChatType::BasicGroup(basic_group) => {
+                let tdlib::enums::BasicGroupFullInfo::BasicGroupFullInfo(full_info) =
+                    backend.get_basic_group_full_info(basic_group.id()).await?;
+
+                Ok(types::ChatMembers {
+                    total_count: full_info.members.len() as i32,
+                    members: full_info.members,
+                })
+            }
+            ChatType::Supergroup(supergroup) => {
+                let tdlib::enums::ChatMembers::ChatMembers(data) = backend
+                    .get_supergroup_members(supergroup.id(), filter, offset, limit)
+                    .await?;
+
+                Ok(data)
+            }
+            _ => Ok(types::ChatMembers {
+                total_count: 0,
+                members: Vec::new(),
+            }),
         }
+    }
+
+    /// Searches for the members of the chat whose name or username contains `query`.
+    pub(crate) async fn search_members(
+        &self,
+        query: String,
+        limit: i32,
+    ) -> Result<Vec<MessageSender>, types::Error> {
+        let session = self.session();
+        let tdlib::enums::ChatMembers::ChatMembers(data) = session
+            .backend()
+            .search_chat_members(self.id(), query, limit, None)
+            .await?;
 
-        functions::toggle_chat_is_marked_as_unread(self.id(), false, self.session().client_id())
+        Ok(data
+            .members
+            .into_iter()
+            .map(|member| MessageSender::from_td_object(&member.member_id, &session))
+            .collect())
+    }
+
+    /// Changes the status of a member of the chat, which is how members are promoted, restricted
+    /// and unbanned.
+    pub(crate) async fn set_member_status(
+        &self,
+        member: &MessageSender,
+        status: tdlib::enums::ChatMemberStatus,
+    ) -> Result<(), types::Error> {
+        self.session()
+            .backend()
+            .set_chat_member_status(self.id(), member.to_td_object(), status)
+            .await
+    }
+
+    /// Bans a member from the chat until `banned_until_date`, or forever if it's 0. Banning a
+    /// member of a basic group just removes them from it.
+    pub(crate) async fn ban_member(
+        &self,
+        member: &MessageSender,
+        banned_until_date: i32,
+        revoke_messages: bool,
+    ) -> Result<(), types::Error> {
+        self.session()
+            .backend()
+            .ban_chat_member(
+                self.id(),
+                member.to_td_object(),
+                banned_until_date,
+                revoke_messages,
+            )
+            .await
+    }
+
+    /// Changes the permissions that all the members of the chat have by default.
+    pub(crate) async fn set_default_permissions(
+        &self,
+        permissions: types::ChatPermissions,
+    ) -> Result<(), types::Error> {
+        self.session()
+            .backend()
+            .set_chat_permissions(self.id(), permissions)
             .await
     }
 
+    /// Tells TDLib that the chat is shown, so that it keeps its messages up to date.
+    pub(crate) async fn open(&self) -> Result<(), types::Error> {
+        let open_count = &self.imp().open_count;
+        open_count.set(open_count.get() + 1);
+        if open_count.get() > 1 {
+            return Ok(());
+        }
+
+        self.session().backend().open_chat(self.id()).await
+    }
+
+    /// Tells TDLib that the chat isn't shown anymore, once every view that opened it closed it.
+    pub(crate) async fn close(&self) -> Result<(), types::Error> {
+        let open_count = &self.imp().open_count;
+        open_count.set(open_count.get().saturating_sub(1));
+        if open_count.get() > 0 {
+            return Ok(());
+        }
+
+        self.session().backend().close_chat(self.id()).await
+    }
+
+    /// Marks the specified messages as viewed, which also reads them and all the older
+    /// messages.
+    pub(crate) async fn view_messages(
+        &self,
+        message_ids: Vec<i64>,
+        source: MessageSource,
+    ) -> Result<(), types::Error> {
+        self.session()
+            .backend()
+            .view_messages(self.id(), message_ids, Some(source), false)
+            .await
+    }
+
+    pub(crate) async fn mark_as_read(&self) -> Result<(), types::Error> {
+        if let Some(message) = self.last_message() {
+            self.session()
+                .backend()
+                .view_messages(self.id(), vec![message.id()], None, true)
+                .await?;
+        }
+
+        self.remove_unread_mark().await
+    }
+
     pub(crate) async fn mark_as_unread(&self) -> Result<(), types::Error> {
-        functions::toggle_chat_is_marked_as_unread(self.id(), true, self.session().client_id())
+        self.session()
+            .backend()
+            .toggle_chat_is_marked_as_unread(self.id(), true)
+            .await
+    }
+
+    /// Removes the mark set by [`Chat::mark_as_unread()`], without reading any message.
+    pub(crate) async fn remove_unread_mark(&self) -> Result<(), types::Error> {
+        self.session()
+            .backend()
+            .toggle_chat_is_marked_as_unread(self.id(), false)
             .await
     }
 }
@@ -2,6 +2,7 @@ use std::cell::Cell;
 use std::cell::RefCell;
 use std::cell::RefMut;
 use std::collections::BTreeMap;
+use std::rc::Rc;
 
 use glib::clone;
 use gtk::gio;
@@ -9,9 +10,9 @@ use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use once_cell::sync::Lazy;
-use tdlib::functions;
 use tdlib::types::ChatPosition as TdChatPosition;
 
+use crate::backend::Backend;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatListItem;
 use crate::utils::spawn;
@@ -83,9 +84,9 @@ impl ChatList {
         glib::Object::new()
     }
 
-    pub(crate) fn fetch(&self, client_id: i32) {
+    pub(crate) fn fetch(&self, backend: Rc<dyn Backend>) {
         spawn(clone!(@weak self as obj => async move {
-            let result = functions::load_chats(None, 20, client_id).await;
+            let result = backend.load_chats(None, 20).await;
 
             if let Err(err) = result {
                 // Error 404 means that all chats have been loaded
@@ -93,7 +94,7 @@ impl ChatList {
                     log::error!("Received an error for LoadChats: {}", err.code);
                 }
             } else {
-                obj.fetch(client_id);
+                obj.fetch(backend);
             }
         }));
     }
@@ -0,0 +1,189 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::WeakRef;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use once_cell::sync::Lazy;
+use tdlib::types;
+
+use crate::tdlib::Chat;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct ForumTopic {
+        pub(super) chat: WeakRef<Chat>,
+        pub(super) message_thread_id: Cell<i64>,
+        pub(super) name: RefCell<String>,
+        pub(super) icon_color: Cell<i32>,
+        pub(super) is_general: Cell<bool>,
+        pub(super) is_closed: Cell<bool>,
+        pub(super) is_pinned: Cell<bool>,
+        pub(super) unread_count: Cell<i32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ForumTopic {
+        const NAME: &'static str = "ForumTopic";
+        type Type = super::ForumTopic;
+    }
+
+    impl ObjectImpl for ForumTopic {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Chat>("chat")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecInt64::builder("message-thread-id")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecString::builder("name").read_only().build(),
+                    glib::ParamSpecInt::builder("icon-color")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecBoolean::builder("is-general")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecBoolean::builder("is-closed")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecBoolean::builder("is-pinned")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecInt::builder("unread-count")
+                        .read_only()
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                "message-thread-id" => obj.message_thread_id().to_value(),
+                "name" => obj.name().to_value(),
+                "icon-color" => obj.icon_color().to_value(),
+                "is-general" => obj.is_general().to_value(),
+                "is-closed" => obj.is_closed().to_value(),
+                "is-pinned" => obj.is_pinned().to_value(),
+                "unread-count" => obj.unread_count().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct ForumTopic(ObjectSubclass<imp::ForumTopic>);
+}
+
+impl ForumTopic {
+    pub(crate) fn new(td_topic: types::ForumTopic, chat: &Chat) -> Self {
+        let topic: ForumTopic = glib::Object::new();
+        let imp = topic.imp();
+
+        imp.chat.set(Some(chat));
+        imp.message_thread_id.set(td_topic.info.message_thread_id);
+        imp.name.replace(td_topic.info.name);
+        imp.icon_color.set(td_topic.info.icon.color);
+        imp.is_general.set(td_topic.info.is_general);
+        imp.is_closed.set(td_topic.info.is_closed);
+        imp.is_pinned.set(td_topic.is_pinned);
+        imp.unread_count.set(td_topic.unread_count);
+
+        topic
+    }
+
+    /// Updates the topic with the data of a topic with the same message thread id.
+    pub(crate) fn update(&self, td_topic: types::ForumTopic) {
+        self.update_info(td_topic.info);
+        self.set_is_pinned(td_topic.is_pinned);
+        self.set_unread_count(td_topic.unread_count);
+    }
+
+    pub(crate) fn update_info(&self, info: types::ForumTopicInfo) {
+        self.set_name(info.name);
+        self.set_icon_color(info.icon.color);
+        self.set_is_closed(info.is_closed);
+    }
+
+    pub(crate) fn chat(&self) -> Chat {
+        self.imp().chat.upgrade().unwrap()
+    }
+
+    pub(crate) fn message_thread_id(&self) -> i64 {
+        self.imp().message_thread_id.get()
+    }
+
+    pub(crate) fn name(&self) -> String {
+        self.imp().name.borrow().clone()
+    }
+
+    fn set_name(&self, name: String) {
+        if self.name() == name {
+            return;
+        }
+        self.imp().name.replace(name);
+        self.notify("name");
+    }
+
+    /// Returns the color of the topic icon, in RGB24 format.
+    pub(crate) fn icon_color(&self) -> i32 {
+        self.imp().icon_color.get()
+    }
+
+    fn set_icon_color(&self, icon_color: i32) {
+        if self.icon_color() == icon_color {
+            return;
+        }
+        self.imp().icon_color.set(icon_color);
+        self.notify("icon-color");
+    }
+
+    pub(crate) fn is_general(&self) -> bool {
+        self.imp().is_general.get()
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.imp().is_closed.get()
+    }
+
+    fn set_is_closed(&self, is_closed: bool) {
+        if self.is_closed() == is_closed {
+            return;
+        }
+        self.imp().is_closed.set(is_closed);
+        self.notify("is-closed");
+    }
+
+    pub(crate) fn is_pinned(&self) -> bool {
+        self.imp().is_pinned.get()
+    }
+
+    fn set_is_pinned(&self, is_pinned: bool) {
+        if self.is_pinned() == is_pinned {
+            return;
+        }
+        self.imp().is_pinned.set(is_pinned);
+        self.notify("is-pinned");
+    }
+
+    pub(crate) fn unread_count(&self) -> i32 {
+        self.imp().unread_count.get()
+    }
+
+    fn set_unread_count(&self, unread_count: i32) {
+        if self.unread_count() == unread_count {
+            return;
+        }
+        self.imp().unread_count.set(unread_count);
+        self.notify("unread-count");
+    }
+}
@@ -0,0 +1,230 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::mem;
+
+use glib::WeakRef;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use indexmap::IndexMap;
+use tdlib::enums;
+use tdlib::types;
+use tdlib::types::Error as TdError;
+
+use crate::tdlib::Chat;
+use crate::tdlib::ForumTopic;
+use crate::tdlib::Message;
+
+/// The offsets of the next page of topics, as returned by `getForumTopics`.
+#[derive(Debug, Default, Clone, Copy)]
+struct PageOffset {
+    date: i32,
+    message_id: i64,
+    message_thread_id: i64,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct ForumTopicList {
+        pub(super) list: RefCell<IndexMap<i64, ForumTopic>>,
+        pub(super) chat: WeakRef<Chat>,
+        pub(super) next_offset: Cell<Option<PageOffset>>,
+        pub(super) is_loading: Cell<bool>,
+        /// Incremented on every reload, so that pages requested before it are discarded.
+        pub(super) generation: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ForumTopicList {
+        const NAME: &'static str = "ForumTopicList";
+        type Type = super::ForumTopicList;
+        type Interfaces = (gio::ListModel,);
+    }
+
+    impl ObjectImpl for ForumTopicList {}
+
+    impl ListModelImpl for ForumTopicList {
+        fn item_type(&self) -> glib::Type {
+            ForumTopic::static_type()
+        }
+
+        fn n_items(&self) -> u32 {
+            self.list.borrow().len() as u32
+        }
+
+        fn item(&self, position: u32) -> Option<glib::Object> {
+            self.list
+                .borrow()
+                .get_index(position as usize)
+                .map(|(_, obj)| obj.upcast_ref())
+                .cloned()
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct ForumTopicList(ObjectSubclass<imp::ForumTopicList>)
+        @implements gio::ListModel;
+}
+
+impl From<&Chat> for ForumTopicList {
+    fn from(chat: &Chat) -> Self {
+        let forum_topic_list: ForumTopicList = glib::Object::new();
+        forum_topic_list.imp().chat.set(Some(chat));
+        forum_topic_list
+    }
+}
+
+impl ForumTopicList {
+    pub(crate) fn handle_update(&self, update: types::UpdateForumTopicInfo) {
+        if let Some(topic) = self.topic(update.info.message_thread_id) {
+            topic.update_info(update.info);
+        }
+    }
+
+    pub(crate) fn chat(&self) -> Chat {
+        self.imp().chat.upgrade().unwrap()
+    }
+
+    pub(crate) fn topic(&self, message_thread_id: i64) -> Option<ForumTopic> {
+        self.imp().list.borrow().get(&message_thread_id).cloned()
+    }
+
+    /// Returns the loaded topic that contains the message, if any.
+    pub(crate) fn topic_of(&self, message: &Message) -> Option<ForumTopic> {
+        let list = self.imp().list.borrow();
+        if message.is_topic_message() {
+            list.get(&message.message_thread_id()).cloned()
+        } else {
+            // The General topic contains the messages that don't belong to another topic
+            list.values().find(|topic| topic.is_general()).cloned()
+        }
+    }
+
+    /// Loads the first page of topics again, so that the order of the topics is up to date.
+    /// Topics that were already loaded keep their objects.
+    pub(crate) async fn reload(&self, limit: i32) -> Result<bool, TdError> {
+        let imp = self.imp();
+
+        // A page that is being loaded is based on the old offset, so it must be discarded
+        imp.generation.set(imp.generation.get() + 1);
+        imp.is_loading.set(false);
+        imp.next_offset.set(None);
+
+        self.load_more(limit).await
+    }
+
+    /// Loads the next page of topics.
+    ///
+    /// Returns `true` when more topics can be loaded.
+    pub(crate) async fn load_more(&self, limit: i32) -> Result<bool, TdError> {
+        let imp = self.imp();
+
+        if imp.is_loading.get() {
+            return Ok(true);
+        }
+
+        let offset = imp.next_offset.get();
+        let is_first_page = offset.is_none();
+        let offset = offset.unwrap_or_default();
+
+        let chat = self.chat();
+        let generation = imp.generation.get();
+
+        imp.is_loading.set(true);
+
+        let result = chat
+            .session()
+            .backend()
+            .get_forum_topics(
+                chat.id(),
+                String::new(),
+                offset.date,
+                offset.message_id,
+                offset.message_thread_id,
+                limit,
+            )
+            .await;
+
+        if imp.generation.get() != generation {
+            // The list was reloaded in the meantime
+            return Ok(true);
+        }
+
+        imp.is_loading.set(false);
+
+        let enums::ForumTopics::ForumTopics(data) = result?;
+
+        imp.next_offset.set(Some(PageOffset {
+            date: data.next_offset_date,
+            message_id: data.next_offset_message_id,
+            message_thread_id: data.next_offset_message_thread_id,
+        }));
+
+        let can_load_more = !data.topics.is_empty() && data.next_offset_message_id != 0;
+
+        if is_first_page {
+            self.replace_topics(data.topics);
+        } else {
+            self.append_topics(data.topics);
+        }
+
+        Ok(can_load_more)
+    }
+
+    fn replace_topics(&self, td_topics: Vec<types::ForumTopic>) {
+        let chat = self.chat();
+
+        let (removed, added) = {
+            let mut list = self.imp().list.borrow_mut();
+            let mut old_list = mem::take(&mut *list);
+            let removed = old_list.len();
+
+            for td_topic in td_topics {
+                let message_thread_id = td_topic.info.message_thread_id;
+                let topic = match old_list.remove(&message_thread_id) {
+                    Some(topic) => {
+                        topic.update(td_topic);
+                        topic
+                    }
+                    None => ForumTopic::new(td_topic, &chat),
+                };
+                list.insert(message_thread_id, topic);
+            }
+
+            (removed, list.len())
+        };
+
+        self.items_changed(0, removed as u32, added as u32);
+    }
+
+    fn append_topics(&self, td_topics: Vec<types::ForumTopic>) {
+        let chat = self.chat();
+
+        let (position, added) = {
+            let mut list = self.imp().list.borrow_mut();
+            let position = list.len();
+
+            for td_topic in td_topics {
+                match list.get(&td_topic.info.message_thread_id) {
+                    Some(topic) => topic.update(td_topic),
+                    None => {
+                        list.insert(
+                            td_topic.info.message_thread_id,
+                            ForumTopic::new(td_topic, &chat),
+                        );
+                    }
+                }
+            }
+
+            (position, list.len() - position)
+        };
+
+        if added > 0 {
+            self.items_changed(position as u32, 0, added as u32);
+        }
+    }
+}
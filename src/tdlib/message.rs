@@ -7,9 +7,11 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
+use tdlib::enums;
 use tdlib::enums::MessageSender as TdMessageSender;
+use tdlib::enums::ReactionType;
 use tdlib::enums::Update;
-use tdlib::functions;
+use tdlib::types;
 use tdlib::types::Error as TdError;
 use tdlib::types::Message as TdMessage;
 
@@ -23,6 +25,9 @@ use crate::tdlib::MessageInteractionInfo;
 use crate::tdlib::User;
 use crate::Session;
 
+/// The number of reactions shown in a row of the reaction picker.
+const REACTIONS_ROW_SIZE: i32 = 7;
+
 #[derive(Clone, Debug, glib::Boxed)]
 #[boxed_type(name = "MessageSender")]
 pub(crate) enum MessageSender {
@@ -57,6 +62,16 @@ impl MessageSender {
             Self::Chat(chat) => chat.id(),
         }
     }
+    pub(crate) fn to_td_object(&self) -> TdMessageSender {
+        match self {
+            Self::User(user) => {
+                TdMessageSender::User(types::MessageSenderUser { user_id: user.id() })
+            }
+            Self::Chat(chat) => {
+                TdMessageSender::Chat(types::MessageSenderChat { chat_id: chat.id() })
+            }
+        }
+    }
 }
 
 mod imp {
@@ -70,15 +85,19 @@ mod imp {
         pub(super) can_be_edited: Cell<bool>,
         pub(super) can_be_deleted_only_for_self: Cell<bool>,
         pub(super) can_be_deleted_for_all_users: Cell<bool>,
+        pub(super) can_get_message_thread: Cell<bool>,
         pub(super) sending_state: RefCell<Option<BoxedMessageSendingState>>,
         pub(super) date: Cell<i32>,
         pub(super) content: RefCell<Option<BoxedMessageContent>>,
         pub(super) is_edited: Cell<bool>,
+        pub(super) is_pinned: Cell<bool>,
         pub(super) interaction_info: OnceCell<MessageInteractionInfo>,
         pub(super) chat: WeakRef<Chat>,
         pub(super) forward_info: OnceCell<Option<MessageForwardInfo>>,
         pub(super) reply_in_chat_id: Cell<i64>,
         pub(super) reply_to_message_id: Cell<i64>,
+        pub(super) message_thread_id: Cell<i64>,
+        pub(super) is_topic_message: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -107,6 +126,9 @@ mod imp {
                     glib::ParamSpecBoolean::builder("can-be-deleted-for-all-users")
                         .read_only()
                         .build(),
+                    glib::ParamSpecBoolean::builder("can-get-message-thread")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecBoxed::builder::<BoxedMessageSendingState>("sending-state")
                         .read_only()
                         .build(),
@@ -117,6 +139,9 @@ mod imp {
                     glib::ParamSpecBoolean::builder("is-edited")
                         .read_only()
                         .build(),
+                    glib::ParamSpecBoolean::builder("is-pinned")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecObject::builder::<MessageInteractionInfo>("interaction-info")
                         .read_only()
                         .build(),
@@ -132,6 +157,9 @@ mod imp {
                     glib::ParamSpecInt64::builder("reply-to-message-id")
                         .read_only()
                         .build(),
+                    glib::ParamSpecInt64::builder("message-thread-id")
+                        .read_only()
+                        .build(),
                 ]
             });
             PROPERTIES.as_ref()
@@ -147,15 +175,18 @@ mod imp {
                 "can-be-edited" => obj.can_be_edited().to_value(),
                 "can-be-deleted-only-for-self" => obj.can_be_deleted_only_for_self().to_value(),
                 "can-be-deleted-for-all-users" => obj.can_be_deleted_for_all_users().to_value(),
+                "can-get-message-thread" => obj.can_get_message_thread().to_value(),
                 "sending-state" => obj.sending_state().to_value(),
                 "date" => obj.date().to_value(),
                 "content" => obj.content().to_value(),
                 "is-edited" => obj.is_edited().to_value(),
+                "is-pinned" => obj.is_pinned().to_value(),
                 "interaction-info" => obj.interaction_info().to_value(),
                 "chat" => obj.chat().to_value(),
                 "forward-info" => obj.forward_info().to_value(),
                 "reply-in-chat-id" => obj.reply_in_chat_id().to_value(),
                 "reply-to-message-id" => obj.reply_to_message_id().to_value(),
+                "message-thread-id" => obj.message_thread_id().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -187,10 +218,13 @@ impl Message {
             .set(td_message.can_be_deleted_only_for_self);
         imp.can_be_deleted_for_all_users
             .set(td_message.can_be_deleted_for_all_users);
+        imp.can_get_message_thread
+            .set(td_message.can_get_message_thread);
         imp.sending_state.replace(sending_state);
         imp.date.set(td_message.date);
         imp.content.replace(Some(content));
         imp.is_edited.set(is_edited);
+        imp.is_pinned.set(td_message.is_pinned);
         imp.interaction_info
             .set(MessageInteractionInfo::from(td_message.interaction_info))
             .unwrap();
@@ -198,6 +232,8 @@ impl Message {
         imp.forward_info.set(forward_info).unwrap();
         imp.reply_in_chat_id.set(td_message.reply_in_chat_id);
         imp.reply_to_message_id.set(td_message.reply_to_message_id);
+        imp.message_thread_id.set(td_message.message_thread_id);
+        imp.is_topic_message.set(td_message.is_topic_message);
 
         message
     }
@@ -208,7 +244,19 @@ impl Message {
                 let new_content = BoxedMessageContent(data.new_content);
                 self.set_content(new_content);
             }
+            Update::MessageContentOpened(_) => {
+                let mut content = self.content().0;
+                match content {
+                    enums::MessageContent::MessageVoiceNote(ref mut data) => {
+                        data.is_listened = true
+                    }
+                    enums::MessageContent::MessageVideoNote(ref mut data) => data.is_viewed = true,
+                    _ => return,
+                }
+                self.set_content(BoxedMessageContent(content));
+            }
             Update::MessageEdited(data) => self.set_is_edited(data.edit_date > 0),
+            Update::MessageIsPinned(data) => self.set_is_pinned(data.is_pinned),
             Update::MessageInteractionInfo(data) => {
                 self.interaction_info().update(data.interaction_info)
             }
@@ -217,13 +265,83 @@ impl Message {
     }
 
     pub(crate) async fn delete(&self, revoke: bool) -> Result<(), TdError> {
-        functions::delete_messages(
-            self.chat().id(),
-            vec![self.id()],
-            revoke,
-            self.chat().session().client_id(),
-        )
-        .await
+        let chat = self.chat();
+        chat.session()
+            .backend()
+            .delete_messages(chat.id(), vec![self.id()], revoke)
+            .await
+    }
+
+    /// Adds the specified reaction to the message or removes it, if it's already chosen.
+    pub(crate) async fn toggle_reaction(&self, reaction_type: ReactionType) -> Result<(), TdError> {
+        let chat = self.chat();
+        let backend = chat.session().backend();
+
+        if self
+            .interaction_info()
+            .reactions()
+            .is_chosen(&reaction_type)
+        {
+            backend
+                .remove_message_reaction(chat.id(), self.id(), reaction_type)
+                .await
+        } else {
+            backend
+                .add_message_reaction(chat.id(), self.id(), reaction_type, false, true)
+                .await
+        }
+    }
+
+    /// Pins the message in its chat or unpins it, if it's already pinned.
+    pub(crate) async fn toggle_is_pinned(&self) -> Result<(), TdError> {
+        let chat = self.chat();
+        let backend = chat.session().backend();
+
+        if self.is_pinned() {
+            backend.unpin_chat_message(chat.id(), self.id()).await
+        } else {
+            backend
+                .pin_chat_message(chat.id(), self.id(), false, false)
+                .await
+        }
+    }
+
+    /// Informs TDLib that the content of the message was opened, which marks voice and video
+    /// notes as listened or viewed.
+    pub(crate) async fn open_content(&self) -> Result<(), TdError> {
+        let chat = self.chat();
+        chat.session()
+            .backend()
+            .open_message_content(chat.id(), self.id())
+            .await
+    }
+
+    /// Votes for the specified options of the poll of the message. Passing no options retracts
+    /// the vote.
+    pub(crate) async fn set_poll_answer(&self, option_ids: Vec<i32>) -> Result<(), TdError> {
+        let chat = self.chat();
+        chat.session()
+            .backend()
+            .set_poll_answer(chat.id(), self.id(), option_ids)
+            .await
+    }
+
+    /// Returns the reactions that can be added to the message, excluding the ones that need
+    /// Telegram Premium.
+    pub(crate) async fn available_reactions(&self) -> Result<Vec<ReactionType>, TdError> {
+        let chat = self.chat();
+        let enums::AvailableReactions::AvailableReactions(reactions) = chat
+            .session()
+            .backend()
+            .get_message_available_reactions(chat.id(), self.id(), REACTIONS_ROW_SIZE)
+            .await?;
+
+        Ok(reactions
+            .top_reactions
+            .into_iter()
+            .filter(|reaction| !reaction.needs_premium)
+            .map(|reaction| reaction.r#type)
+            .collect())
     }
 
     pub(crate) fn id(&self) -> i64 {
@@ -250,6 +368,10 @@ impl Message {
         self.imp().can_be_deleted_for_all_users.get()
     }
 
+    pub(crate) fn can_get_message_thread(&self) -> bool {
+        self.imp().can_get_message_thread.get()
+    }
+
     pub(crate) fn sending_state(&self) -> Option<BoxedMessageSendingState> {
         self.imp().sending_state.borrow().clone()
     }
@@ -282,6 +404,18 @@ impl Message {
         self.notify("is-edited");
     }
 
+    pub(crate) fn is_pinned(&self) -> bool {
+        self.imp().is_pinned.get()
+    }
+
+    fn set_is_pinned(&self, is_pinned: bool) {
+        if self.is_pinned() == is_pinned {
+            return;
+        }
+        self.imp().is_pinned.set(is_pinned);
+        self.notify("is-pinned");
+    }
+
     pub(crate) fn interaction_info(&self) -> &MessageInteractionInfo {
         self.imp().interaction_info.get().unwrap()
     }
@@ -309,6 +443,16 @@ impl Message {
         self.imp().reply_to_message_id.get()
     }
 
+    /// Returns the id of the message thread or forum topic the message belongs to, or 0.
+    pub(crate) fn message_thread_id(&self) -> i64 {
+        self.imp().message_thread_id.get()
+    }
+
+    /// Returns whether the message belongs to a forum topic other than the General one.
+    pub(crate) fn is_topic_message(&self) -> bool {
+        self.imp().is_topic_message.get()
+    }
+
     pub(crate) fn sender_name_expression(&self) -> gtk::Expression {
         match self.sender() {
             MessageSender::User(user) => {
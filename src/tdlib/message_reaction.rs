@@ -0,0 +1,114 @@
+use std::cell::Cell;
+
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use tdlib::enums::ReactionType;
+use tdlib::types;
+
+#[derive(Clone, Debug, PartialEq, glib::Boxed)]
+#[boxed_type(name = "BoxedReactionType")]
+pub(crate) struct BoxedReactionType(pub(crate) ReactionType);
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct MessageReaction {
+        pub(super) type_: OnceCell<BoxedReactionType>,
+        pub(super) total_count: Cell<i32>,
+        pub(super) is_chosen: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageReaction {
+        const NAME: &'static str = "MessageReaction";
+        type Type = super::MessageReaction;
+    }
+
+    impl ObjectImpl for MessageReaction {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecBoxed::builder::<BoxedReactionType>("type")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecInt::builder("total-count")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecBoolean::builder("is-chosen")
+                        .read_only()
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "type" => obj.type_().to_value(),
+                "total-count" => obj.total_count().to_value(),
+                "is-chosen" => obj.is_chosen().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageReaction(ObjectSubclass<imp::MessageReaction>);
+}
+
+impl From<types::MessageReaction> for MessageReaction {
+    fn from(td_reaction: types::MessageReaction) -> Self {
+        let reaction: MessageReaction = glib::Object::new();
+        let imp = reaction.imp();
+
+        imp.type_
+            .set(BoxedReactionType(td_reaction.r#type))
+            .unwrap();
+        imp.total_count.set(td_reaction.total_count);
+        imp.is_chosen.set(td_reaction.is_chosen);
+
+        reaction
+    }
+}
+
+impl MessageReaction {
+    pub(crate) fn update(&self, td_reaction: types::MessageReaction) {
+        self.set_total_count(td_reaction.total_count);
+        self.set_is_chosen(td_reaction.is_chosen);
+    }
+
+    pub(crate) fn type_(&self) -> &BoxedReactionType {
+        self.imp().type_.get().unwrap()
+    }
+
+    pub(crate) fn total_count(&self) -> i32 {
+        self.imp().total_count.get()
+    }
+
+    fn set_total_count(&self, total_count: i32) {
+        if self.total_count() == total_count {
+            return;
+        }
+        self.imp().total_count.set(total_count);
+        self.notify("total-count");
+    }
+
+    pub(crate) fn is_chosen(&self) -> bool {
+        self.imp().is_chosen.get()
+    }
+
+    fn set_is_chosen(&self, is_chosen: bool) {
+        if self.is_chosen() == is_chosen {
+            return;
+        }
+        self.imp().is_chosen.set(is_chosen);
+        self.notify("is-chosen");
+    }
+}
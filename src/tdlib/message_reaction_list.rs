@@ -0,0 +1,124 @@
+use std::cell::RefCell;
+
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use tdlib::enums::ReactionType;
+use tdlib::types;
+
+use crate::tdlib::MessageReaction;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct MessageReactionList {
+        pub(super) list: RefCell<Vec<MessageReaction>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageReactionList {
+        const NAME: &'static str = "MessageReactionList";
+        type Type = super::MessageReactionList;
+        type Interfaces = (gio::ListModel,);
+    }
+
+    impl ObjectImpl for MessageReactionList {}
+
+    impl ListModelImpl for MessageReactionList {
+        fn item_type(&self) -> glib::Type {
+            MessageReaction::static_type()
+        }
+
+        fn n_items(&self) -> u32 {
+            self.list.borrow().len() as u32
+        }
+
+        fn item(&self, position: u32) -> Option<glib::Object> {
+            self.list
+                .borrow()
+                .get(position as usize)
+                .map(|obj| obj.upcast_ref())
+                .cloned()
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageReactionList(ObjectSubclass<imp::MessageReactionList>)
+        @implements gio::ListModel;
+}
+
+impl Default for MessageReactionList {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl MessageReactionList {
+    /// Replaces the reactions of the list, keeping the objects of the reactions that were already
+    /// present. Only the reactions that were added, removed or moved are reported as changed, so
+    /// that the widgets bound to the other ones are not recreated.
+    pub(crate) fn update(&self, td_reactions: Vec<types::MessageReaction>) {
+        let imp = self.imp();
+
+        // Remove the reactions that are not present anymore, starting from the last one so that
+        // the positions of the ones still to check don't change
+        for position in (0..imp.list.borrow().len()).rev() {
+            let is_removed = {
+                let list = imp.list.borrow();
+                let reaction_type = &list[position].type_().0;
+                !td_reactions
+                    .iter()
+                    .any(|td_reaction| &td_reaction.r#type == reaction_type)
+            };
+
+            if is_removed {
+                imp.list.borrow_mut().remove(position);
+                self.items_changed(position as u32, 1, 0);
+            }
+        }
+
+        // Update the remaining reactions and add the new ones, following the new order
+        for (position, td_reaction) in td_reactions.into_iter().enumerate() {
+            let current_position = imp
+                .list
+                .borrow()
+                .iter()
+                .skip(position)
+                .position(|reaction| reaction.type_().0 == td_reaction.r#type)
+                .map(|offset| position + offset);
+
+            match current_position {
+                Some(current_position) if current_position == position => {
+                    let reaction = imp.list.borrow()[position].clone();
+                    reaction.update(td_reaction);
+                }
+                Some(current_position) => {
+                    let reaction = imp.list.borrow_mut().remove(current_position);
+                    self.items_changed(current_position as u32, 1, 0);
+
+                    reaction.update(td_reaction);
+                    imp.list.borrow_mut().insert(position, reaction);
+                    self.items_changed(position as u32, 0, 1);
+                }
+                None => {
+                    imp.list
+                        .borrow_mut()
+                        .insert(position, MessageReaction::from(td_reaction));
+                    self.items_changed(position as u32, 0, 1);
+                }
+            }
+        }
+    }
+
+    /// Returns whether the current user has chosen the specified reaction.
+    pub(crate) fn is_chosen(&self, reaction_type: &ReactionType) -> bool {
+        self.imp()
+            .list
+            .borrow()
+            .iter()
+            .any(|reaction| reaction.is_chosen() && &reaction.type_().0 == reaction_type)
+    }
+}
@@ -7,9 +7,13 @@ mod chat_list;
 mod chat_list_item;
 mod country_info;
 mod country_list;
+mod forum_topic;
+mod forum_topic_list;
 mod message;
 mod message_forward_info;
 mod message_interaction_info;
+mod message_reaction;
+mod message_reaction_list;
 mod secret_chat;
 mod sponsored_message;
 mod supergroup;
@@ -37,11 +41,16 @@ pub(crate) use self::chat_list::ChatList;
 pub(crate) use self::chat_list_item::ChatListItem;
 pub(crate) use self::country_info::CountryInfo;
 pub(crate) use self::country_list::CountryList;
+pub(crate) use self::forum_topic::ForumTopic;
+pub(crate) use self::forum_topic_list::ForumTopicList;
 pub(crate) use self::message::Message;
 pub(crate) use self::message::MessageSender;
 pub(crate) use self::message_forward_info::MessageForwardInfo;
 pub(crate) use self::message_forward_info::MessageForwardOrigin;
 pub(crate) use self::message_interaction_info::MessageInteractionInfo;
+pub(crate) use self::message_reaction::BoxedReactionType;
+pub(crate) use self::message_reaction::MessageReaction;
+pub(crate) use self::message_reaction_list::MessageReactionList;
 pub(crate) use self::secret_chat::SecretChat;
 pub(crate) use self::secret_chat::SecretChatState;
 pub(crate) use self::sponsored_message::SponsoredMessage;
@@ -18,6 +18,7 @@ mod imp {
         pub(super) username: RefCell<String>,
         pub(super) member_count: Cell<i32>,
         pub(super) is_channel: Cell<bool>,
+        pub(super) is_forum: Cell<bool>,
         pub(super) status: RefCell<Option<BoxedChatMemberStatus>>,
     }
 
@@ -41,6 +42,9 @@ mod imp {
                     glib::ParamSpecBoolean::builder("is-channel")
                         .read_only()
                         .build(),
+                    glib::ParamSpecBoolean::builder("is-forum")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecBoxed::builder::<BoxedChatMemberStatus>("status")
                         .read_only()
                         .build(),
@@ -57,6 +61,7 @@ mod imp {
                 "username" => obj.username().to_value(),
                 "member-count" => obj.member_count().to_value(),
                 "is-channel" => obj.is_channel().to_value(),
+                "is-forum" => obj.is_forum().to_value(),
                 "status" => obj.status().to_value(),
                 _ => unimplemented!(),
             }
@@ -83,6 +88,7 @@ impl Supergroup {
         imp.username.replace(username);
         imp.member_count.set(td_supergroup.member_count);
         imp.is_channel.set(td_supergroup.is_channel);
+        imp.is_forum.set(td_supergroup.is_forum);
         imp.status.replace(Some(status));
 
         supergroup
@@ -96,6 +102,7 @@ impl Supergroup {
                 .unwrap_or_default(),
         );
         self.set_member_count(td_supergroup.member_count);
+        self.set_is_forum(td_supergroup.is_forum);
         self.set_status(BoxedChatMemberStatus(td_supergroup.status));
     }
 
@@ -131,6 +138,19 @@ impl Supergroup {
         self.imp().is_channel.get()
     }
 
+    /// Returns whether the messages of the supergroup are organized in topics.
+    pub(crate) fn is_forum(&self) -> bool {
+        self.imp().is_forum.get()
+    }
+
+    fn set_is_forum(&self, is_forum: bool) {
+        if self.is_forum() == is_forum {
+            return;
+        }
+        self.imp().is_forum.set(is_forum);
+        self.notify("is-forum");
+    }
+
     pub(crate) fn status(&self) -> BoxedChatMemberStatus {
         self.imp().status.borrow().to_owned().unwrap()
     }
@@ -0,0 +1,125 @@
+//! Recording and replaying of the TDLib update stream.
+//!
+//! An update log is a JSON lines file in which every line contains the id of the client that
+//! received the update and the update itself, serialized in the same format TDLib uses:
+//!
+//! ```json
+//! {"client_id":1,"update":{"@type":"updateAuthorizationState", ...}}
+//! ```
+//!
+//! Logs are written by [`UpdateRecorder`] when the application is started with
+//! `--record-updates` and fed back into the [`Window`](crate::window::Window) by
+//! [`UpdateReplay`] when it is started with `--replay-updates`. While replaying, no TDLib client
+//! is ever created and the data directory is left untouched, so that the models can be exercised
+//! deterministically and without network access.
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Lines;
+use std::io::Write;
+use std::path::Path;
+
+use serde_json::json;
+use serde_json::Value;
+use tdlib::enums::Update;
+use thiserror::Error;
+
+use crate::APPLICATION_OPTS;
+
+#[derive(Error, Debug)]
+pub(crate) enum UpdateLogError {
+    #[error("I/O error: {0:?}")]
+    Io(std::io::Error),
+    #[error("Invalid JSON on line {0}: {1:?}")]
+    Json(usize, serde_json::Error),
+    #[error("Missing client id on line {0}")]
+    MissingClientId(usize),
+}
+
+/// Returns whether the application is replaying a recorded update log instead of talking to
+/// TDLib.
+pub(crate) fn is_replaying() -> bool {
+    APPLICATION_OPTS
+        .get()
+        .map(|opts| opts.replay_updates.is_some())
+        .unwrap_or_default()
+}
+
+/// Writes every received update to an update log.
+#[derive(Debug)]
+pub(crate) struct UpdateRecorder {
+    writer: BufWriter<File>,
+}
+
+impl UpdateRecorder {
+    /// Creates the update log at the specified path, truncating it if it already exists.
+    pub(crate) fn create(path: &Path) -> Result<Self, UpdateLogError> {
+        let file = File::create(path).map_err(UpdateLogError::Io)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub(crate) fn record(&mut self, update: &Update, client_id: i32) -> Result<(), UpdateLogError> {
+        let line = json!({
+            "client_id": client_id,
+            "update": update,
+        });
+
+        writeln!(self.writer, "{line}").map_err(UpdateLogError::Io)?;
+
+        // Flush after every update, so that the log is complete even if the application crashes.
+        self.writer.flush().map_err(UpdateLogError::Io)
+    }
+}
+
+/// Iterator over the `(Update, client_id)` pairs of an update log, in the order they were
+/// recorded.
+#[derive(Debug)]
+pub(crate) struct UpdateReplay {
+    lines: Lines<BufReader<File>>,
+    line_number: usize,
+}
+
+impl UpdateReplay {
+    pub(crate) fn open(path: &Path) -> Result<Self, UpdateLogError> {
+        let file = File::open(path).map_err(UpdateLogError::Io)?;
+        Ok(Self {
+            lines: BufReader::new(file).lines(),
+            line_number: 0,
+        })
+    }
+
+    fn parse_line(&self, line: &str) -> Result<(Update, i32), UpdateLogError> {
+        let mut value: Value =
+            serde_json::from_str(line).map_err(|e| UpdateLogError::Json(self.line_number, e))?;
+
+        let client_id = value["client_id"]
+            .as_i64()
+            .ok_or(UpdateLogError::MissingClientId(self.line_number))?;
+        let update = serde_json::from_value(value["update"].take())
+            .map_err(|e| UpdateLogError::Json(self.line_number, e))?;
+
+        Ok((update, client_id as i32))
+    }
+}
+
+impl Iterator for UpdateReplay {
+    type Item = Result<(Update, i32), UpdateLogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            self.line_number += 1;
+
+            match line {
+                // Allow empty lines, e.g. at the end of a hand edited log
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => return Some(self.parse_line(&line)),
+                Err(e) => return Some(Err(UpdateLogError::Io(e))),
+            }
+        }
+    }
+}
@@ -156,6 +156,16 @@ pub(crate) fn human_friendly_duration(mut seconds: i32) -> String {
     }
 }
 
+/// Formats a duration like a media player does, e.g. "2:05" or "1:02:05".
+pub(crate) fn format_duration(seconds: i32) -> String {
+    let hours = seconds / (60 * 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
 /// Returns the Paper Plane data directory (e.g. /home/bob/.local/share/paper-plane).
 pub(crate) fn data_dir() -> &'static PathBuf {
     &APPLICATION_OPTS.get().unwrap().data_dir
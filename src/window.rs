@@ -20,8 +20,11 @@ use crate::session_manager::SessionManager;
 use crate::strings;
 use crate::tdlib::ChatType;
 use crate::tdlib::Message;
+use crate::update_log::UpdateRecorder;
+use crate::update_log::UpdateReplay;
 use crate::utils::spawn;
 use crate::Application;
+use crate::APPLICATION_OPTS;
 
 mod imp {
     use super::*;
@@ -147,8 +150,37 @@ impl Window {
 
     fn start_tdlib_thread(&self) {
         let sender = self.create_update_channel();
+        let application_opts = APPLICATION_OPTS.get().unwrap();
+
+        if let Some(path) = application_opts.replay_updates.clone() {
+            thread::spawn(move || match UpdateReplay::open(&path) {
+                Ok(replay) => {
+                    for entry in replay {
+                        match entry {
+                            Ok((update, client_id)) => sender.send((update, client_id)).unwrap(),
+                            Err(e) => log::warn!("Skipping invalid update log entry: {}", e),
+                        }
+                    }
+                }
+                Err(e) => log::error!("Could not open update log {}: {}", path.display(), e),
+            });
+            return;
+        }
+
+        let mut recorder = application_opts.record_updates.as_ref().and_then(|path| {
+            UpdateRecorder::create(path)
+                .map_err(|e| log::error!("Could not create update log {}: {}", path.display(), e))
+                .ok()
+        });
+
         thread::spawn(move || loop {
             if let Some((update, client_id)) = tdlib::receive() {
+                if let Some(recorder) = recorder.as_mut() {
+                    if let Err(e) = recorder.record(&update, client_id) {
+                        log::warn!("Error recording an update: {}", e);
+                    }
+                }
+
                 sender.send((update, client_id)).unwrap();
             }
         });